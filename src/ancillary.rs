@@ -0,0 +1,849 @@
+/*!
+ * # Ancillary crate
+ *
+ * Defines typed content of the registered ancillary chunks as specified in [PNG specification](http://www.libpng.org/pub/png/spec/1.2/PNG-Chunks.html#C.Ancillary-chunks).
+ *
+ * Textual and EXIF chunks are handled by their own crates and are not part of this one.
+ *
+ */
+
+use crate::image_header::{ColorType, ImageHeader};
+use crate::text;
+use crate::text::TextError;
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SEPARATOR: u8 = 0;
+const COMPRESSION_METHOD_DEFLATE: u8 = 0;
+/// Scale of the fixed point values of the gamma and the chromaticities
+const FIXED_POINT_SCALE: f64 = 100000.0;
+const INCHES_PER_METER: f64 = 0.0254;
+const ICC_HEADER_LEN: usize = 128;
+
+#[derive(Debug)]
+pub enum AncillaryError {
+    InvalidLength(&'static str),
+    InvalidValue(&'static str),
+    Text(TextError),
+}
+
+impl From<TextError> for AncillaryError {
+    fn from(item: TextError) -> AncillaryError {
+        AncillaryError::Text(item)
+    }
+}
+
+impl fmt::Display for AncillaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AncillaryError::InvalidLength(chunk_type) => {
+                write!(f, "Invalid length of the {} chunk data.", chunk_type)
+            }
+            AncillaryError::InvalidValue(chunk_type) => {
+                write!(f, "Invalid value in the {} chunk data.", chunk_type)
+            }
+            AncillaryError::Text(ref err) => write!(f, "Text error: {}", err),
+        }
+    }
+}
+
+impl error::Error for AncillaryError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            AncillaryError::InvalidLength(_) => None,
+            AncillaryError::InvalidValue(_) => None,
+            AncillaryError::Text(ref err) => Some(err),
+        }
+    }
+}
+
+fn be_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([data[offset], data[offset + 1]])
+}
+
+fn be_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+/// Splits the data at the first null separator, returning the data before and after the separator
+fn split_at_separator<'a>(
+    data: &'a [u8],
+    chunk_type: &'static str,
+) -> Result<(String, &'a [u8]), AncillaryError> {
+    let index = data
+        .iter()
+        .position(|&b| b == SEPARATOR)
+        .ok_or(AncillaryError::InvalidValue(chunk_type))?;
+    Ok((
+        String::from_utf8_lossy(&data[..index]).into_owned(),
+        &data[index + 1..],
+    ))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderingIntent {
+    Perceptual = 0,
+    RelativeColorimetric = 1,
+    Saturation = 2,
+    AbsoluteColorimetric = 3,
+}
+
+impl fmt::Display for RenderingIntent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderingIntent::Perceptual => write!(f, "perceptual"),
+            RenderingIntent::RelativeColorimetric => write!(f, "relative colorimetric"),
+            RenderingIntent::Saturation => write!(f, "saturation"),
+            RenderingIntent::AbsoluteColorimetric => write!(f, "absolute colorimetric"),
+        }
+    }
+}
+
+/// Chromaticity coordinates of the white point and the primaries, stored as the value times 100000
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chromaticities {
+    pub white: (u32, u32),
+    pub red: (u32, u32),
+    pub green: (u32, u32),
+    pub blue: (u32, u32),
+}
+
+/// Embedded ICC profile, kept in its decompressed form
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IccProfile {
+    pub name: String,
+    pub profile: Vec<u8>,
+}
+
+impl IccProfile {
+    /// Returns the text of the `desc` tag of the profile, supporting both the ICC v2 and v4 encodings
+    pub fn description(&self) -> Option<String> {
+        let profile = &self.profile;
+        if profile.len() < ICC_HEADER_LEN + 4 {
+            return None;
+        }
+
+        let tag_count = be_u32(profile, ICC_HEADER_LEN) as usize;
+        let (offset, size) = (0..tag_count)
+            .map(|i| ICC_HEADER_LEN + 4 + i * 12)
+            .take_while(|entry| entry + 12 <= profile.len())
+            .find(|&entry| &profile[entry..entry + 4] == b"desc")
+            .map(|entry| {
+                (
+                    be_u32(profile, entry + 4) as usize,
+                    be_u32(profile, entry + 8) as usize,
+                )
+            })?;
+        let tag = profile.get(offset..offset.checked_add(size)?)?;
+
+        match tag.get(..4)? {
+            b"desc" => {
+                let length = be_u32(tag.get(..12)?, 8) as usize;
+                let ascii = tag.get(12..12 + length)?;
+                let ascii = ascii.split(|&b| b == 0).next()?;
+                Some(String::from_utf8_lossy(ascii).into_owned())
+            }
+            b"mluc" => {
+                let header = tag.get(..28)?;
+                let length = be_u32(header, 20) as usize;
+                let offset = be_u32(header, 24) as usize;
+                let utf16: Vec<u16> = tag
+                    .get(offset..offset + length)?
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                    .collect();
+                Some(String::from_utf16_lossy(&utf16))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Background {
+    Palette(u8),
+    Gray(u16),
+    Rgb(u16, u16, u16),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transparency {
+    Palette(Vec<u8>),
+    Gray(u16),
+    Rgb(u16, u16, u16),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Unknown = 0,
+    Meter = 1,
+}
+
+/// Intended pixel size or aspect ratio
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhysicalDimensions {
+    pub x: u32,
+    pub y: u32,
+    pub unit: Unit,
+}
+
+impl PhysicalDimensions {
+    /// Creates the dimensions from the number of dots per inch in both directions
+    pub fn from_dpi(dpi: u32) -> PhysicalDimensions {
+        let pixels_per_meter = (dpi as f64 / INCHES_PER_METER).round() as u32;
+        PhysicalDimensions {
+            x: pixels_per_meter,
+            y: pixels_per_meter,
+            unit: Unit::Meter,
+        }
+    }
+
+    /// Returns the number of dots per inch in both directions, if the unit is known
+    pub fn dpi(&self) -> Option<(f64, f64)> {
+        match self.unit {
+            Unit::Meter => Some((
+                self.x as f64 * INCHES_PER_METER,
+                self.y as f64 * INCHES_PER_METER,
+            )),
+            Unit::Unknown => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaletteEntry {
+    pub red: u16,
+    pub green: u16,
+    pub blue: u16,
+    pub alpha: u16,
+    pub frequency: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuggestedPalette {
+    pub name: String,
+    pub sample_depth: u8,
+    pub entries: Vec<PaletteEntry>,
+}
+
+/// Time of the last image modification, in UTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Time {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl Time {
+    const SECONDS_PER_DAY: u64 = 86400;
+
+    /// Returns the current time
+    pub fn now() -> Time {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Time::from_unix(seconds)
+    }
+
+    /// Returns the number of days of the month, taking leap years of the Gregorian calendar into account
+    fn days_in_month(year: u16, month: u8) -> u8 {
+        match month {
+            2 if year.is_multiple_of(4)
+                && (!year.is_multiple_of(100) || year.is_multiple_of(400)) =>
+            {
+                29
+            }
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    /// Converts the number of seconds since the Unix epoch to the calendar time
+    pub fn from_unix(seconds: u64) -> Time {
+        let days = (seconds / Time::SECONDS_PER_DAY) as i64;
+        let time_of_day = seconds % Time::SECONDS_PER_DAY;
+
+        // Civil from days algorithm, counting eras of 400 years from the 1st of March of the year 0
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let day_of_era = z - era * 146097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_from_march = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+        let month = match month_from_march < 10 {
+            true => month_from_march + 3,
+            false => month_from_march - 9,
+        };
+        let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+        Time {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+            hour: (time_of_day / 3600) as u8,
+            minute: (time_of_day / 60 % 60) as u8,
+            second: (time_of_day % 60) as u8,
+        }
+    }
+}
+
+impl FromStr for Time {
+    type Err = AncillaryError;
+
+    /// Parses the time in the `YYYY-MM-DDTHH:MM:SS` format, with an optional trailing `Z`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_suffix('Z').unwrap_or(s);
+        let (date, time) = s
+            .split_once(['T', ' '])
+            .ok_or(AncillaryError::InvalidValue("tIME"))?;
+        let date: Vec<&str> = date.split('-').collect();
+        let time: Vec<&str> = time.split(':').collect();
+        let (date, time) = match (date.as_slice(), time.as_slice()) {
+            ([year, month, day], [hour, minute, second]) => {
+                ([*year, *month, *day], [*hour, *minute, *second])
+            }
+            _ => return Err(AncillaryError::InvalidValue("tIME")),
+        };
+        let number = |value: &str, max: u16| -> Result<u16, AncillaryError> {
+            match value.parse::<u16>() {
+                Ok(number) if number <= max => Ok(number),
+                _ => Err(AncillaryError::InvalidValue("tIME")),
+            }
+        };
+
+        let time = Time {
+            year: number(date[0], u16::MAX)?,
+            month: number(date[1], 12)? as u8,
+            day: number(date[2], 31)? as u8,
+            hour: number(time[0], 23)? as u8,
+            minute: number(time[1], 59)? as u8,
+            // Leap second is allowed by the specification
+            second: number(time[2], 60)? as u8,
+        };
+        match time.month == 0
+            || time.day == 0
+            || time.day > Time::days_in_month(time.year, time.month)
+        {
+            true => Err(AncillaryError::InvalidValue("tIME")),
+            false => Ok(time),
+        }
+    }
+}
+
+/// Typed content of a registered ancillary chunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ancillary {
+    /// Image gamma times 100000
+    Gamma(u32),
+    Chromaticities(Chromaticities),
+    StandardRgb(RenderingIntent),
+    IccProfile(IccProfile),
+    SignificantBits(Vec<u8>),
+    Background(Background),
+    Histogram(Vec<u16>),
+    Transparency(Transparency),
+    PhysicalDimensions(PhysicalDimensions),
+    SuggestedPalette(SuggestedPalette),
+    Time(Time),
+}
+
+impl Ancillary {
+    /// Parses the data of a chunk, returning `None` for chunk types that are not handled by this crate
+    ///
+    /// The image header is needed to tell apart the `sBIT` and `tRNS` layouts of some color types, without it
+    /// the layout is guessed from the data length.
+    pub fn parse(
+        chunk_type: &str,
+        data: &[u8],
+        header: Option<&ImageHeader>,
+    ) -> Result<Option<Ancillary>, AncillaryError> {
+        let ancillary = match chunk_type {
+            "gAMA" => {
+                expect_length(data, 4, "gAMA")?;
+                Ancillary::Gamma(be_u32(data, 0))
+            }
+            "cHRM" => {
+                expect_length(data, 32, "cHRM")?;
+                let point = |i: usize| (be_u32(data, i * 8), be_u32(data, i * 8 + 4));
+                Ancillary::Chromaticities(Chromaticities {
+                    white: point(0),
+                    red: point(1),
+                    green: point(2),
+                    blue: point(3),
+                })
+            }
+            "sRGB" => {
+                expect_length(data, 1, "sRGB")?;
+                Ancillary::StandardRgb(match data[0] {
+                    0 => RenderingIntent::Perceptual,
+                    1 => RenderingIntent::RelativeColorimetric,
+                    2 => RenderingIntent::Saturation,
+                    3 => RenderingIntent::AbsoluteColorimetric,
+                    _ => return Err(AncillaryError::InvalidValue("sRGB")),
+                })
+            }
+            "iCCP" => {
+                let (name, rest) = split_at_separator(data, "iCCP")?;
+                match rest {
+                    [COMPRESSION_METHOD_DEFLATE, profile @ ..] => {
+                        Ancillary::IccProfile(IccProfile {
+                            name,
+                            profile: text::inflate(profile)?,
+                        })
+                    }
+                    _ => return Err(AncillaryError::InvalidValue("iCCP")),
+                }
+            }
+            "sBIT" => {
+                let expected = match header.map(|h| h.color_type) {
+                    Some(ColorType::Indexed) => 3,
+                    Some(color_type) => color_type.channels(),
+                    None => data.len(),
+                };
+                if data.is_empty() || data.len() > 4 {
+                    return Err(AncillaryError::InvalidLength("sBIT"));
+                }
+                expect_length(data, expected, "sBIT")?;
+                Ancillary::SignificantBits(data.to_vec())
+            }
+            "bKGD" => Ancillary::Background(match data.len() {
+                1 => Background::Palette(data[0]),
+                2 => Background::Gray(be_u16(data, 0)),
+                6 => Background::Rgb(be_u16(data, 0), be_u16(data, 2), be_u16(data, 4)),
+                _ => return Err(AncillaryError::InvalidLength("bKGD")),
+            }),
+            "hIST" => {
+                if !data.len().is_multiple_of(2) {
+                    return Err(AncillaryError::InvalidLength("hIST"));
+                }
+                Ancillary::Histogram(
+                    (0..data.len())
+                        .step_by(2)
+                        .map(|i| be_u16(data, i))
+                        .collect(),
+                )
+            }
+            "tRNS" => Ancillary::Transparency(match (header.map(|h| h.color_type), data.len()) {
+                (Some(ColorType::Grayscale), 2) | (None, 2) => Transparency::Gray(be_u16(data, 0)),
+                (Some(ColorType::Rgb), 6) | (None, 6) => {
+                    Transparency::Rgb(be_u16(data, 0), be_u16(data, 2), be_u16(data, 4))
+                }
+                (Some(ColorType::Indexed), _) | (None, _) => Transparency::Palette(data.to_vec()),
+                _ => return Err(AncillaryError::InvalidLength("tRNS")),
+            }),
+            "pHYs" => {
+                expect_length(data, 9, "pHYs")?;
+                Ancillary::PhysicalDimensions(PhysicalDimensions {
+                    x: be_u32(data, 0),
+                    y: be_u32(data, 4),
+                    unit: match data[8] {
+                        0 => Unit::Unknown,
+                        1 => Unit::Meter,
+                        _ => return Err(AncillaryError::InvalidValue("pHYs")),
+                    },
+                })
+            }
+            "sPLT" => {
+                let (name, rest) = split_at_separator(data, "sPLT")?;
+                let (sample_depth, entries) = match rest {
+                    [8, entries @ ..] if entries.len().is_multiple_of(6) => {
+                        (8, entries.chunks_exact(6))
+                    }
+                    [16, entries @ ..] if entries.len().is_multiple_of(10) => {
+                        (16, entries.chunks_exact(10))
+                    }
+                    _ => return Err(AncillaryError::InvalidLength("sPLT")),
+                };
+                let entries = entries
+                    .map(|entry| match sample_depth {
+                        8 => PaletteEntry {
+                            red: entry[0] as u16,
+                            green: entry[1] as u16,
+                            blue: entry[2] as u16,
+                            alpha: entry[3] as u16,
+                            frequency: be_u16(entry, 4),
+                        },
+                        _ => PaletteEntry {
+                            red: be_u16(entry, 0),
+                            green: be_u16(entry, 2),
+                            blue: be_u16(entry, 4),
+                            alpha: be_u16(entry, 6),
+                            frequency: be_u16(entry, 8),
+                        },
+                    })
+                    .collect();
+                Ancillary::SuggestedPalette(SuggestedPalette {
+                    name,
+                    sample_depth,
+                    entries,
+                })
+            }
+            "tIME" => {
+                expect_length(data, 7, "tIME")?;
+                Ancillary::Time(Time {
+                    year: be_u16(data, 0),
+                    month: data[2],
+                    day: data[3],
+                    hour: data[4],
+                    minute: data[5],
+                    second: data[6],
+                })
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(ancillary))
+    }
+
+    pub fn chunk_type(&self) -> &'static str {
+        match self {
+            Ancillary::Gamma(_) => "gAMA",
+            Ancillary::Chromaticities(_) => "cHRM",
+            Ancillary::StandardRgb(_) => "sRGB",
+            Ancillary::IccProfile(_) => "iCCP",
+            Ancillary::SignificantBits(_) => "sBIT",
+            Ancillary::Background(_) => "bKGD",
+            Ancillary::Histogram(_) => "hIST",
+            Ancillary::Transparency(_) => "tRNS",
+            Ancillary::PhysicalDimensions(_) => "pHYs",
+            Ancillary::SuggestedPalette(_) => "sPLT",
+            Ancillary::Time(_) => "tIME",
+        }
+    }
+
+    pub fn as_bytes(&self) -> Result<Vec<u8>, AncillaryError> {
+        let bytes = match self {
+            Ancillary::Gamma(gamma) => gamma.to_be_bytes().to_vec(),
+            Ancillary::Chromaticities(c) => [c.white, c.red, c.green, c.blue]
+                .iter()
+                .flat_map(|(x, y)| x.to_be_bytes().into_iter().chain(y.to_be_bytes()))
+                .collect(),
+            Ancillary::StandardRgb(intent) => vec![*intent as u8],
+            Ancillary::IccProfile(icc) => icc
+                .name
+                .bytes()
+                .chain([SEPARATOR, COMPRESSION_METHOD_DEFLATE])
+                .chain(text::deflate(&icc.profile)?)
+                .collect(),
+            Ancillary::SignificantBits(bits) => bits.clone(),
+            Ancillary::Background(Background::Palette(index)) => vec![*index],
+            Ancillary::Background(Background::Gray(gray))
+            | Ancillary::Transparency(Transparency::Gray(gray)) => gray.to_be_bytes().to_vec(),
+            Ancillary::Background(Background::Rgb(r, g, b))
+            | Ancillary::Transparency(Transparency::Rgb(r, g, b)) => [r, g, b]
+                .iter()
+                .flat_map(|sample| sample.to_be_bytes())
+                .collect(),
+            Ancillary::Histogram(frequencies) => frequencies
+                .iter()
+                .flat_map(|frequency| frequency.to_be_bytes())
+                .collect(),
+            Ancillary::Transparency(Transparency::Palette(alphas)) => alphas.clone(),
+            Ancillary::PhysicalDimensions(p) => {
+                p.x.to_be_bytes()
+                    .into_iter()
+                    .chain(p.y.to_be_bytes())
+                    .chain([p.unit as u8])
+                    .collect()
+            }
+            Ancillary::SuggestedPalette(palette) => {
+                let mut bytes: Vec<u8> = palette
+                    .name
+                    .bytes()
+                    .chain([SEPARATOR, palette.sample_depth])
+                    .collect();
+                for entry in palette.entries.iter() {
+                    let samples = [entry.red, entry.green, entry.blue, entry.alpha];
+                    match palette.sample_depth {
+                        8 => bytes.extend(samples.iter().map(|&sample| sample as u8)),
+                        _ => bytes.extend(samples.iter().flat_map(|sample| sample.to_be_bytes())),
+                    }
+                    bytes.extend(entry.frequency.to_be_bytes());
+                }
+                bytes
+            }
+            Ancillary::Time(t) => t
+                .year
+                .to_be_bytes()
+                .into_iter()
+                .chain([t.month, t.day, t.hour, t.minute, t.second])
+                .collect(),
+        };
+
+        Ok(bytes)
+    }
+}
+
+fn expect_length(
+    data: &[u8],
+    length: usize,
+    chunk_type: &'static str,
+) -> Result<(), AncillaryError> {
+    match data.len() == length {
+        true => Ok(()),
+        false => Err(AncillaryError::InvalidLength(chunk_type)),
+    }
+}
+
+impl fmt::Display for Ancillary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let point = |(x, y): (u32, u32)| {
+            format!(
+                "({:.5}, {:.5})",
+                x as f64 / FIXED_POINT_SCALE,
+                y as f64 / FIXED_POINT_SCALE
+            )
+        };
+
+        match self {
+            Ancillary::Gamma(gamma) => {
+                write!(f, "Gamma: {:.5}", *gamma as f64 / FIXED_POINT_SCALE)
+            }
+            Ancillary::Chromaticities(c) => write!(
+                f,
+                "White point: {}, Red: {}, Green: {}, Blue: {}",
+                point(c.white),
+                point(c.red),
+                point(c.green),
+                point(c.blue)
+            ),
+            Ancillary::StandardRgb(intent) => write!(f, "Rendering intent: {}", intent),
+            Ancillary::IccProfile(icc) => write!(
+                f,
+                "Profile name: {}, Profile: {} bytes, Description: {}",
+                icc.name,
+                icc.profile.len(),
+                icc.description().unwrap_or_else(|| "unknown".to_owned())
+            ),
+            Ancillary::SignificantBits(bits) => write!(
+                f,
+                "Significant bits: {}",
+                bits.iter()
+                    .map(|b| b.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Ancillary::Background(Background::Palette(index)) => {
+                write!(f, "Background: palette index {}", index)
+            }
+            Ancillary::Background(Background::Gray(gray)) => write!(f, "Background: gray {}", gray),
+            Ancillary::Background(Background::Rgb(r, g, b)) => {
+                write!(f, "Background: RGB ({}, {}, {})", r, g, b)
+            }
+            Ancillary::Histogram(frequencies) => {
+                write!(f, "Histogram: {} entries", frequencies.len())
+            }
+            Ancillary::Transparency(Transparency::Palette(alphas)) => {
+                write!(f, "Transparency: {} palette alpha values", alphas.len())
+            }
+            Ancillary::Transparency(Transparency::Gray(gray)) => {
+                write!(f, "Transparency: gray {}", gray)
+            }
+            Ancillary::Transparency(Transparency::Rgb(r, g, b)) => {
+                write!(f, "Transparency: RGB ({}, {}, {})", r, g, b)
+            }
+            Ancillary::PhysicalDimensions(p) => match p.dpi() {
+                Some((x, y)) => write!(
+                    f,
+                    "Pixels per meter: {} x {} (DPI: {:.0} x {:.0})",
+                    p.x, p.y, x, y
+                ),
+                None => write!(f, "Pixel aspect ratio: {}:{}", p.x, p.y),
+            },
+            Ancillary::SuggestedPalette(palette) => write!(
+                f,
+                "Suggested palette: {}, sample depth {}, {} entries",
+                palette.name,
+                palette.sample_depth,
+                palette.entries.len()
+            ),
+            Ancillary::Time(t) => write!(
+                f,
+                "Last modification: {:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+                t.year, t.month, t.day, t.hour, t.minute, t.second
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(chunk_type: &str, data: &[u8]) -> Ancillary {
+        let ancillary = Ancillary::parse(chunk_type, data, None).unwrap().unwrap();
+        assert_eq!(ancillary.chunk_type(), chunk_type);
+        assert_eq!(ancillary.as_bytes().unwrap(), data);
+        ancillary
+    }
+
+    #[test]
+    fn test_gamma() {
+        let gamma = roundtrip("gAMA", &45455u32.to_be_bytes());
+        assert_eq!(gamma, Ancillary::Gamma(45455));
+        assert_eq!(gamma.to_string(), "Gamma: 0.45455");
+    }
+
+    #[test]
+    fn test_chromaticities() {
+        let data: Vec<u8> = [31270u32, 32900, 64000, 33000, 30000, 60000, 15000, 6000]
+            .iter()
+            .flat_map(|v| v.to_be_bytes())
+            .collect();
+        let chromaticities = roundtrip("cHRM", &data);
+        assert!(chromaticities
+            .to_string()
+            .starts_with("White point: (0.31270, 0.32900)"));
+    }
+
+    #[test]
+    fn test_standard_rgb() {
+        assert_eq!(
+            roundtrip("sRGB", &[0]),
+            Ancillary::StandardRgb(RenderingIntent::Perceptual)
+        );
+        assert!(Ancillary::parse("sRGB", &[4], None).is_err());
+    }
+
+    #[test]
+    fn test_icc_profile() {
+        let mut profile = vec![0; ICC_HEADER_LEN];
+        profile.extend(1u32.to_be_bytes());
+        let offset = (profile.len() + 12) as u32;
+        let mut tag = b"desc\0\0\0\0".to_vec();
+        tag.extend(5u32.to_be_bytes());
+        tag.extend(b"Test\0");
+        profile.extend(b"desc");
+        profile.extend(offset.to_be_bytes());
+        profile.extend((tag.len() as u32).to_be_bytes());
+        profile.extend(tag);
+
+        let mut data = b"ICC\0\0".to_vec();
+        data.extend(text::deflate(&profile).unwrap());
+        match Ancillary::parse("iCCP", &data, None).unwrap().unwrap() {
+            Ancillary::IccProfile(icc) => {
+                assert_eq!(icc.name, "ICC");
+                assert_eq!(icc.profile, profile);
+                assert_eq!(icc.description().unwrap(), "Test");
+            }
+            other => panic!("Unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_significant_bits() {
+        let header = ImageHeader::try_from(&[0, 0, 0, 1, 0, 0, 0, 1, 8, 6, 0, 0, 0][..]).unwrap();
+        assert!(Ancillary::parse("sBIT", &[8, 8, 8, 8], Some(&header)).is_ok());
+        assert!(Ancillary::parse("sBIT", &[8, 8, 8], Some(&header)).is_err());
+        roundtrip("sBIT", &[5, 6, 5]);
+    }
+
+    #[test]
+    fn test_background_and_transparency() {
+        assert_eq!(
+            roundtrip("bKGD", &[0, 1, 0, 2, 0, 3]),
+            Ancillary::Background(Background::Rgb(1, 2, 3))
+        );
+        let header = ImageHeader::try_from(&[0, 0, 0, 1, 0, 0, 0, 1, 8, 3, 0, 0, 0][..]).unwrap();
+        assert_eq!(
+            Ancillary::parse("tRNS", &[0, 255], Some(&header))
+                .unwrap()
+                .unwrap(),
+            Ancillary::Transparency(Transparency::Palette(vec![0, 255]))
+        );
+        assert_eq!(
+            roundtrip("tRNS", &[0, 255]),
+            Ancillary::Transparency(Transparency::Gray(255))
+        );
+    }
+
+    #[test]
+    fn test_histogram() {
+        assert_eq!(
+            roundtrip("hIST", &[0, 1, 1, 0]),
+            Ancillary::Histogram(vec![1, 256])
+        );
+        assert!(Ancillary::parse("hIST", &[0], None).is_err());
+    }
+
+    #[test]
+    fn test_physical_dimensions() {
+        let dimensions = PhysicalDimensions::from_dpi(300);
+        assert_eq!(dimensions.x, 11811);
+        let bytes = Ancillary::PhysicalDimensions(dimensions)
+            .as_bytes()
+            .unwrap();
+        let parsed = roundtrip("pHYs", &bytes);
+        assert_eq!(
+            parsed.to_string(),
+            "Pixels per meter: 11811 x 11811 (DPI: 300 x 300)"
+        );
+    }
+
+    #[test]
+    fn test_suggested_palette() {
+        let palette = roundtrip("sPLT", b"colors\0\x08\x01\x02\x03\x04\x00\x05");
+        match palette {
+            Ancillary::SuggestedPalette(palette) => {
+                assert_eq!(palette.entries.len(), 1);
+                assert_eq!(palette.entries[0].frequency, 5);
+            }
+            other => panic!("Unexpected {:?}", other),
+        }
+        roundtrip("sPLT", b"p\0\x10\x00\x01\x00\x02\x00\x03\x00\x04\x00\x05");
+        assert!(Ancillary::parse("sPLT", b"p\0\x08\x01", None).is_err());
+    }
+
+    #[test]
+    fn test_time() {
+        let time = roundtrip("tIME", &[0x07, 0xe7, 1, 2, 3, 4, 5]);
+        assert_eq!(
+            time.to_string(),
+            "Last modification: 2023-01-02 03:04:05 UTC"
+        );
+    }
+
+    #[test]
+    fn test_time_from_unix() {
+        assert_eq!(
+            Time::from_unix(0),
+            Time::from_str("1970-01-01T00:00:00Z").unwrap()
+        );
+        assert_eq!(
+            Time::from_unix(1709210096),
+            Time::from_str("2024-02-29 12:34:56").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_time() {
+        assert!(Time::from_str("2024-02-29").is_err());
+        assert!(Time::from_str("2024-13-01T00:00:00").is_err());
+        assert!(Time::from_str("2024-01-00T00:00:00").is_err());
+        assert!(Time::from_str("2024-02-30T00:00:00").is_err());
+        assert!(Time::from_str("2023-02-29T00:00:00").is_err());
+        assert!(Time::from_str("1900-02-29T00:00:00").is_err());
+        assert!(Time::from_str("2000-02-29T00:00:00").is_ok());
+        assert!(Time::from_str("2024-04-31T00:00:00").is_err());
+        assert!(Time::from_str("2024-12-31T00:00:00").is_ok());
+        assert!(Time::from_str("2024-01-01T24:00:00").is_err());
+    }
+
+    #[test]
+    fn test_unknown_chunk_type() {
+        assert!(Ancillary::parse("ruSt", &[], None).unwrap().is_none());
+    }
+}
@@ -0,0 +1,701 @@
+/*!
+ * # APNG crate
+ *
+ * Defines the animation chunks `acTL`, `fcTL` and `fdAT` as specified in [APNG specification](https://wiki.mozilla.org/APNG_Specification).
+ *
+ * Frames are kept as they are stored in the file, i.e. as regions of the canvas that are not composited with the
+ * previous frames.
+ *
+ */
+
+use crate::chunk::Chunk;
+use crate::chunk_type::{ChunkType, ChunkTypeError};
+use crate::image_header::{ImageHeader, ImageHeaderError};
+use crate::png::Png;
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+const ANIMATION_CONTROL_LEN: usize = 8;
+const FRAME_CONTROL_LEN: usize = 26;
+const SEQUENCE_NUMBER_LEN: usize = 4;
+/// Denominator of the frame delay used when the stored denominator is zero
+const DEFAULT_DELAY_DENOMINATOR: u16 = 100;
+
+#[derive(Debug)]
+pub enum ApngError {
+    InvalidLength(&'static str),
+    InvalidOperation(&'static str),
+    MissingImageHeader,
+    MissingAnimationControl,
+    InvalidSequence { expected: u32, found: u32 },
+    UnexpectedFrameData,
+    MissingFrameData(u32),
+    InvalidFrameRegion(u32),
+    FrameCountMismatch { declared: u32, found: usize },
+    MissingFrames,
+    MismatchedImageHeader(usize),
+    MismatchedPalette(usize, &'static str),
+    ImageHeader(ImageHeaderError),
+    ChunkType(ChunkTypeError),
+}
+
+impl From<ImageHeaderError> for ApngError {
+    fn from(item: ImageHeaderError) -> ApngError {
+        ApngError::ImageHeader(item)
+    }
+}
+
+impl From<ChunkTypeError> for ApngError {
+    fn from(item: ChunkTypeError) -> ApngError {
+        ApngError::ChunkType(item)
+    }
+}
+
+impl fmt::Display for ApngError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ApngError::InvalidLength(chunk_type) => {
+                write!(f, "Invalid length of the {} chunk data.", chunk_type)
+            }
+            ApngError::InvalidOperation(operation) => {
+                write!(f, "Invalid {} operation in the fcTL chunk.", operation)
+            }
+            ApngError::MissingImageHeader => write!(f, "PNG file doesn't contain an IHDR chunk."),
+            ApngError::MissingAnimationControl => {
+                write!(
+                    f,
+                    "PNG file doesn't contain an acTL chunk before the image data."
+                )
+            }
+            ApngError::InvalidSequence { expected, found } => write!(
+                f,
+                "Invalid sequence number {}, expected {}.",
+                found, expected
+            ),
+            ApngError::UnexpectedFrameData => {
+                write!(f, "fdAT chunk doesn't follow an fcTL chunk of its frame.")
+            }
+            ApngError::MissingFrameData(sequence_number) => write!(
+                f,
+                "Frame with the sequence number {} doesn't contain any image data.",
+                sequence_number
+            ),
+            ApngError::InvalidFrameRegion(sequence_number) => write!(
+                f,
+                "Frame with the sequence number {} doesn't fit into the image.",
+                sequence_number
+            ),
+            ApngError::FrameCountMismatch { declared, found } => write!(
+                f,
+                "acTL chunk declares {} frames, but {} frames were found.",
+                declared, found
+            ),
+            ApngError::MissingFrames => write!(f, "Animation doesn't contain any frames."),
+            ApngError::MismatchedImageHeader(index) => write!(
+                f,
+                "IHDR chunk of the frame {} doesn't match the IHDR chunk of the first frame.",
+                index
+            ),
+            ApngError::MismatchedPalette(index, chunk_type) => write!(
+                f,
+                "{} chunk of the frame {} doesn't match the {} chunk of the first frame.",
+                chunk_type, index, chunk_type
+            ),
+            ApngError::ImageHeader(ref err) => write!(f, "Image header error: {}", err),
+            ApngError::ChunkType(ref err) => write!(f, "Chunk type error: {}", err),
+        }
+    }
+}
+
+impl error::Error for ApngError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ApngError::InvalidLength(_) => None,
+            ApngError::InvalidOperation(_) => None,
+            ApngError::MissingImageHeader => None,
+            ApngError::MissingAnimationControl => None,
+            ApngError::InvalidSequence { .. } => None,
+            ApngError::UnexpectedFrameData => None,
+            ApngError::MissingFrameData(_) => None,
+            ApngError::InvalidFrameRegion(_) => None,
+            ApngError::FrameCountMismatch { .. } => None,
+            ApngError::MissingFrames => None,
+            ApngError::MismatchedImageHeader(_) => None,
+            ApngError::MismatchedPalette(..) => None,
+            ApngError::ImageHeader(ref err) => Some(err),
+            ApngError::ChunkType(ref err) => Some(err),
+        }
+    }
+}
+
+fn be_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([data[offset], data[offset + 1]])
+}
+
+fn be_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+/// Animation control, stored in the `acTL` chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimationControl {
+    pub num_frames: u32,
+    /// Number of times the animation is played, zero means infinitely
+    pub num_plays: u32,
+}
+
+impl AnimationControl {
+    pub const CHUNK_TYPE: &'static str = "acTL";
+
+    pub fn as_bytes(&self) -> Vec<u8> {
+        self.num_frames
+            .to_be_bytes()
+            .into_iter()
+            .chain(self.num_plays.to_be_bytes())
+            .collect()
+    }
+}
+
+impl TryFrom<&[u8]> for AnimationControl {
+    type Error = ApngError;
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        if value.len() != ANIMATION_CONTROL_LEN {
+            return Err(ApngError::InvalidLength(AnimationControl::CHUNK_TYPE));
+        }
+        Ok(AnimationControl {
+            num_frames: be_u32(value, 0),
+            num_plays: be_u32(value, 4),
+        })
+    }
+}
+
+/// Disposal of the frame region before rendering the next frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisposeOp {
+    None = 0,
+    Background = 1,
+    Previous = 2,
+}
+
+impl fmt::Display for DisposeOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisposeOp::None => write!(f, "none"),
+            DisposeOp::Background => write!(f, "background"),
+            DisposeOp::Previous => write!(f, "previous"),
+        }
+    }
+}
+
+/// Blending of the frame with the current content of the canvas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendOp {
+    Source = 0,
+    Over = 1,
+}
+
+impl fmt::Display for BlendOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlendOp::Source => write!(f, "source"),
+            BlendOp::Over => write!(f, "over"),
+        }
+    }
+}
+
+/// Frame control, stored in the `fcTL` chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameControl {
+    pub sequence_number: u32,
+    pub width: u32,
+    pub height: u32,
+    pub x_offset: u32,
+    pub y_offset: u32,
+    pub delay_num: u16,
+    pub delay_den: u16,
+    pub dispose_op: DisposeOp,
+    pub blend_op: BlendOp,
+}
+
+impl FrameControl {
+    pub const CHUNK_TYPE: &'static str = "fcTL";
+
+    /// Returns the frame delay in milliseconds
+    pub fn delay_ms(&self) -> f64 {
+        let den = match self.delay_den {
+            0 => DEFAULT_DELAY_DENOMINATOR,
+            den => den,
+        };
+        self.delay_num as f64 * 1000.0 / den as f64
+    }
+
+    pub fn as_bytes(&self) -> Vec<u8> {
+        [
+            self.sequence_number,
+            self.width,
+            self.height,
+            self.x_offset,
+            self.y_offset,
+        ]
+        .iter()
+        .flat_map(|value| value.to_be_bytes())
+        .chain(self.delay_num.to_be_bytes())
+        .chain(self.delay_den.to_be_bytes())
+        .chain([self.dispose_op as u8, self.blend_op as u8])
+        .collect()
+    }
+}
+
+impl TryFrom<&[u8]> for FrameControl {
+    type Error = ApngError;
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        if value.len() != FRAME_CONTROL_LEN {
+            return Err(ApngError::InvalidLength(FrameControl::CHUNK_TYPE));
+        }
+        Ok(FrameControl {
+            sequence_number: be_u32(value, 0),
+            width: be_u32(value, 4),
+            height: be_u32(value, 8),
+            x_offset: be_u32(value, 12),
+            y_offset: be_u32(value, 16),
+            delay_num: be_u16(value, 20),
+            delay_den: be_u16(value, 22),
+            dispose_op: match value[24] {
+                0 => DisposeOp::None,
+                1 => DisposeOp::Background,
+                2 => DisposeOp::Previous,
+                _ => return Err(ApngError::InvalidOperation("dispose")),
+            },
+            blend_op: match value[25] {
+                0 => BlendOp::Source,
+                1 => BlendOp::Over,
+                _ => return Err(ApngError::InvalidOperation("blend")),
+            },
+        })
+    }
+}
+
+/// Single frame of the animation together with its image data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub control: FrameControl,
+    /// Index of the `fcTL` chunk of the frame
+    pub chunk_index: usize,
+    /// Whether the frame is the default image stored in the `IDAT` chunks
+    pub is_default_image: bool,
+    /// Image data of the frame, one item per `IDAT` or `fdAT` chunk, without sequence numbers
+    pub data: Vec<Vec<u8>>,
+}
+
+impl Frame {
+    /// Creates a standalone PNG file with the frame as its only image
+    ///
+    /// Chunks that precede the image data of the animation, such as the palette or the color space chunks,
+    /// are copied into the new file.
+    pub fn to_png(&self, png: &Png) -> Result<Png, ApngError> {
+        let mut header = ImageHeader::try_from(
+            png.chunk_by_type(ImageHeader::CHUNK_TYPE)
+                .ok_or(ApngError::MissingImageHeader)?
+                .data(),
+        )?;
+        header.width = self.control.width;
+        header.height = self.control.height;
+
+        let mut chunks = vec![Chunk::new(
+            ChunkType::from_str(ImageHeader::CHUNK_TYPE)?,
+            header.as_bytes(),
+        )];
+        chunks.extend(
+            png.chunks()
+                .iter()
+                .skip(1)
+                .take_while(|c| !matches!(&c.chunk_type().bytes(), b"IDAT" | b"fcTL"))
+                .filter(|c| c.chunk_type().bytes() != *b"acTL")
+                .cloned(),
+        );
+        for data in self.data.iter() {
+            chunks.push(Chunk::new(ChunkType::from_str("IDAT")?, data.clone()));
+        }
+        chunks.push(Chunk::new(ChunkType::from_str("IEND")?, vec![]));
+
+        Ok(Png::from_chunks(chunks))
+    }
+}
+
+/// Animation stored in an APNG file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Animation {
+    pub control: AnimationControl,
+    pub frames: Vec<Frame>,
+}
+
+impl Animation {
+    /// Chunk types, other than the image header, that every frame shares with the default image
+    const SHARED_CHUNK_TYPES: [&'static str; 2] = ["PLTE", "tRNS"];
+
+    /// Builds an APNG file from the frames, using the first frame as the default image
+    ///
+    /// All frames must have the same image header, palette and transparency, since frames share the chunks of the default
+    /// image. Chunks of the first frame other than the image data are kept,
+    /// while only the image data of the other frames is used.
+    pub fn build(
+        frames: &[Png],
+        delay_num: u16,
+        delay_den: u16,
+        num_plays: u32,
+    ) -> Result<Png, ApngError> {
+        let (first, rest) = frames.split_first().ok_or(ApngError::MissingFrames)?;
+        let header_chunk = first
+            .chunk_by_type(ImageHeader::CHUNK_TYPE)
+            .ok_or(ApngError::MissingImageHeader)?;
+        let header = ImageHeader::try_from(header_chunk.data())?;
+        for (index, frame) in rest.iter().enumerate() {
+            if frame.chunk_by_type(ImageHeader::CHUNK_TYPE) != Some(header_chunk) {
+                return Err(ApngError::MismatchedImageHeader(index + 1));
+            }
+            for chunk_type in Animation::SHARED_CHUNK_TYPES {
+                if frame.chunk_by_type(chunk_type) != first.chunk_by_type(chunk_type) {
+                    return Err(ApngError::MismatchedPalette(index + 1, chunk_type));
+                }
+            }
+        }
+
+        let is_image_data = |c: &&Chunk| c.chunk_type().bytes() == *b"IDAT";
+        let mut sequence_number = 0;
+        let frame_control =
+            |sequence_number: &mut u32, data: &[Chunk]| -> Result<Chunk, ApngError> {
+                let control = FrameControl {
+                    sequence_number: *sequence_number,
+                    width: header.width,
+                    height: header.height,
+                    x_offset: 0,
+                    y_offset: 0,
+                    delay_num,
+                    delay_den,
+                    dispose_op: DisposeOp::None,
+                    blend_op: BlendOp::Source,
+                };
+                if data.is_empty() {
+                    return Err(ApngError::MissingFrameData(*sequence_number));
+                }
+                *sequence_number += 1;
+                Ok(Chunk::new(
+                    ChunkType::from_str(FrameControl::CHUNK_TYPE)?,
+                    control.as_bytes(),
+                ))
+            };
+
+        let first_data_index = first
+            .chunks()
+            .iter()
+            .position(|c| is_image_data(&c))
+            .ok_or(ApngError::MissingFrameData(0))?;
+        let (before_data, after_data) = first.chunks().split_at(first_data_index);
+        let first_data: Vec<Chunk> = after_data.iter().filter(is_image_data).cloned().collect();
+
+        let mut chunks = vec![header_chunk.clone()];
+        chunks.push(Chunk::new(
+            ChunkType::from_str(AnimationControl::CHUNK_TYPE)?,
+            AnimationControl {
+                num_frames: frames.len() as u32,
+                num_plays,
+            }
+            .as_bytes(),
+        ));
+        chunks.extend(before_data.iter().skip(1).cloned());
+        chunks.push(frame_control(&mut sequence_number, &first_data)?);
+        chunks.extend(first_data);
+
+        for frame in rest {
+            let data: Vec<Chunk> = frame
+                .chunks()
+                .iter()
+                .filter(is_image_data)
+                .cloned()
+                .collect();
+            chunks.push(frame_control(&mut sequence_number, &data)?);
+            for chunk in data {
+                let frame_data = sequence_number
+                    .to_be_bytes()
+                    .into_iter()
+                    .chain(chunk.data().iter().copied())
+                    .collect();
+                chunks.push(Chunk::new(ChunkType::from_str("fdAT")?, frame_data));
+                sequence_number += 1;
+            }
+        }
+
+        chunks.extend(
+            after_data
+                .iter()
+                .filter(|c| !matches!(&c.chunk_type().bytes(), b"IDAT" | b"IEND"))
+                .cloned(),
+        );
+        chunks.push(Chunk::new(ChunkType::from_str("IEND")?, vec![]));
+
+        Ok(Png::from_chunks(chunks))
+    }
+}
+
+impl TryFrom<&Png> for Animation {
+    type Error = ApngError;
+
+    /// Parses the animation chunks and validates their sequence numbers
+    fn try_from(png: &Png) -> Result<Self, Self::Error> {
+        let header = ImageHeader::try_from(
+            png.chunk_by_type(ImageHeader::CHUNK_TYPE)
+                .ok_or(ApngError::MissingImageHeader)?
+                .data(),
+        )?;
+
+        let mut control = None;
+        let mut frames: Vec<Frame> = vec![];
+        let mut expected_sequence_number = 0;
+        let mut image_data_seen = false;
+        let mut check_sequence_number = |found: u32| match found == expected_sequence_number {
+            true => {
+                expected_sequence_number += 1;
+                Ok(())
+            }
+            false => Err(ApngError::InvalidSequence {
+                expected: expected_sequence_number,
+                found,
+            }),
+        };
+
+        for (index, chunk) in png.chunks().iter().enumerate() {
+            match &chunk.chunk_type().bytes() {
+                b"acTL" if !image_data_seen => {
+                    control = Some(AnimationControl::try_from(chunk.data())?)
+                }
+                b"fcTL" => {
+                    let frame_control = FrameControl::try_from(chunk.data())?;
+                    check_sequence_number(frame_control.sequence_number)?;
+                    let fits = |offset: u32, size: u32, max: u32| {
+                        size > 0 && offset.checked_add(size).is_some_and(|end| end <= max)
+                    };
+                    let is_default_image = !image_data_seen;
+                    if !fits(frame_control.x_offset, frame_control.width, header.width)
+                        || !fits(frame_control.y_offset, frame_control.height, header.height)
+                        || (is_default_image
+                            && (
+                                frame_control.width,
+                                frame_control.height,
+                                frame_control.x_offset,
+                                frame_control.y_offset,
+                            ) != (header.width, header.height, 0, 0))
+                    {
+                        return Err(ApngError::InvalidFrameRegion(frame_control.sequence_number));
+                    }
+                    frames.push(Frame {
+                        control: frame_control,
+                        chunk_index: index,
+                        is_default_image,
+                        data: vec![],
+                    });
+                }
+                b"IDAT" => {
+                    image_data_seen = true;
+                    if let Some(frame) = frames.last_mut().filter(|f| f.is_default_image) {
+                        frame.data.push(chunk.data().to_vec());
+                    }
+                }
+                b"fdAT" => {
+                    if chunk.data().len() < SEQUENCE_NUMBER_LEN {
+                        return Err(ApngError::InvalidLength("fdAT"));
+                    }
+                    check_sequence_number(be_u32(chunk.data(), 0))?;
+                    let frame = frames
+                        .last_mut()
+                        .filter(|f| !f.is_default_image)
+                        .ok_or(ApngError::UnexpectedFrameData)?;
+                    frame
+                        .data
+                        .push(chunk.data()[SEQUENCE_NUMBER_LEN..].to_vec());
+                }
+                _ => {}
+            }
+        }
+
+        let control = control.ok_or(ApngError::MissingAnimationControl)?;
+        if control.num_frames as usize != frames.len() {
+            return Err(ApngError::FrameCountMismatch {
+                declared: control.num_frames,
+                found: frames.len(),
+            });
+        }
+        if let Some(frame) = frames.iter().find(|f| f.data.is_empty()) {
+            return Err(ApngError::MissingFrameData(frame.control.sequence_number));
+        }
+
+        Ok(Animation { control, frames })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(chunk_type: &str, data: Vec<u8>) -> Chunk {
+        Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data)
+    }
+
+    fn frame_control(sequence_number: u32, width: u32, x_offset: u32) -> FrameControl {
+        FrameControl {
+            sequence_number,
+            width,
+            height: 2,
+            x_offset,
+            y_offset: 0,
+            delay_num: 1,
+            delay_den: 10,
+            dispose_op: DisposeOp::None,
+            blend_op: BlendOp::Over,
+        }
+    }
+
+    fn frame_data(sequence_number: u32, data: &[u8]) -> Vec<u8> {
+        sequence_number
+            .to_be_bytes()
+            .into_iter()
+            .chain(data.iter().copied())
+            .collect()
+    }
+
+    fn testing_apng() -> Png {
+        Png::from_chunks(vec![
+            chunk("IHDR", vec![0, 0, 0, 4, 0, 0, 0, 2, 8, 6, 0, 0, 0]),
+            chunk(
+                "acTL",
+                AnimationControl {
+                    num_frames: 2,
+                    num_plays: 0,
+                }
+                .as_bytes(),
+            ),
+            chunk("gAMA", vec![0, 0, 177, 143]),
+            chunk("fcTL", frame_control(0, 4, 0).as_bytes()),
+            chunk("IDAT", vec![1, 2, 3]),
+            chunk("fcTL", frame_control(1, 2, 1).as_bytes()),
+            chunk("fdAT", frame_data(2, &[4, 5])),
+            chunk("fdAT", frame_data(3, &[6])),
+            chunk("IEND", vec![]),
+        ])
+    }
+
+    #[test]
+    fn test_frame_control_roundtrip() {
+        let control = frame_control(7, 3, 1);
+        let bytes = control.as_bytes();
+        assert_eq!(bytes.len(), FRAME_CONTROL_LEN);
+        assert_eq!(FrameControl::try_from(bytes.as_slice()).unwrap(), control);
+        assert_eq!(control.delay_ms(), 100.0);
+    }
+
+    #[test]
+    fn test_animation_from_png() {
+        let animation = Animation::try_from(&testing_apng()).unwrap();
+        assert_eq!(animation.control.num_frames, 2);
+        assert!(animation.frames[0].is_default_image);
+        assert_eq!(animation.frames[0].data, vec![vec![1, 2, 3]]);
+        assert_eq!(animation.frames[1].chunk_index, 5);
+        assert_eq!(animation.frames[1].data, vec![vec![4, 5], vec![6]]);
+    }
+
+    #[test]
+    fn test_invalid_sequence_number() {
+        let mut png = testing_apng();
+        png.replace_chunk_at(7, chunk("fdAT", frame_data(5, &[6])))
+            .unwrap();
+        assert!(matches!(
+            Animation::try_from(&png),
+            Err(ApngError::InvalidSequence {
+                expected: 3,
+                found: 5
+            })
+        ));
+    }
+
+    #[test]
+    fn test_frame_count_mismatch() {
+        let mut png = testing_apng();
+        png.remove_chunks(|c| c.chunk_type().bytes() == *b"fdAT");
+        png.remove_chunk("fcTL").unwrap();
+        png.remove_chunk("fcTL").unwrap();
+        assert!(matches!(
+            Animation::try_from(&png),
+            Err(ApngError::FrameCountMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_build_animation() {
+        let png = testing_apng();
+        let animation = Animation::try_from(&png).unwrap();
+        let frame = animation.frames[0].to_png(&png).unwrap();
+        let frames = vec![frame.clone(), frame.clone(), frame];
+
+        let apng = Animation::build(&frames, 1, 10, 3).unwrap();
+        let built = Animation::try_from(&apng).unwrap();
+        assert_eq!(built.control.num_frames, 3);
+        assert_eq!(built.control.num_plays, 3);
+        assert!(built.frames[0].is_default_image);
+        assert_eq!(built.frames[2].control.sequence_number, 3);
+        assert_eq!(built.frames[2].data, vec![vec![1, 2, 3]]);
+        assert_eq!(built.frames[1].control.delay_ms(), 100.0);
+    }
+
+    #[test]
+    fn test_build_mismatched_frames() {
+        let png = testing_apng();
+        let animation = Animation::try_from(&png).unwrap();
+        let frames = vec![
+            animation.frames[0].to_png(&png).unwrap(),
+            animation.frames[1].to_png(&png).unwrap(),
+        ];
+        assert!(matches!(
+            Animation::build(&frames, 1, 10, 0),
+            Err(ApngError::MismatchedImageHeader(1))
+        ));
+
+        let frame = animation.frames[0].to_png(&png).unwrap();
+        let mut transparent = frame.clone();
+        transparent
+            .insert_chunk_ordered(Chunk::new(ChunkType::from_str("tRNS").unwrap(), vec![0, 0]))
+            .unwrap();
+        assert!(matches!(
+            Animation::build(&[frame.clone(), transparent.clone()], 1, 10, 0),
+            Err(ApngError::MismatchedPalette(1, "tRNS"))
+        ));
+        assert!(Animation::build(&[transparent.clone(), transparent], 1, 10, 0).is_ok());
+
+        assert!(matches!(
+            Animation::build(&[], 1, 10, 0),
+            Err(ApngError::MissingFrames)
+        ));
+    }
+
+    #[test]
+    fn test_frame_to_png() {
+        let png = testing_apng();
+        let animation = Animation::try_from(&png).unwrap();
+        let frame = animation.frames[1].to_png(&png).unwrap();
+
+        let types: Vec<String> = frame
+            .chunks()
+            .iter()
+            .map(|c| c.chunk_type().to_string())
+            .collect();
+        assert_eq!(types, ["IHDR", "gAMA", "IDAT", "IDAT", "IEND"]);
+        let header = ImageHeader::try_from(frame.chunks()[0].data()).unwrap();
+        assert_eq!((header.width, header.height), (2, 2));
+        assert_eq!(frame.chunks()[2].data(), &[4, 5]);
+        assert!(Png::try_from(frame.as_bytes().as_slice()).is_ok());
+    }
+}
@@ -4,8 +4,8 @@
  * Defines the command line arguments that are available for the user to invoke.
  *
  */
-use clap::Parser;
-use std::path::PathBuf;
+use clap::{ArgGroup, Parser};
+use std::path::{Path, PathBuf};
 
 /**
  *
@@ -21,29 +21,61 @@ pub enum PngMeArgs {
     Decode(DecodeArgs),
     Remove(RemoveArgs),
     Print(PrintArgs),
+    SplitEncode(SplitEncodeArgs),
+    CombineDecode(CombineDecodeArgs),
+    Replace(ReplaceArgs),
+    RenameChunk(RenameChunkArgs),
+    Move(MoveArgs),
+    Extract(ExtractArgs),
+    Import(ImportArgs),
+    Transplant(TransplantArgs),
+    Strip(StripArgs),
+    Exif(ExifArgs),
+    Xmp(XmpArgs),
+    SetDpi(SetDpiArgs),
+    Touch(TouchArgs),
+    Apng(ApngArgs),
+    Disassemble(DisassembleArgs),
+    Assemble(AssembleArgs),
+    Textconv(TextconvArgs),
+    Diff(DiffArgs),
+    Shell(ShellArgs),
+    View(ViewArgs),
+    Grep(GrepArgs),
+    Carve(CarveArgs),
+    Trailer(TrailerArgs),
 }
 
 /**
 *
-* Encode operation writes a specified message to a PNG file under a specific chunk type.
+* Encode operation writes a specified message to a PNG file under a specific chunk type, before the image data.
 *
 * To invoke the encode functionality the user must provide the following:
 * - a valid file path, absolute or relative, to the PNG file in which the message wants to be encoded
 * - a valid string representation of the chunk type under which the message is going to be stored, that matches the requirements described in the [PNG specification](http://www.libpng.org/pub/png/spec/1.2/PNG-Structure.html)
 * - the string message that wants to be encoded in the specified PNG file
 *
-* The optional value that can be specified is:
+* The optional values that can be specified are:
 * - a valid file path to the output file of the PNG file with the message will be stored in
+* - the redundancy level of the Reed-Solomon forward error correction, as the number of parity bytes added to every 255 byte block of the encoded message.
+*   Up to half as many corrupted bytes per block can be corrected when decoding.
 *
 * NOTE: if the output file path is not specified thant the modified PNG file will be stored in the source file provided as the first argument
 *
 */
-#[derive(Debug, clap::Args)]
+#[derive(Debug, Clone, clap::Args)]
 pub struct EncodeArgs {
     pub file_path: PathBuf,
     pub chunk_type: String,
     pub message: String,
+    #[arg(conflicts_with_all = ["output", "in_place"])]
     pub output_file_path: Option<PathBuf>,
+    #[arg(long)]
+    pub ecc: Option<u8>,
+    #[command(flatten)]
+    pub write: WriteArgs,
+    #[command(flatten)]
+    pub batch: BatchArgs,
 }
 
 /**
@@ -54,11 +86,26 @@ pub struct EncodeArgs {
 * - a valid file path, absolute or relative, to the PNG file from which the message wants to be decoded
 * - a valid string representation of the chunk type under which the message is stored, that matches the requirements described in the [PNG specification](http://www.libpng.org/pub/png/spec/1.2/PNG-Structure.html)
 *
+* The optional values that can be specified are:
+* - the redundancy level of the Reed-Solomon forward error correction the message was encoded with.
+*   Corrupted bytes of the message are corrected and the number of repaired bytes is reported.
+* - the machine readable output format, either `json`, `jsonl` or `yaml`, see the report crate for the schema
+* - the trailing chunks flag to also read the well formed chunks after the IEND chunk, where earlier versions of encode
+*   appended the message, instead of keeping them as trailing data
+*
 */
-#[derive(Debug, clap::Args)]
+#[derive(Debug, Clone, clap::Args)]
 pub struct DecodeArgs {
     pub file_path: PathBuf,
     pub chunk_type: String,
+    #[arg(long)]
+    pub ecc: Option<u8>,
+    #[arg(long)]
+    pub trailing_chunks: bool,
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+    #[command(flatten)]
+    pub batch: BatchArgs,
 }
 
 /**
@@ -70,10 +117,14 @@ pub struct DecodeArgs {
 * - a valid string representation of the chunk type under which the message is stored, that matches the requirements described in the [PNG specification](http://www.libpng.org/pub/png/spec/1.2/PNG-Structure.html)
 *
 */
-#[derive(Debug, clap::Args)]
+#[derive(Debug, Clone, clap::Args)]
 pub struct RemoveArgs {
     pub file_path: PathBuf,
     pub chunk_type: String,
+    #[command(flatten)]
+    pub write: WriteArgs,
+    #[command(flatten)]
+    pub batch: BatchArgs,
 }
 
 /**
@@ -90,8 +141,986 @@ pub struct RemoveArgs {
 * - a valid file path, absolute or relative, to the PNG file from which the message wants to be decoded
 * - a valid string representation of the chunk type under which the message is stored, that matches the requirements described in the [PNG specification](http://www.libpng.org/pub/png/spec/1.2/PNG-Structure.html)
 *
+* Optionally, the user can provide the verbose flag to also print the decoded content of the image header and of the
+* registered ancillary chunks, such as gamma, chromaticities, ICC profile, physical dimensions in DPI or modification time.
+*
+* The chunks can also be printed in a machine readable output format, either `json`, `jsonl` or `yaml`, see the report crate
+* for the schema. In that case chunks with a corrupted CRC are reported instead of failing, and the user can provide the data
+* flag to include the base64 encoded chunk data.
+*
+* A single chunk, selected by its index or by its type, can be printed in detail with the chunk flag: the absolute file
+* offsets of its fields, the four property bits of its type with their meaning and its decoded data. The hexdump flag
+* adds an annotated hex and ASCII dump of the chunk, optionally limited to the first max bytes of its data.
+*
 */
-#[derive(Debug, clap::Args)]
+#[derive(Debug, Clone, clap::Args)]
 pub struct PrintArgs {
     pub file_path: PathBuf,
+    #[arg(short, long)]
+    pub verbose: bool,
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+    #[arg(long, requires = "format")]
+    pub data: bool,
+    #[arg(long, value_name = "INDEX|TYPE", conflicts_with_all = ["format", "verbose"])]
+    pub chunk: Option<String>,
+    #[arg(long, requires = "chunk")]
+    pub hexdump: bool,
+    #[arg(long, requires = "hexdump")]
+    pub max_bytes: Option<usize>,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Defines the machine readable output formats of the read operations.
+*/
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Single pretty printed JSON document
+    Json,
+    /// One JSON document per line
+    Jsonl,
+    /// Single YAML document
+    Yaml,
+}
+
+/**
+*
+* Defines the options of the operations that modify or read a single PNG file, to run them on many files at once.
+*
+* The file path of the operation can also be a directory, processed with the PNG files it contains, or a glob pattern,
+* e.g. `"*.png"`. More files, directories and patterns can be added with `--files`.
+*
+* When more than one file is processed, the files are processed in parallel, a per-file summary is written to the standard
+* error and the operation fails if any of the files failed. The output of the files processed in parallel can interleave,
+* use `--jobs 1` to keep it in order.
+*
+* The optional values that can be specified are:
+* - more file paths, directories or glob patterns that the operation is run on
+* - the recursive flag, to process the PNG files of the sub-directories as well
+* - the number of files processed in parallel, by default the number of available CPUs
+* - the continue on error flag, to process the remaining files after a file failed instead of stopping
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct BatchArgs {
+    #[arg(long = "files", num_args = 1.., value_name = "PATH")]
+    pub file_paths: Vec<PathBuf>,
+    #[arg(short, long)]
+    pub recursive: bool,
+    #[arg(short, long)]
+    pub jobs: Option<usize>,
+    #[arg(long)]
+    pub continue_on_error: bool,
+}
+
+/**
+*
+* Defines the options of the operations that modify a PNG file.
+*
+* The modified file is written to a temporary file in the same directory, synced to the disk and renamed over the original
+* file, so that the original file is never left partially written.
+*
+* A file path of `-` reads the PNG file from the standard input, in which case the modified file is written to the standard
+* output unless an output file path is specified. An output file path of `-` writes the modified file to the standard output.
+*
+* The optional values that can be specified are:
+* - a valid file path to the output file the modified PNG file will be stored in, by default the source file is modified in place
+* - the in place flag, to explicitly modify the source file, which can't be combined with an output file path
+* - the backup flag with an optional suffix, `--backup` or `--backup=.orig`, to keep a copy of the original file, by default with the `.bak` suffix
+* - the preserve flag, to keep the modification time of the original file, whose permissions are always kept
+* - the dry run flag, in which case the PNG file is not modified and only the changes that would be written are listed
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct WriteArgs {
+    #[arg(short, long, value_name = "PATH", conflicts_with = "in_place")]
+    pub output: Option<PathBuf>,
+    #[arg(long)]
+    pub in_place: bool,
+    #[arg(
+        long,
+        value_name = "SUFFIX",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = ".bak"
+    )]
+    pub backup: Option<String>,
+    #[arg(long)]
+    pub preserve: bool,
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Operation on a single PNG file, that can be run on every file of a batch
+pub trait FileCommand: Clone + Send + Sync {
+    fn file_path(&self) -> &Path;
+
+    fn batch(&self) -> &BatchArgs;
+
+    /// Returns the same operation on another file
+    fn with_file_path(&self, file_path: PathBuf) -> Self;
+
+    /// Single output file the operation writes to, that can't be shared by the files of a batch
+    fn output_file_path(&self) -> Option<&Path> {
+        None
+    }
+
+    /// Output directory the operation writes its files to, in which every file of a batch gets its own subdirectory
+    fn output_dir_path(&self) -> Option<&Path> {
+        None
+    }
+
+    /// Returns the same operation writing its files to another output directory
+    fn with_output_dir_path(&self, _output_dir_path: PathBuf) -> Self {
+        self.clone()
+    }
+}
+
+/// Implements the file command for the arguments, with an optional override of the output file or directory path
+macro_rules! file_command {
+    ($args:ty $(, $($output:tt)+)?) => {
+        impl FileCommand for $args {
+            fn file_path(&self) -> &Path {
+                &self.file_path
+            }
+
+            fn batch(&self) -> &BatchArgs {
+                &self.batch
+            }
+
+            fn with_file_path(&self, file_path: PathBuf) -> Self {
+                Self {
+                    file_path,
+                    ..self.clone()
+                }
+            }
+
+            $(file_command!(@output $($output)+);)?
+        }
+    };
+    (@output |$this:ident| $output:expr) => {
+        fn output_file_path(&self) -> Option<&Path> {
+            let $this = self;
+            $output
+        }
+    };
+    (@output output_dir_path) => {
+        fn output_dir_path(&self) -> Option<&Path> {
+            Some(&self.output_dir_path)
+        }
+
+        fn with_output_dir_path(&self, output_dir_path: PathBuf) -> Self {
+            Self {
+                output_dir_path,
+                ..self.clone()
+            }
+        }
+    };
+}
+
+file_command!(EncodeArgs, |args| args
+    .output_file_path
+    .as_deref()
+    .or(args.write.output.as_deref()));
+file_command!(DecodeArgs);
+file_command!(RemoveArgs, |args| args.write.output.as_deref());
+file_command!(PrintArgs);
+file_command!(ReplaceArgs, |args| args.write.output.as_deref());
+file_command!(RenameChunkArgs, |args| args.write.output.as_deref());
+file_command!(MoveArgs, |args| args.write.output.as_deref());
+file_command!(ExtractArgs, output_dir_path);
+file_command!(ImportArgs, |args| args.write.output.as_deref());
+file_command!(TransplantArgs, |args| args.write.output.as_deref());
+file_command!(StripArgs, |args| args.write.output.as_deref());
+file_command!(ExifShowArgs);
+file_command!(ExifRemoveArgs, |args| args.write.output.as_deref());
+file_command!(XmpGetArgs);
+file_command!(XmpSetArgs, |args| args.write.output.as_deref());
+file_command!(XmpMergeArgs, |args| args.write.output.as_deref());
+file_command!(SetDpiArgs, |args| args.write.output.as_deref());
+file_command!(TouchArgs, |args| args.write.output.as_deref());
+file_command!(ApngInfoArgs);
+file_command!(ApngExtractArgs, output_dir_path);
+file_command!(DisassembleArgs, |args| args.output_file_path.as_deref());
+file_command!(TextconvArgs);
+file_command!(TrailerShowArgs, |args| args.output_file_path.as_deref());
+file_command!(TrailerStripArgs, |args| args.write.output.as_deref());
+file_command!(TrailerSetArgs, |args| args.write.output.as_deref());
+
+/**
+*
+* Split encode operation splits a message into secret shares and writes one share to each of the specified PNG files under a specific chunk type.
+* Any threshold number of the PNG files is enough to reconstruct the message, while fewer files reveal nothing about it.
+* The shares of a previous split under the same chunk type are replaced.
+*
+* To invoke the split encode functionality the user must provide the following:
+* - a valid string representation of the chunk type under which the shares are going to be stored, that matches the requirements described in the [PNG specification](http://www.libpng.org/pub/png/spec/1.2/PNG-Structure.html)
+* - the string message that wants to be split
+* - the number of shares, which must match the number of the PNG files
+* - the threshold of shares needed to reconstruct the message
+* - valid file paths, absolute or relative, to the PNG files in which the shares want to be encoded
+*
+*/
+#[derive(Debug, clap::Args)]
+pub struct SplitEncodeArgs {
+    pub chunk_type: String,
+    pub message: String,
+    #[arg(long)]
+    pub shares: u8,
+    #[arg(long)]
+    pub threshold: u8,
+    #[arg(required = true)]
+    pub file_paths: Vec<PathBuf>,
+    #[command(flatten)]
+    pub write: WriteArgs,
+}
+
+/**
+*
+* Combine decode operation reads the secret shares written to PNG files under a specific chunk type and reconstructs the message.
+* Chunks that aren't valid shares are reported and skipped, and shares of different splits are combined separately, so the
+* operation only fails if no split has enough valid shares, or if more than one split does.
+*
+* To invoke the combine decode functionality the user must provide the following:
+* - a valid string representation of the chunk type under which the shares are stored, that matches the requirements described in the [PNG specification](http://www.libpng.org/pub/png/spec/1.2/PNG-Structure.html)
+* - valid file paths, absolute or relative, to at least threshold number of PNG files that contain the shares
+*
+* The optional values that can be specified are:
+* - the machine readable output format, either `json`, `jsonl` or `yaml`, see the report crate for the schema
+*
+*/
+#[derive(Debug, clap::Args)]
+pub struct CombineDecodeArgs {
+    pub chunk_type: String,
+    #[arg(required = true)]
+    pub file_paths: Vec<PathBuf>,
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+}
+
+/**
+*
+* Replace operation overwrites the data of an existing chunk in the PNG file, while keeping the position of the chunk. Chunk type is used to reference the chunk that wants to be replaced.
+*
+* To invoke the replace functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file in which the chunk wants to be replaced
+* - a valid string representation of the chunk type of the chunk that wants to be replaced, that matches the requirements described in the [PNG specification](http://www.libpng.org/pub/png/spec/1.2/PNG-Structure.html)
+* - the string message that is going to be the new data of the chunk
+*
+* Critical chunks, e.g. `IHDR`, `IDAT` or `IEND`, are only replaced with the force flag, since replacing them corrupts the image.
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct ReplaceArgs {
+    pub file_path: PathBuf,
+    pub chunk_type: String,
+    pub message: String,
+    #[arg(long)]
+    pub force: bool,
+    #[command(flatten)]
+    pub write: WriteArgs,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Rename chunk operation changes the chunk type of an existing chunk in the PNG file, while keeping the data and the position of the chunk.
+*
+* To invoke the rename chunk functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file in which the chunk wants to be renamed
+* - a valid string representation of the current chunk type of the chunk
+* - a valid string representation of the new chunk type of the chunk, that matches the requirements described in the [PNG specification](http://www.libpng.org/pub/png/spec/1.2/PNG-Structure.html)
+*
+* Critical chunks are only renamed, and chunks are only renamed to a critical chunk type, with the force flag, since
+* either corrupts the image.
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct RenameChunkArgs {
+    pub file_path: PathBuf,
+    pub chunk_type: String,
+    pub new_chunk_type: String,
+    #[arg(long)]
+    pub force: bool,
+    #[command(flatten)]
+    pub write: WriteArgs,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Move operation reorders the chunks of the PNG file by moving a chunk before or after another chunk. Chunk types are used to reference the chunks.
+*
+* To invoke the move functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file in which the chunk wants to be moved
+* - a valid string representation of the chunk type of the chunk that wants to be moved
+* - exactly one of the options `--before` or `--after`, with the chunk type of the chunk relative to which the chunk is moved
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+#[command(group(ArgGroup::new("position").required(true).args(["before", "after"])))]
+pub struct MoveArgs {
+    pub file_path: PathBuf,
+    pub chunk_type: String,
+    #[arg(long)]
+    pub before: Option<String>,
+    #[arg(long)]
+    pub after: Option<String>,
+    #[command(flatten)]
+    pub write: WriteArgs,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Extract operation writes chunks of the PNG file to separate files in the output directory.
+* Every chunk is written to a file named after the index and the type of the chunk, e.g. `3_iCCP.bin`.
+*
+* To invoke the extract functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file from which the chunks want to be extracted
+*
+* The optional values that can be specified are:
+* - a valid string representation of the chunk type of the chunks that want to be extracted, if not specified all chunks are extracted
+* - a valid directory path to which the chunks are written, if not specified the current directory is used.
+*   When more than one file is extracted, the chunks of every file are written to a subdirectory named after the file, e.g. `out/image/3_iCCP.bin`.
+* - the record flag, in which case the full length, type, data and CRC record of the chunk is written instead of only the chunk data.
+*   Chunk records are written to files with the `.chunk` extension and can be imported with the import operation.
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct ExtractArgs {
+    pub file_path: PathBuf,
+    #[arg(long = "type")]
+    pub chunk_type: Option<String>,
+    #[arg(long = "out", default_value = ".")]
+    pub output_dir_path: PathBuf,
+    #[arg(long)]
+    pub record: bool,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Import operation inserts previously extracted chunk records into the PNG file.
+* Chunks are inserted before the image data, respecting the ordering of the chunks described in the [PNG specification](http://www.libpng.org/pub/png/spec/1.2/PNG-Structure.html)
+* Records of critical chunks, e.g. `IHDR` or `IEND`, are rejected, since importing them corrupts the image.
+*
+* To invoke the import functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file into which the chunks want to be imported
+* - valid file paths to one or more chunk records, as written by the extract operation with the record flag
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct ImportArgs {
+    pub file_path: PathBuf,
+    #[arg(required = true)]
+    pub chunk_file_paths: Vec<PathBuf>,
+    #[command(flatten)]
+    pub write: WriteArgs,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Transplant operation copies the ancillary chunks from one PNG file to another, e.g. to restore the metadata of a re-rendered image.
+*
+* Safe-to-copy chunks are always copied. Unsafe-to-copy chunks depend on the image data, so they are only copied if the critical chunks
+* of both PNG files are the same, or if the copy is forced. The copied chunks keep their position relative to the critical chunks,
+* as described in the [PNG specification](http://www.libpng.org/pub/png/spec/1.2/PNG-Structure.html)
+*
+* To invoke the transplant functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file from which the chunks are copied
+* - a valid file path, absolute or relative, to the PNG file to which the chunks are copied
+*
+* The optional value that can be specified is:
+* - the force flag, in which case the unsafe-to-copy chunks are copied even if the critical chunks differ
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct TransplantArgs {
+    #[arg(long = "from")]
+    pub source_file_path: PathBuf,
+    #[arg(long = "to")]
+    pub file_path: PathBuf,
+    #[arg(long)]
+    pub force: bool,
+    #[command(flatten)]
+    pub write: WriteArgs,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Defines which ancillary chunks are removed by the strip operation.
+*/
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum StripPreset {
+    /// All ancillary chunks
+    Ancillary,
+    /// All private ancillary chunks
+    Private,
+    /// Textual chunks: tEXt, zTXt and iTXt
+    Text,
+    /// EXIF chunk: eXIf
+    Exif,
+}
+
+/**
+*
+* Strip operation removes the chunks that aren't needed to render the PNG file, e.g. before publishing it. Critical chunks are never removed.
+*
+* To invoke the strip functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file from which the chunks want to be removed
+*
+* The optional values that can be specified are:
+* - the preset that defines which ancillary chunks are removed, if not specified all ancillary chunks are removed
+* - a comma separated list of chunk types that are kept even if the preset would remove them, e.g. `sRGB,gAMA,iCCP`
+* - the dry run flag, in which case the PNG file is not modified and only the chunks that would be removed are listed
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct StripArgs {
+    pub file_path: PathBuf,
+    #[arg(long, value_enum, default_value_t = StripPreset::Ancillary)]
+    pub preset: StripPreset,
+    #[arg(long, value_delimiter = ',')]
+    pub keep: Vec<String>,
+    #[command(flatten)]
+    pub write: WriteArgs,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Exif operation inspects and edits the EXIF metadata stored in the `eXIf` chunk of the PNG file.
+*
+*/
+#[derive(Debug, clap::Args)]
+pub struct ExifArgs {
+    #[command(subcommand)]
+    pub command: ExifCommand,
+}
+
+/**
+*
+* Defines available EXIF subcommands.
+*/
+#[derive(Debug, clap::Subcommand)]
+pub enum ExifCommand {
+    Show(ExifShowArgs),
+    Remove(ExifRemoveArgs),
+}
+
+/**
+*
+* Exif show operation prints the EXIF entries of the PNG file, grouped by the image file directory that contains them.
+*
+* To invoke the exif show functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file whose EXIF metadata wants to be printed
+*
+* The optional values that can be specified are:
+* - the machine readable output format, either `json`, `jsonl` or `yaml`, see the report crate for the schema
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct ExifShowArgs {
+    pub file_path: PathBuf,
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Exif remove operation removes EXIF entries from the PNG file and rewrites the `eXIf` chunk.
+* Removing a pointer entry, e.g. `GPSInfo`, removes the whole directory it points to.
+*
+* To invoke the exif remove functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file whose EXIF metadata wants to be edited
+* - one or more tag name patterns, where `*` matches any sequence of characters, e.g. `GPS*`, `DateTime*` or `*SerialNumber`
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct ExifRemoveArgs {
+    pub file_path: PathBuf,
+    #[arg(long = "tag", required = true)]
+    pub tags: Vec<String>,
+    #[command(flatten)]
+    pub write: WriteArgs,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Xmp operation reads and writes the XMP metadata packet stored in the `iTXt` chunk with the `XML:com.adobe.xmp` keyword.
+* An existing XMP chunk is updated in place, otherwise a new chunk is inserted before the image data.
+*
+*/
+#[derive(Debug, clap::Args)]
+pub struct XmpArgs {
+    #[command(subcommand)]
+    pub command: XmpCommand,
+}
+
+/**
+*
+* Defines available XMP subcommands.
+*/
+#[derive(Debug, clap::Subcommand)]
+pub enum XmpCommand {
+    Get(XmpGetArgs),
+    Set(XmpSetArgs),
+    Merge(XmpMergeArgs),
+}
+
+/**
+*
+* Xmp get operation prints the XMP packet of the PNG file.
+*
+* To invoke the xmp get functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file whose XMP packet wants to be printed
+*
+* The optional values that can be specified are:
+* - the machine readable output format, either `json`, `jsonl` or `yaml`, see the report crate for the schema
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct XmpGetArgs {
+    pub file_path: PathBuf,
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Xmp set operation replaces the XMP packet of the PNG file with the content of a file.
+*
+* To invoke the xmp set functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file whose XMP packet wants to be replaced
+* - a valid file path, absolute or relative, to the file that contains the new XMP packet
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct XmpSetArgs {
+    pub file_path: PathBuf,
+    pub xmp_file_path: PathBuf,
+    #[command(flatten)]
+    pub write: WriteArgs,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Xmp merge operation sets individual properties of the XMP packet of the PNG file, keeping the other properties.
+* If the PNG file has no XMP packet, a new packet is created.
+*
+* To invoke the xmp merge functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file whose XMP packet wants to be edited
+* - one or more properties in the `prefix:name=value` format, e.g. `dc:creator=Alice` or `dc:rights="CC BY 4.0"`
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct XmpMergeArgs {
+    pub file_path: PathBuf,
+    #[arg(long = "set", required = true)]
+    pub properties: Vec<String>,
+    #[command(flatten)]
+    pub write: WriteArgs,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Set DPI operation writes the physical resolution of the PNG file into the pHYs chunk, replacing any previous resolution.
+* The chunk is placed before the image data, as required by the [PNG specification](http://www.libpng.org/pub/png/spec/1.2/PNG-Chunks.html#C.pHYs).
+*
+* To invoke the set DPI functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file whose resolution wants to be set
+* - the resolution in dots per inch, used for both the horizontal and the vertical direction
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct SetDpiArgs {
+    pub file_path: PathBuf,
+    pub dpi: u32,
+    #[command(flatten)]
+    pub write: WriteArgs,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Touch operation writes the time of the last image modification into the tIME chunk, replacing any previous time.
+*
+* To invoke the touch functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file whose modification time wants to be set
+*
+* The optional values that can be specified are:
+* - the UTC time in the `YYYY-MM-DDTHH:MM:SS` format, if not specified the current time is used
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct TouchArgs {
+    pub file_path: PathBuf,
+    #[arg(long)]
+    pub time: Option<String>,
+    #[command(flatten)]
+    pub write: WriteArgs,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Apng operation inspects the animation of an [APNG](https://wiki.mozilla.org/APNG_Specification) file.
+*
+*/
+#[derive(Debug, clap::Args)]
+pub struct ApngArgs {
+    #[command(subcommand)]
+    pub command: ApngCommand,
+}
+
+/**
+*
+* Defines available APNG subcommands.
+*/
+#[derive(Debug, clap::Subcommand)]
+pub enum ApngCommand {
+    Info(ApngInfoArgs),
+    Extract(ApngExtractArgs),
+    Build(ApngBuildArgs),
+}
+
+/**
+*
+* Apng info operation validates the animation chunks and prints the frames of the animation with the following info:
+* - byte offset of the frame control chunk in the file
+* - size and position of the frame region
+* - delay of the frame in milliseconds
+* - dispose and blend operations of the frame
+*
+* To invoke the apng info functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the APNG file whose frames want to be printed
+*
+* The optional values that can be specified are:
+* - the machine readable output format, either `json`, `jsonl` or `yaml`, see the report crate for the schema
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct ApngInfoArgs {
+    pub file_path: PathBuf,
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Apng extract operation writes every frame of the animation as a standalone PNG file named after the index of the frame, e.g. `frame_3.png`.
+* Frames are written as they are stored, without composing them with the previous frames.
+*
+* To invoke the apng extract functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the APNG file from which the frames want to be extracted
+*
+* The optional values that can be specified are:
+* - a valid directory path to which the frames are written, if not specified the current directory is used.
+*   When more than one file is extracted, the frames of every file are written to a subdirectory named after the file, e.g. `out/image/frame_3.png`.
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct ApngExtractArgs {
+    pub file_path: PathBuf,
+    #[arg(long = "out", default_value = ".")]
+    pub output_dir_path: PathBuf,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Apng build operation creates an APNG file from PNG files, each of which becomes one frame of the animation.
+* The first PNG file is also used as the default image shown by viewers without APNG support.
+*
+* To invoke the apng build functionality the user must provide the following:
+* - valid file paths, absolute or relative, to the PNG files of the frames, in the order of the animation. All files must have the same IHDR chunk, and the same PLTE and tRNS chunks if present
+* - a valid file path, absolute or relative, to which the APNG file is written
+*
+* The optional values that can be specified are:
+* - the delay of each frame in milliseconds or seconds, e.g. `100ms` or `1.5s`, if not specified 100 milliseconds are used
+* - the number of times the animation is played, if not specified or zero the animation is played infinitely
+*
+*/
+#[derive(Debug, clap::Args)]
+pub struct ApngBuildArgs {
+    #[arg(required = true)]
+    pub frame_file_paths: Vec<PathBuf>,
+    #[arg(short, long = "output")]
+    pub output_file_path: PathBuf,
+    #[arg(long, default_value = "100ms")]
+    pub delay: String,
+    #[arg(long, default_value_t = 0)]
+    pub loops: u32,
+}
+
+/**
+*
+* Disassemble operation converts the PNG file to a textual PNG assembly listing, see the assembly crate for the format.
+* Every chunk is listed with its type, properties and data, shown as quoted strings for textual data and as hex digits otherwise.
+*
+* To invoke the disassemble functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file that wants to be disassembled
+*
+* The optional value that can be specified is:
+* - a valid file path, absolute or relative, to which the listing is written, if not specified the listing is printed
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct DisassembleArgs {
+    pub file_path: PathBuf,
+    #[arg(short, long = "output")]
+    pub output_file_path: Option<PathBuf>,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Assemble operation builds a PNG file from a PNG assembly listing. CRCs are computed from the chunk data, unless they are
+* given explicitly in the listing.
+*
+* To invoke the assemble functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG assembly listing
+* - a valid file path, absolute or relative, to which the PNG file is written
+*
+*/
+#[derive(Debug, clap::Args)]
+pub struct AssembleArgs {
+    pub source_file_path: PathBuf,
+    #[arg(short, long = "output")]
+    pub output_file_path: PathBuf,
+}
+
+/**
+*
+* Textconv operation prints a summary of the PNG file that is stable and suitable for diffing. The summary contains the
+* image header fields, the decoded ancillary, textual and EXIF chunks, the messages stored in other chunks and a hash of the image data.
+*
+* To use it as a git diff driver, configure the driver and assign it to the PNG files:
+* - `git config diff.png.textconv "pngme textconv"`
+* - `echo "*.png diff=png" >> .gitattributes`
+*
+* To invoke the textconv functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file that wants to be summarized
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct TextconvArgs {
+    pub file_path: PathBuf,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Diff operation compares two PNG files. Chunks are aligned and reported as added, removed, reordered or changed,
+* textual chunks are compared line by line, image headers field by field and the image data by decoded pixels.
+*
+* To invoke the diff functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the original PNG file
+* - a valid file path, absolute or relative, to the PNG file that is compared to the original
+*
+* The optional values that can be specified are:
+* - the machine readable output format, either `json`, `jsonl` or `yaml`, see the report crate for the schema
+*
+*/
+#[derive(Debug, clap::Args)]
+pub struct DiffArgs {
+    pub old_file_path: PathBuf,
+    pub new_file_path: PathBuf,
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+}
+
+/**
+*
+* Shell operation loads the PNG file once and starts an interactive shell to explore and edit its chunks. The shell
+* lists, shows, hexdumps, removes, adds and moves chunks, validates the structure of the file, undoes the edits and saves the file.
+* Type `help` in the shell for the list of commands.
+*
+* To invoke the shell functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file that wants to be explored
+*
+*/
+#[derive(Debug, clap::Args)]
+pub struct ShellArgs {
+    pub file_path: PathBuf,
+}
+
+/**
+*
+* View operation opens a full screen terminal browser of the chunks of a PNG file. The browser lists the chunks with
+* the critical, public and safe to copy properties of their types and their CRC status, and shows the decoded and the
+* hex and ASCII listing of the data of the selected chunk.
+*
+* To invoke the view functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file that wants to be viewed
+*
+*/
+#[derive(Debug, clap::Args)]
+pub struct ViewArgs {
+    pub file_path: PathBuf,
+}
+
+/**
+*
+* Grep operation searches a pattern in the chunks of PNG files and prints every match as the file, the chunk index,
+* the chunk type, whether the match is in the stored or in the inflated data, the offset of the match and the match itself.
+* Besides the raw chunk data, the compressed contents of the `zTXt`, `iTXt` and `iCCP` chunks are inflated and searched.
+*
+* To invoke the grep functionality the user must provide the following:
+* - a regular expression, or hex encoded bytes with the hex flag, e.g. `89 50 4e 47`
+* - one or more file paths, directories or glob patterns to the PNG files that are searched
+*
+* The optional values that can be specified are:
+* - the recursive flag to also search the PNG files in the subdirectories of the directories
+* - the ignore case flag to match the regular expression case insensitively
+* - the redundancy level of the Reed-Solomon forward error correction, to also search the corrected messages encoded with it.
+*   Secret shares can't be searched, since a single share reveals nothing about the message.
+*
+* The operation fails if the pattern doesn't match any chunk, so that it can be used in conditions of shell scripts.
+*
+*/
+#[derive(Debug, clap::Args)]
+pub struct GrepArgs {
+    pub pattern: String,
+    #[arg(required = true)]
+    pub file_paths: Vec<PathBuf>,
+    #[arg(short, long)]
+    pub recursive: bool,
+    #[arg(long)]
+    pub hex: bool,
+    #[arg(short, long, conflicts_with = "hex")]
+    pub ignore_case: bool,
+    #[arg(long)]
+    pub ecc: Option<u8>,
+}
+
+/**
+*
+* Carve operation recovers PNG files from arbitrary binary data, such as disk images or memory dumps. Every PNG signature
+* found in the data is followed chunk by chunk, validating the CRCs, up to the IEND chunk. Every recovered file is written
+* to the output directory, named after the offset at which it was found, e.g. `carved_0000a3f0.png`. Candidates that are
+* truncated or contain a corrupted chunk are reported separately as damaged.
+*
+* To invoke the carve functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the binary data that is scanned
+*
+* The optional values that can be specified are:
+* - a valid directory path, absolute or relative, to which the recovered files are written, the current directory by default
+* - the keep damaged flag to also write the valid chunks of the damaged candidates, e.g. `carved_0000a3f0.damaged.png`
+*
+*/
+#[derive(Debug, clap::Args)]
+pub struct CarveArgs {
+    pub blob_path: PathBuf,
+    #[arg(short, long = "output-dir", default_value = ".")]
+    pub output_dir: PathBuf,
+    #[arg(long)]
+    pub keep_damaged: bool,
+}
+
+/**
+*
+* Trailer operation inspects and edits the data stored after the IEND chunk of the PNG file, e.g. an archive appended
+* to make a polyglot file. All of the data after the IEND chunk is trailing data, even if it starts with a well formed chunk.
+*
+*/
+#[derive(Debug, clap::Args)]
+pub struct TrailerArgs {
+    #[command(subcommand)]
+    pub command: TrailerCommand,
+}
+
+/**
+*
+* Defines available trailer subcommands.
+*/
+#[derive(Debug, clap::Subcommand)]
+pub enum TrailerCommand {
+    Show(TrailerShowArgs),
+    Strip(TrailerStripArgs),
+    Set(TrailerSetArgs),
+}
+
+/**
+*
+* Trailer show operation prints the offset and the length of the trailing data of the PNG file, followed by its hex dump.
+*
+* To invoke the trailer show functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file whose trailing data wants to be printed
+*
+* The optional value that can be specified is:
+* - a valid file path, absolute or relative, to which the raw trailing data is written instead, `-` for the standard output
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct TrailerShowArgs {
+    pub file_path: PathBuf,
+    #[arg(short, long = "output")]
+    pub output_file_path: Option<PathBuf>,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Trailer strip operation removes the trailing data of the PNG file.
+*
+* To invoke the trailer strip functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file whose trailing data wants to be removed
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct TrailerStripArgs {
+    pub file_path: PathBuf,
+    #[command(flatten)]
+    pub write: WriteArgs,
+    #[command(flatten)]
+    pub batch: BatchArgs,
+}
+
+/**
+*
+* Trailer set operation replaces the trailing data of the PNG file with the content of a file.
+*
+* To invoke the trailer set functionality the user must provide the following:
+* - a valid file path, absolute or relative, to the PNG file whose trailing data wants to be replaced
+* - a valid file path, absolute or relative, to the file that contains the new trailing data
+*
+*/
+#[derive(Debug, Clone, clap::Args)]
+pub struct TrailerSetArgs {
+    pub file_path: PathBuf,
+    pub data_file_path: PathBuf,
+    #[command(flatten)]
+    pub write: WriteArgs,
+    #[command(flatten)]
+    pub batch: BatchArgs,
 }
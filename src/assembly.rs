@@ -0,0 +1,411 @@
+/*!
+ * # Assembly crate
+ *
+ * Defines the PNG assembly, a line based textual representation of the chunk structure of a PNG file that can be
+ * reviewed, edited by hand and assembled back into a byte identical PNG file.
+ *
+ * ```text
+ * ; comments start with a semicolon
+ * chunk IHDR ; critical, public, unsafe to copy
+ *   hex 00000004 00000003 08020000 00
+ * end
+ * chunk tEXt
+ *   str "Comment\0Hello\n"
+ * end
+ * chunk ruSt crc 0x12345678
+ *   hex 00ff
+ * end
+ * trailer ; data after the last chunk
+ *   str "PK\x03\x04"
+ * end
+ * ```
+ *
+ * Every chunk starts with the `chunk` directive followed by the chunk type and ends with the `end` directive.
+ * The chunk data is the concatenation of the `hex` and `str` lines in between. Strings support the `\\`, `\"`,
+ * `\0`, `\t`, `\n`, `\r` and `\xHH` escapes. The CRC is computed from the chunk type and data, unless it is
+ * provided explicitly after the chunk type, which is how the disassembly preserves corrupted CRCs.
+ *
+ * The data after the last chunk, if any, is listed in the same way between the `trailer` and `end` directives.
+ *
+ */
+
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::png::Png;
+use std::error;
+use std::fmt;
+use std::fmt::Write;
+use std::str::FromStr;
+
+const COMMENT: char = ';';
+const HEX_BYTES_PER_LINE: usize = 32;
+const HEX_BYTES_PER_GROUP: usize = 4;
+const STR_BYTES_PER_LINE: usize = 64;
+
+#[derive(Debug)]
+pub enum AssemblyError {
+    Syntax { line: usize, message: String },
+    UnterminatedChunk,
+}
+
+impl fmt::Display for AssemblyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AssemblyError::Syntax { line, ref message } => {
+                write!(f, "Syntax error on line {}: {}.", line, message)
+            }
+            AssemblyError::UnterminatedChunk => {
+                write!(f, "Last chunk isn't terminated with the end directive.")
+            }
+        }
+    }
+}
+
+impl error::Error for AssemblyError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            AssemblyError::Syntax { .. } => None,
+            AssemblyError::UnterminatedChunk => None,
+        }
+    }
+}
+
+/// Returns whether the data is shown as a string rather than as hex digits
+fn is_text(data: &[u8]) -> bool {
+    !data.is_empty()
+        && data
+            .iter()
+            .all(|&b| (0x20..0x7f).contains(&b) || matches!(b, 0 | b'\t' | b'\n' | b'\r'))
+}
+
+fn escape(data: &[u8]) -> String {
+    data.iter()
+        .map(|&b| match b {
+            b'\\' => String::from("\\\\"),
+            b'"' => String::from("\\\""),
+            0 => String::from("\\0"),
+            b'\t' => String::from("\\t"),
+            b'\n' => String::from("\\n"),
+            b'\r' => String::from("\\r"),
+            b => (b as char).to_string(),
+        })
+        .collect()
+}
+
+fn describe(chunk_type: &ChunkType) -> String {
+    let mut properties = vec![
+        if chunk_type.is_critical() {
+            "critical"
+        } else {
+            "ancillary"
+        },
+        if chunk_type.is_public() {
+            "public"
+        } else {
+            "private"
+        },
+        if chunk_type.is_safe_to_copy() {
+            "safe to copy"
+        } else {
+            "unsafe to copy"
+        },
+    ];
+    if !chunk_type.is_reserved_bit_valid() {
+        properties.push("reserved bit set");
+    }
+    properties.join(", ")
+}
+
+/// Lists the data as quoted strings if it is textual and as hex digits otherwise
+fn write_data(listing: &mut String, data: &[u8]) {
+    if is_text(data) {
+        let mut rest = data;
+        while !rest.is_empty() {
+            let len = rest
+                .iter()
+                .take(STR_BYTES_PER_LINE)
+                .position(|&b| b == b'\n')
+                .map_or(rest.len().min(STR_BYTES_PER_LINE), |i| i + 1);
+            let _ = writeln!(listing, "  str \"{}\"", escape(&rest[..len]));
+            rest = &rest[len..];
+        }
+    } else {
+        for line in data.chunks(HEX_BYTES_PER_LINE) {
+            let groups: Vec<String> = line
+                .chunks(HEX_BYTES_PER_GROUP)
+                .map(|group| group.iter().map(|b| format!("{:02x}", b)).collect())
+                .collect();
+            let _ = writeln!(listing, "  hex {}", groups.join(" "));
+        }
+    }
+}
+
+/// Converts the PNG file to its assembly listing
+pub fn disassemble(png: &Png) -> String {
+    let mut listing = String::new();
+    for chunk in png.chunks() {
+        let _ = write!(listing, "chunk {}", chunk.chunk_type());
+        if !chunk.has_valid_crc() {
+            let _ = write!(listing, " crc {:#010x}", chunk.crc());
+        }
+        let _ = writeln!(listing, " ; {}", describe(chunk.chunk_type()));
+
+        write_data(&mut listing, chunk.data());
+        listing.push_str("end\n");
+    }
+    if !png.trailer().is_empty() {
+        listing.push_str("trailer ; data after the last chunk\n");
+        write_data(&mut listing, png.trailer());
+        listing.push_str("end\n");
+    }
+    listing
+}
+
+/// Chunk whose directives are being assembled, or the trailer if it has no chunk type
+struct PendingChunk {
+    chunk_type: Option<ChunkType>,
+    crc: Option<u32>,
+    data: Vec<u8>,
+}
+
+/// Removes the comment from the line, ignoring the comment character inside of strings
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            COMMENT if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn parse_hex(digits: &str) -> Result<Vec<u8>, String> {
+    let digits: Vec<char> = digits.chars().filter(|c| !c.is_whitespace()).collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err(String::from("odd number of hex digits"));
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let pair: String = pair.iter().collect();
+            u8::from_str_radix(&pair, 16).map_err(|_| format!("invalid hex byte {}", pair))
+        })
+        .collect()
+}
+
+fn parse_str(literal: &str) -> Result<Vec<u8>, String> {
+    let inner = literal
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .ok_or_else(|| String::from("string must be enclosed in double quotes"))?;
+
+    let mut bytes = vec![];
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buffer = [0; 4];
+            bytes.extend(c.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => bytes.push(b'\\'),
+            Some('"') => bytes.push(b'"'),
+            Some('0') => bytes.push(0),
+            Some('t') => bytes.push(b'\t'),
+            Some('n') => bytes.push(b'\n'),
+            Some('r') => bytes.push(b'\r'),
+            Some('x') => {
+                let digits: String = chars.by_ref().take(2).collect();
+                bytes.push(
+                    u8::from_str_radix(&digits, 16)
+                        .map_err(|_| format!("invalid escape \\x{}", digits))?,
+                );
+            }
+            Some(c) => return Err(format!("unknown escape \\{}", c)),
+            None => return Err(String::from("unterminated escape")),
+        }
+    }
+    Ok(bytes)
+}
+
+fn parse_crc(value: &str) -> Result<u32, String> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"));
+    match digits {
+        Some(digits) => u32::from_str_radix(digits, 16),
+        None => value.parse(),
+    }
+    .map_err(|_| format!("invalid CRC {}", value))
+}
+
+/// Builds the PNG file from its assembly listing
+pub fn assemble(source: &str) -> Result<Png, AssemblyError> {
+    let mut chunks = vec![];
+    let mut trailer: Option<Vec<u8>> = None;
+    let mut pending: Option<PendingChunk> = None;
+
+    for (index, line) in source.lines().enumerate() {
+        let syntax = |message: String| AssemblyError::Syntax {
+            line: index + 1,
+            message,
+        };
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (directive, operand) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let operand = operand.trim();
+
+        match (directive, pending.as_mut()) {
+            ("chunk" | "trailer", None) if trailer.is_some() => {
+                return Err(syntax(format!("{} directive after the trailer", directive)))
+            }
+            ("trailer", None) if operand.is_empty() => {
+                pending = Some(PendingChunk {
+                    chunk_type: None,
+                    crc: None,
+                    data: vec![],
+                });
+            }
+            ("chunk", None) => {
+                let mut words = operand.split_whitespace();
+                let chunk_type = words.next().unwrap_or_default();
+                let chunk_type = ChunkType::from_str(chunk_type)
+                    .map_err(|_| syntax(format!("invalid chunk type {}", chunk_type)))?;
+                let crc = match (words.next(), words.next(), words.next()) {
+                    (None, _, _) => None,
+                    (Some("crc"), Some(crc), None) => Some(parse_crc(crc).map_err(syntax)?),
+                    _ => return Err(syntax(String::from("expected crc after the chunk type"))),
+                };
+                pending = Some(PendingChunk {
+                    chunk_type: Some(chunk_type),
+                    crc,
+                    data: vec![],
+                });
+            }
+            ("hex", Some(chunk)) => chunk.data.extend(parse_hex(operand).map_err(syntax)?),
+            ("str", Some(chunk)) => chunk.data.extend(parse_str(operand).map_err(syntax)?),
+            ("end", Some(_)) if operand.is_empty() => {
+                let chunk = pending.take().expect("pending chunk is matched");
+                match (chunk.chunk_type, chunk.crc) {
+                    (Some(chunk_type), Some(crc)) => {
+                        chunks.push(Chunk::with_crc(chunk_type, chunk.data, crc))
+                    }
+                    (Some(chunk_type), None) => chunks.push(Chunk::new(chunk_type, chunk.data)),
+                    (None, _) => trailer = Some(chunk.data),
+                }
+            }
+            ("chunk" | "trailer", Some(_)) => {
+                return Err(syntax(String::from(
+                    "previous chunk isn't terminated with the end directive",
+                )))
+            }
+            ("hex" | "str" | "end", None) => {
+                return Err(syntax(format!(
+                    "{} directive outside of a chunk",
+                    directive
+                )))
+            }
+            _ => return Err(syntax(format!("unknown directive {}", line))),
+        }
+    }
+
+    match pending {
+        Some(_) => Err(AssemblyError::UnterminatedChunk),
+        None => {
+            let mut png = Png::from_chunks(chunks);
+            png.set_trailer(trailer.unwrap_or_default());
+            Ok(png)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(chunk_type: &str, data: &[u8]) -> Chunk {
+        Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data.to_vec())
+    }
+
+    fn testing_png() -> Png {
+        Png::from_chunks(vec![
+            chunk("IHDR", &[0, 0, 0, 4, 0, 0, 0, 3, 8, 2, 0, 0, 0]),
+            chunk("tEXt", b"Comment\0a \"quoted\"; line\nand more\\"),
+            chunk("IDAT", &(0..=255).collect::<Vec<u8>>()),
+            Chunk::with_crc(ChunkType::from_str("ruSt").unwrap(), b"x".to_vec(), 7),
+            chunk("IEND", &[]),
+        ])
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let listing = disassemble(&testing_png());
+        assert!(listing.starts_with("chunk IHDR ; critical, public, unsafe to copy\n  hex 00000004 00000003 08020000 00\nend\n"));
+        assert!(listing
+            .contains("  str \"Comment\\0a \\\"quoted\\\"; line\\n\"\n  str \"and more\\\\\"\n"));
+        assert!(listing.contains("chunk ruSt crc 0x00000007 ; ancillary, private, safe to copy\n"));
+        assert!(listing.ends_with("chunk IEND ; critical, public, unsafe to copy\nend\n"));
+    }
+
+    #[test]
+    fn test_assemble_roundtrip() {
+        let png = testing_png();
+        let assembled = assemble(&disassemble(&png)).unwrap();
+        assert_eq!(assembled.as_bytes(), png.as_bytes());
+    }
+
+    #[test]
+    fn test_assemble_trailer_roundtrip() {
+        let mut png = testing_png();
+        png.set_trailer(b"PK\x03\x04".to_vec());
+        let listing = disassemble(&png);
+        assert!(listing.ends_with("trailer ; data after the last chunk\n  hex 504b0304\nend\n"));
+        assert_eq!(assemble(&listing).unwrap().as_bytes(), png.as_bytes());
+
+        assert!(matches!(
+            assemble("trailer\nend\nchunk IEND\nend"),
+            Err(AssemblyError::Syntax { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_assemble_recomputes_crc() {
+        let png = assemble("chunk IEND ; trailing comment\nend\n").unwrap();
+        assert_eq!(png.chunks()[0].crc(), 2923585666);
+
+        let png = assemble("chunk ruSt crc 12\n  str \"\\x41B\"\n  hex 43\nend").unwrap();
+        assert_eq!(png.chunks()[0].crc(), 12);
+        assert_eq!(png.chunks()[0].data(), b"ABC");
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        assert!(matches!(
+            assemble("chunk IHDR\n  hex 0\nend"),
+            Err(AssemblyError::Syntax { line: 2, .. })
+        ));
+        assert!(matches!(
+            assemble("hex 00"),
+            Err(AssemblyError::Syntax { line: 1, .. })
+        ));
+        assert!(matches!(
+            assemble("chunk IHDR\nchunk IEND\nend"),
+            Err(AssemblyError::Syntax { line: 2, .. })
+        ));
+        assert!(matches!(
+            assemble("chunk 1234\nend"),
+            Err(AssemblyError::Syntax { line: 1, .. })
+        ));
+        assert!(matches!(
+            assemble("chunk IEND"),
+            Err(AssemblyError::UnterminatedChunk)
+        ));
+    }
+}
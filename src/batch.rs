@@ -0,0 +1,279 @@
+/*!
+ * # Batch crate
+ *
+ * Defines the processing of many PNG files with a single invocation. Paths are expanded from files, directories and
+ * glob patterns, and the files are processed in parallel by a fixed number of worker threads.
+ *
+ */
+
+use std::collections::HashSet;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+const PNG_EXTENSION: &str = "png";
+const GLOB_CHARACTERS: [char; 3] = ['*', '?', '['];
+
+#[derive(Debug)]
+pub enum BatchError {
+    Filesystem(io::Error),
+    Pattern(glob::PatternError),
+    NoMatches(String),
+}
+
+impl From<io::Error> for BatchError {
+    fn from(item: io::Error) -> BatchError {
+        BatchError::Filesystem(item)
+    }
+}
+
+impl From<glob::PatternError> for BatchError {
+    fn from(item: glob::PatternError) -> BatchError {
+        BatchError::Pattern(item)
+    }
+}
+
+impl From<glob::GlobError> for BatchError {
+    fn from(item: glob::GlobError) -> BatchError {
+        BatchError::Filesystem(item.into())
+    }
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BatchError::Filesystem(ref err) => write!(f, "Filesystem error: {}", err),
+            BatchError::Pattern(ref err) => write!(f, "Invalid glob pattern: {}", err),
+            BatchError::NoMatches(ref pattern) => write!(f, "No PNG files match {}.", pattern),
+        }
+    }
+}
+
+impl error::Error for BatchError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            BatchError::Filesystem(ref err) => Some(err),
+            BatchError::Pattern(ref err) => Some(err),
+            BatchError::NoMatches(_) => None,
+        }
+    }
+}
+
+/// Outcome of processing a single file of the batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Succeeded,
+    Failed(String),
+    /// File wasn't processed, because an earlier file failed
+    Skipped,
+}
+
+/**
+ * Returns whether the path is a pattern that has to be expanded instead of a path to a single file.
+ *
+ * An existing file is never a pattern, even if its name contains glob characters, e.g. `img[1].png`.
+ */
+pub fn is_pattern(path: &Path) -> bool {
+    path.is_dir() || (!path.exists() && path.to_string_lossy().contains(GLOB_CHARACTERS))
+}
+
+fn is_png(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case(PNG_EXTENSION))
+}
+
+/// Collects the PNG files of the directory, descending into the sub-directories if recursive
+fn collect_directory(
+    dir: &Path,
+    recursive: bool,
+    paths: &mut Vec<PathBuf>,
+) -> Result<(), BatchError> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    entries.sort();
+
+    for entry in entries {
+        if entry.is_dir() {
+            if recursive {
+                collect_directory(&entry, recursive, paths)?;
+            }
+        } else if is_png(&entry) {
+            paths.push(entry);
+        }
+    }
+    Ok(())
+}
+
+/**
+ * Expands the paths to the files of the batch, keeping the order in which they are given and removing duplicates.
+ *
+ * Directories are expanded to the PNG files they contain and glob patterns to the files they match. Any other path
+ * is kept as it is, so that a missing file is reported as a failure of that file.
+ */
+pub fn expand(paths: &[PathBuf], recursive: bool) -> Result<Vec<PathBuf>, BatchError> {
+    let mut expanded = Vec::new();
+    for path in paths {
+        let start = expanded.len();
+        if path.is_dir() {
+            collect_directory(path, recursive, &mut expanded)?;
+        } else if is_pattern(path) {
+            for entry in glob::glob(&path.to_string_lossy())? {
+                let entry = entry?;
+                if entry.is_dir() {
+                    collect_directory(&entry, recursive, &mut expanded)?;
+                } else {
+                    expanded.push(entry);
+                }
+            }
+        } else {
+            expanded.push(path.clone());
+        }
+
+        if expanded.len() == start && is_pattern(path) {
+            return Err(BatchError::NoMatches(path.display().to_string()));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    expanded.retain(|path| seen.insert(path.clone()));
+    Ok(expanded)
+}
+
+/**
+ * Processes the files in parallel and returns the status of every file, in the order of the files.
+ *
+ * Unless the processing should continue on error, files that weren't started before the first failure are skipped.
+ */
+pub fn run<F, E>(paths: &[PathBuf], jobs: usize, continue_on_error: bool, process: F) -> Vec<Status>
+where
+    F: Fn(&Path) -> Result<(), E> + Sync,
+    E: fmt::Display,
+{
+    let statuses = Mutex::new(vec![Status::Skipped; paths.len()]);
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, paths.len().max(1)) {
+            scope.spawn(|| loop {
+                if failed.load(Ordering::SeqCst) && !continue_on_error {
+                    break;
+                }
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(path) = paths.get(index) else {
+                    break;
+                };
+
+                let status = match process(path) {
+                    Ok(()) => Status::Succeeded,
+                    Err(err) => {
+                        failed.store(true, Ordering::SeqCst);
+                        Status::Failed(err.to_string())
+                    }
+                };
+                statuses.lock().unwrap()[index] = status;
+            });
+        }
+    });
+
+    statuses.into_inner().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn testing_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pngme-batch-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        for file in ["b.png", "a.PNG", "notes.txt", "nested/c.png"] {
+            fs::write(dir.join(file), []).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_expand_directory() {
+        let dir = testing_dir("directory");
+        let flat = expand(std::slice::from_ref(&dir), false).unwrap();
+        assert_eq!(flat, vec![dir.join("a.PNG"), dir.join("b.png")]);
+
+        let recursive = expand(std::slice::from_ref(&dir), true).unwrap();
+        assert_eq!(
+            recursive,
+            vec![
+                dir.join("a.PNG"),
+                dir.join("b.png"),
+                dir.join("nested/c.png")
+            ]
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_expand_glob_and_files() {
+        let dir = testing_dir("glob");
+        let paths = expand(
+            &[
+                dir.join("b.png"),
+                dir.join("*.png"),
+                PathBuf::from("missing.png"),
+            ],
+            false,
+        )
+        .unwrap();
+        assert_eq!(paths, vec![dir.join("b.png"), PathBuf::from("missing.png")]);
+
+        assert!(matches!(
+            expand(&[dir.join("*.gif")], false),
+            Err(BatchError::NoMatches(_))
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_existing_file_is_not_pattern() {
+        let dir = testing_dir("literal");
+        let literal = dir.join("img[1].png");
+        assert!(is_pattern(&literal));
+        fs::write(&literal, []).unwrap();
+        assert!(!is_pattern(&literal));
+        assert_eq!(
+            expand(std::slice::from_ref(&literal), false).unwrap(),
+            vec![literal]
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_run() {
+        let paths: Vec<PathBuf> = ["a", "fail", "b"].iter().map(PathBuf::from).collect();
+        let process = |path: &Path| match path.to_str() {
+            Some("fail") => Err("failed"),
+            _ => Ok(()),
+        };
+
+        let statuses = run(&paths, 4, true, process);
+        assert_eq!(
+            statuses,
+            vec![
+                Status::Succeeded,
+                Status::Failed(String::from("failed")),
+                Status::Succeeded
+            ]
+        );
+
+        let statuses = run(&paths, 1, false, process);
+        assert_eq!(statuses[1], Status::Failed(String::from("failed")));
+        assert_eq!(statuses[2], Status::Skipped);
+    }
+}
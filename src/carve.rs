@@ -0,0 +1,292 @@
+/*!
+ * # Carve crate
+ *
+ * Defines the recovery of PNG files from arbitrary binary data, such as disk images or memory dumps. Every occurrence
+ * of the PNG signature is a candidate, whose chunks are walked and validated by their CRC until the `IEND` chunk to
+ * find the true extent of the file.
+ *
+ * Candidates that end before the `IEND` chunk, because the data is truncated or a chunk is corrupted, are reported as
+ * damaged, together with the extent of their valid chunks.
+ *
+ * The data is streamed in blocks and the chunks are read one by one, so neither the binary data nor a single chunk is
+ * ever held in memory as a whole, which keeps the memory use flat when scanning a disk image of several gigabytes.
+ *
+ */
+
+use crate::chunk_type::ChunkType;
+use crate::png::Png;
+use crc::{Crc, CRC_32_ISO_HDLC};
+use std::fmt;
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+
+const IMAGE_HEADER: [u8; 4] = *b"IHDR";
+const IMAGE_TRAILER: [u8; 4] = *b"IEND";
+const LENGTH_SIZE: usize = 4;
+const TYPE_SIZE: usize = 4;
+const CRC_SIZE: usize = 4;
+/// Maximum chunk length allowed by the PNG specification
+const MAX_LENGTH: u32 = (1 << 31) - 1;
+/// Number of bytes read at once while scanning for signatures and checking the chunk CRCs
+const BLOCK_SIZE: usize = 64 * 1024;
+static CHECKSUM: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+
+/// Reason a candidate couldn't be recovered completely
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Damage {
+    Truncated(usize),
+    InvalidLength(usize),
+    InvalidChunkType(usize),
+    InvalidCrc(usize, String),
+    MissingImageHeader,
+}
+
+impl fmt::Display for Damage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Damage::Truncated(index) => write!(f, "data truncated in chunk {}", index),
+            Damage::InvalidLength(index) => write!(f, "invalid length of chunk {}", index),
+            Damage::InvalidChunkType(index) => write!(f, "invalid type of chunk {}", index),
+            Damage::InvalidCrc(index, ref chunk_type) => {
+                write!(f, "invalid CRC of chunk {} ({})", index, chunk_type)
+            }
+            Damage::MissingImageHeader => write!(f, "first chunk isn't IHDR"),
+        }
+    }
+}
+
+/// PNG file found in the binary data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    /// Offset of the PNG signature in the binary data
+    pub offset: usize,
+    /// Length of the signature and the valid chunks, up to and including the `IEND` chunk if the file is complete
+    pub length: usize,
+    /// Number of valid chunks
+    pub chunks: usize,
+    /// Reason the file is incomplete, if it is damaged
+    pub damage: Option<Damage>,
+}
+
+impl Candidate {
+    /// Copies the bytes of the recovered file from the binary data the candidate was found in to the writer
+    pub fn copy<R: Read + Seek, W: Write>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> io::Result<u64> {
+        reader.seek(SeekFrom::Start(self.offset as u64))?;
+        io::copy(&mut reader.take(self.length as u64), writer)
+    }
+
+    pub fn is_damaged(&self) -> bool {
+        self.damage.is_some()
+    }
+}
+
+/// Fills the buffer, returning `false` instead of an error if the data ends before it is full
+fn read_exact_or_end<R: Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<bool> {
+    match reader.read_exact(buffer) {
+        Ok(()) => Ok(true),
+        Err(error) if error.kind() == ErrorKind::UnexpectedEof => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+/// Reads the chunk data in blocks, returning its CRC or `None` if the data ends before the chunk does
+fn read_checksum<R: Read>(
+    reader: &mut R,
+    chunk_type: &[u8],
+    length: u32,
+) -> io::Result<Option<u32>> {
+    let mut digest = CHECKSUM.digest();
+    digest.update(chunk_type);
+    let mut block = vec![0; BLOCK_SIZE.min(length as usize)];
+    let mut remaining = length as usize;
+    while remaining > 0 {
+        let size = remaining.min(block.len());
+        if !read_exact_or_end(reader, &mut block[..size])? {
+            return Ok(None);
+        }
+        digest.update(&block[..size]);
+        remaining -= size;
+    }
+    Ok(Some(digest.finalize()))
+}
+
+/// Walks the chunks that start at the offset, stopping after the `IEND` chunk or at the first damaged chunk
+fn walk<R: Read + Seek>(reader: &mut R, offset: usize) -> io::Result<Candidate> {
+    let mut candidate = Candidate {
+        offset,
+        length: Png::STANDARD_HEADER.len(),
+        chunks: 0,
+        damage: None,
+    };
+    reader.seek(SeekFrom::Start((offset + candidate.length) as u64))?;
+
+    loop {
+        let index = candidate.chunks;
+        let mut prefix = [0; LENGTH_SIZE + TYPE_SIZE];
+        if !read_exact_or_end(reader, &mut prefix)? {
+            candidate.damage = Some(Damage::Truncated(index));
+            return Ok(candidate);
+        }
+        let length = u32::from_be_bytes(prefix[..LENGTH_SIZE].try_into().unwrap());
+        if length > MAX_LENGTH {
+            candidate.damage = Some(Damage::InvalidLength(index));
+            return Ok(candidate);
+        }
+        let chunk_type = <[u8; 4]>::try_from(&prefix[LENGTH_SIZE..]).unwrap();
+        if ChunkType::try_from(chunk_type).is_err() {
+            candidate.damage = Some(Damage::InvalidChunkType(index));
+            return Ok(candidate);
+        }
+
+        let mut crc = [0; CRC_SIZE];
+        let checksum = match read_checksum(reader, &chunk_type, length)? {
+            Some(checksum) if read_exact_or_end(reader, &mut crc)? => checksum,
+            _ => {
+                candidate.damage = Some(Damage::Truncated(index));
+                return Ok(candidate);
+            }
+        };
+        if index == 0 && chunk_type != IMAGE_HEADER {
+            candidate.damage = Some(Damage::MissingImageHeader);
+            return Ok(candidate);
+        }
+        if checksum != u32::from_be_bytes(crc) {
+            let chunk_type = String::from_utf8_lossy(&chunk_type).into_owned();
+            candidate.damage = Some(Damage::InvalidCrc(index, chunk_type));
+            return Ok(candidate);
+        }
+
+        candidate.length += LENGTH_SIZE + TYPE_SIZE + length as usize + CRC_SIZE;
+        candidate.chunks += 1;
+        if chunk_type == IMAGE_TRAILER {
+            return Ok(candidate);
+        }
+    }
+}
+
+/// Streams the binary data in blocks and returns the offsets of the PNG signatures, including the ones across blocks
+fn find_signatures<R: Read + Seek>(reader: &mut R) -> io::Result<Vec<usize>> {
+    let signature = Png::STANDARD_HEADER;
+    let mut offsets = Vec::new();
+    let mut block = vec![0; BLOCK_SIZE];
+    // NOTE: the window keeps the end of the previous block, which may hold the start of a signature
+    let mut window = Vec::with_capacity(BLOCK_SIZE + signature.len());
+    let mut position = 0;
+    reader.seek(SeekFrom::Start(0))?;
+
+    loop {
+        let size = match reader.read(&mut block) {
+            Ok(0) => return Ok(offsets),
+            Ok(size) => size,
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+        window.extend_from_slice(&block[..size]);
+        offsets.extend(
+            window
+                .windows(signature.len())
+                .enumerate()
+                .filter(|(_, bytes)| *bytes == signature)
+                .map(|(index, _)| position + index),
+        );
+        let consumed = window.len() - window.len().min(signature.len() - 1);
+        window.drain(..consumed);
+        position += consumed;
+    }
+}
+
+/**
+ * Scans the binary data for PNG signatures and walks the chunks of each of them, in the order they are found.
+ *
+ * Every signature is a candidate, including the ones inside another recovered file, e.g. an embedded thumbnail.
+ */
+pub fn scan<R: Read + Seek>(reader: &mut R) -> io::Result<Vec<Candidate>> {
+    find_signatures(reader)?
+        .into_iter()
+        .map(|offset| walk(reader, offset))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::Chunk;
+    use std::io::Cursor;
+    use std::str::FromStr;
+
+    fn testing_png() -> Vec<u8> {
+        Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![0; 13]),
+            Chunk::new(ChunkType::from_str("IDAT").unwrap(), vec![1, 2, 3]),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ])
+        .as_bytes()
+    }
+
+    #[test]
+    fn test_scan_complete() {
+        let png = testing_png();
+        let mut blob = vec![0xaa; 100];
+        blob.extend(&png);
+        blob.extend([0xbb; 50]);
+
+        let candidates = scan(&mut Cursor::new(&blob)).unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].offset, 100);
+        assert_eq!(candidates[0].length, png.len());
+        assert_eq!(candidates[0].chunks, 3);
+        assert!(!candidates[0].is_damaged());
+        let mut recovered = Vec::new();
+        candidates[0]
+            .copy(&mut Cursor::new(&blob), &mut recovered)
+            .unwrap();
+        assert_eq!(recovered, png);
+    }
+
+    #[test]
+    fn test_scan_across_blocks() {
+        let png = testing_png();
+        let mut blob = vec![0xaa; BLOCK_SIZE - 3];
+        blob.extend(&png);
+        blob.extend(&png);
+
+        let candidates = scan(&mut Cursor::new(&blob)).unwrap();
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].offset, BLOCK_SIZE - 3);
+        assert_eq!(candidates[1].offset, BLOCK_SIZE - 3 + png.len());
+        assert!(candidates.iter().all(|candidate| !candidate.is_damaged()));
+    }
+
+    #[test]
+    fn test_scan_damaged() {
+        let png = testing_png();
+        let mut corrupted = png.clone();
+        // NOTE: the IDAT data starts after the signature, the IHDR chunk and the IDAT length and type
+        corrupted[8 + 25 + 8] ^= 0xff;
+        let mut blob = corrupted;
+        blob.extend(&png[..png.len() - 6]);
+
+        let candidates = scan(&mut Cursor::new(&blob)).unwrap();
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(
+            candidates[0].damage,
+            Some(Damage::InvalidCrc(1, String::from("IDAT")))
+        );
+        assert_eq!(candidates[0].length, 8 + 25);
+        assert_eq!(candidates[1].offset, png.len());
+        assert_eq!(candidates[1].damage, Some(Damage::Truncated(2)));
+        assert_eq!(candidates[1].chunks, 2);
+    }
+
+    #[test]
+    fn test_scan_false_positive() {
+        let mut blob = Png::STANDARD_HEADER.to_vec();
+        blob.extend(b"\0\0\0\x01ab$d");
+        let candidates = scan(&mut Cursor::new(&blob)).unwrap();
+        assert_eq!(candidates[0].damage, Some(Damage::InvalidChunkType(0)));
+        assert_eq!(candidates[0].chunks, 0);
+    }
+}
@@ -62,7 +62,7 @@ impl error::Error for ChunkError {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Chunk {
     length: u32,
     chunk_type: ChunkType,
@@ -72,9 +72,7 @@ pub struct Chunk {
 
 impl Chunk {
     pub fn new(chunk_type: ChunkType, data: Vec<u8>) -> Chunk {
-        let mut type_and_data: Vec<u8> = chunk_type.bytes().to_vec();
-        type_and_data.extend(&data);
-        let crc = Crc::<u32>::new(&CRC_32_ISO_HDLC).checksum(type_and_data.as_slice());
+        let crc = Chunk::calculate_crc(&chunk_type, &data);
         Chunk {
             length: data.len() as u32,
             chunk_type,
@@ -83,44 +81,24 @@ impl Chunk {
         }
     }
 
-    pub fn length(&self) -> u32 {
-        self.length
-    }
-
-    pub fn chunk_type(&self) -> &ChunkType {
-        self.chunk_type.borrow()
-    }
-
-    pub fn data(&self) -> &[u8] {
-        self.data.iter().as_slice()
-    }
-
-    pub fn crc(&self) -> u32 {
-        self.crc
-    }
-
-    #[allow(dead_code)] // NOTE: intentionally, not used for now
-    pub fn data_as_string(&self) -> Result<String, ChunkError> {
-        str::from_utf8(&self.data)
-            .map(|s| s.to_owned())
-            .map_err(ChunkError::Conversion)
+    /// Creates a chunk with the provided CRC, even if it doesn't match the chunk data
+    pub fn with_crc(chunk_type: ChunkType, data: Vec<u8>, crc: u32) -> Chunk {
+        Chunk {
+            length: data.len() as u32,
+            chunk_type,
+            data,
+            crc,
+        }
     }
 
-    pub fn as_bytes(&self) -> Vec<u8> {
-        self.length
-            .to_be_bytes()
-            .iter()
-            .chain(self.chunk_type.bytes().iter())
-            .chain(self.data.iter())
-            .chain(self.crc.to_be_bytes().iter())
-            .copied()
-            .collect()
+    fn calculate_crc(chunk_type: &ChunkType, data: &[u8]) -> u32 {
+        let mut type_and_data: Vec<u8> = chunk_type.bytes().to_vec();
+        type_and_data.extend(data);
+        Crc::<u32>::new(&CRC_32_ISO_HDLC).checksum(type_and_data.as_slice())
     }
-}
 
-impl TryFrom<&[u8]> for Chunk {
-    type Error = ChunkError;
-    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+    /// Parses a chunk while keeping the provided CRC, even if it doesn't match the chunk data
+    pub fn try_from_unverified(value: &[u8]) -> Result<Chunk, ChunkError> {
         let mut value_iter = value.iter().copied();
 
         let length = u32::from_be_bytes(
@@ -156,8 +134,58 @@ impl TryFrom<&[u8]> for Chunk {
                 .map_err(|_| ChunkError::InvalidCrc)?,
         );
 
-        let chunk = Self::new(chunk_type, data);
-        if chunk.crc != crc {
+        Ok(Chunk {
+            length,
+            chunk_type,
+            data,
+            crc,
+        })
+    }
+
+    pub fn has_valid_crc(&self) -> bool {
+        Chunk::calculate_crc(&self.chunk_type, &self.data) == self.crc
+    }
+
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+
+    pub fn chunk_type(&self) -> &ChunkType {
+        self.chunk_type.borrow()
+    }
+
+    pub fn data(&self) -> &[u8] {
+        self.data.iter().as_slice()
+    }
+
+    pub fn crc(&self) -> u32 {
+        self.crc
+    }
+
+    #[allow(dead_code)] // NOTE: intentionally, not used for now
+    pub fn data_as_string(&self) -> Result<String, ChunkError> {
+        str::from_utf8(&self.data)
+            .map(|s| s.to_owned())
+            .map_err(ChunkError::Conversion)
+    }
+
+    pub fn as_bytes(&self) -> Vec<u8> {
+        self.length
+            .to_be_bytes()
+            .iter()
+            .chain(self.chunk_type.bytes().iter())
+            .chain(self.data.iter())
+            .chain(self.crc.to_be_bytes().iter())
+            .copied()
+            .collect()
+    }
+}
+
+impl TryFrom<&[u8]> for Chunk {
+    type Error = ChunkError;
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let chunk = Chunk::try_from_unverified(value)?;
+        if !chunk.has_valid_crc() {
             return Err(ChunkError::MismatchCrc);
         }
 
@@ -286,6 +314,30 @@ mod tests {
         assert!(chunk.is_err());
     }
 
+    #[test]
+    fn test_unverified_chunk_from_bytes() {
+        let data_length: u32 = 42;
+        let chunk_type = "RuSt".as_bytes();
+        let message_bytes = "This is where your secret message will be!".as_bytes();
+        let crc: u32 = 2882656333;
+
+        let chunk_data: Vec<u8> = data_length
+            .to_be_bytes()
+            .iter()
+            .chain(chunk_type.iter())
+            .chain(message_bytes.iter())
+            .chain(crc.to_be_bytes().iter())
+            .copied()
+            .collect();
+
+        let chunk = Chunk::try_from_unverified(chunk_data.as_ref()).unwrap();
+
+        assert!(!chunk.has_valid_crc());
+        assert_eq!(chunk.crc(), 2882656333);
+        assert_eq!(chunk.as_bytes(), chunk_data);
+        assert!(testing_chunk().has_valid_crc());
+    }
+
     #[test]
     pub fn test_chunk_trait_impls() {
         let data_length: u32 = 42;
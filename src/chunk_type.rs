@@ -41,7 +41,7 @@ impl error::Error for ChunkTypeError {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ChunkType {
     code: [u8; 4],
 }
@@ -59,22 +59,18 @@ impl ChunkType {
             .count(), 4 if self.is_reserved_bit_valid())
     }
 
-    #[allow(dead_code)] // NOTE: intentionally, not used for now
     pub fn is_critical(&self) -> bool {
         (self.code[ANCILLARY_BYTE] & BIT_OF_INTEREST) >> BIT_SHIFT_NUM == 0
     }
 
-    #[allow(dead_code)] // NOTE: intentionally, not used for now
     pub fn is_public(&self) -> bool {
         (self.code[PRIVATE_BYTE] & BIT_OF_INTEREST) >> BIT_SHIFT_NUM == 0
     }
 
-    #[allow(dead_code)] // NOTE: intentionally, not used for now
     pub fn is_reserved_bit_valid(&self) -> bool {
         (self.code[RESERVED_BYTE] & BIT_OF_INTEREST) >> BIT_SHIFT_NUM == 0
     }
 
-    #[allow(dead_code)] // NOTE: intentionally, not used for now
     pub fn is_safe_to_copy(&self) -> bool {
         (self.code[SAFE_TO_COPY_BYTE] & BIT_OF_INTEREST) >> BIT_SHIFT_NUM == 1
     }
@@ -5,21 +5,70 @@
  *
  */
 
-use crate::args::{DecodeArgs, EncodeArgs, PrintArgs, RemoveArgs};
+use crate::ancillary::{Ancillary, AncillaryError, PhysicalDimensions, Time};
+use crate::apng::{Animation, ApngError};
+use crate::args::{
+    ApngArgs, ApngBuildArgs, ApngCommand, ApngExtractArgs, ApngInfoArgs, AssembleArgs, CarveArgs,
+    CombineDecodeArgs, DecodeArgs, DiffArgs, DisassembleArgs, EncodeArgs, ExifArgs, ExifCommand,
+    ExifRemoveArgs, ExifShowArgs, ExtractArgs, FileCommand, GrepArgs, ImportArgs, MoveArgs,
+    OutputFormat, PrintArgs, RemoveArgs, RenameChunkArgs, ReplaceArgs, SetDpiArgs, ShellArgs,
+    SplitEncodeArgs, StripArgs, StripPreset, TextconvArgs, TouchArgs, TrailerArgs, TrailerCommand,
+    TrailerSetArgs, TrailerShowArgs, TrailerStripArgs, TransplantArgs, ViewArgs, WriteArgs,
+    XmpArgs, XmpCommand, XmpGetArgs, XmpMergeArgs, XmpSetArgs,
+};
+use crate::assembly::{self, AssemblyError};
+use crate::batch::{self, BatchError, Status};
+use crate::carve;
 use crate::chunk::{Chunk, ChunkError};
 use crate::chunk_type::{ChunkType, ChunkTypeError};
+use crate::detail;
+use crate::diff::{self, PngDiff};
+use crate::exif::{self, ByteOrder, Exif, ExifError, Ifd};
+use crate::hexdump;
+use crate::image_header::ImageHeader;
+use crate::output;
 use crate::png::{Png, PngError};
+use crate::reed_solomon::{self, ReedSolomonError};
+use crate::report::{
+    ApngReport, CombineReport, DecodeReport, ExifReport, PrintReport, Report, ReportError,
+    XmpReport, SCHEMA_VERSION,
+};
+use crate::search::{self, SearchError};
+use crate::shamir::{self, ShamirError, Share};
+use crate::shell::{self, Session, ShellError};
+use crate::summary;
+use crate::text::{InternationalText, TextError};
+use crate::view::{self, Viewer};
+use crate::xmp::{Xmp, XmpError};
+use std::collections::{HashMap, HashSet};
 use std::error;
 use std::fmt;
-use std::fs;
-use std::io::Error;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Cursor, Error, Read, Seek, Write};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::thread;
 
 #[derive(Debug)]
 pub enum CommandError {
     Filesystem(Error),
     Png(PngError),
     Chunk(ChunkError),
+    Shamir(ShamirError),
+    Exif(ExifError),
+    Text(TextError),
+    Xmp(XmpError),
+    ReedSolomon(ReedSolomonError),
+    Ancillary(AncillaryError),
+    Apng(ApngError),
+    Report(ReportError),
+    Assembly(AssemblyError),
+    Batch(BatchError),
+    Shell(ShellError),
+    Search(SearchError),
+    BatchFailed { failed: usize, total: usize },
+    NoMatches,
+    InvalidArgument(String),
 }
 
 impl From<std::io::Error> for CommandError {
@@ -40,6 +89,78 @@ impl From<ChunkError> for CommandError {
     }
 }
 
+impl From<ShamirError> for CommandError {
+    fn from(item: ShamirError) -> CommandError {
+        CommandError::Shamir(item)
+    }
+}
+
+impl From<ReedSolomonError> for CommandError {
+    fn from(item: ReedSolomonError) -> CommandError {
+        CommandError::ReedSolomon(item)
+    }
+}
+
+impl From<AncillaryError> for CommandError {
+    fn from(item: AncillaryError) -> CommandError {
+        CommandError::Ancillary(item)
+    }
+}
+
+impl From<ApngError> for CommandError {
+    fn from(item: ApngError) -> CommandError {
+        CommandError::Apng(item)
+    }
+}
+
+impl From<ReportError> for CommandError {
+    fn from(item: ReportError) -> CommandError {
+        CommandError::Report(item)
+    }
+}
+
+impl From<AssemblyError> for CommandError {
+    fn from(item: AssemblyError) -> CommandError {
+        CommandError::Assembly(item)
+    }
+}
+
+impl From<BatchError> for CommandError {
+    fn from(item: BatchError) -> CommandError {
+        CommandError::Batch(item)
+    }
+}
+
+impl From<SearchError> for CommandError {
+    fn from(item: SearchError) -> CommandError {
+        CommandError::Search(item)
+    }
+}
+
+impl From<ShellError> for CommandError {
+    fn from(item: ShellError) -> CommandError {
+        CommandError::Shell(item)
+    }
+}
+
+impl From<ExifError> for CommandError {
+    fn from(item: ExifError) -> CommandError {
+        CommandError::Exif(item)
+    }
+}
+
+impl From<TextError> for CommandError {
+    fn from(item: TextError) -> CommandError {
+        CommandError::Text(item)
+    }
+}
+
+impl From<XmpError> for CommandError {
+    fn from(item: XmpError) -> CommandError {
+        CommandError::Xmp(item)
+    }
+}
+
 impl From<ChunkTypeError> for CommandError {
     fn from(item: ChunkTypeError) -> CommandError {
         CommandError::Chunk(ChunkError::ChunkType(item))
@@ -52,6 +173,23 @@ impl fmt::Display for CommandError {
             CommandError::Filesystem(ref err) => write!(f, "Filesystem error: {}", err),
             CommandError::Png(ref err) => write!(f, "Png error: {}", err),
             CommandError::Chunk(ref err) => write!(f, "Chunk error: {}", err),
+            CommandError::Shamir(ref err) => write!(f, "Secret sharing error: {}", err),
+            CommandError::Exif(ref err) => write!(f, "EXIF error: {}", err),
+            CommandError::Text(ref err) => write!(f, "Text error: {}", err),
+            CommandError::Xmp(ref err) => write!(f, "XMP error: {}", err),
+            CommandError::ReedSolomon(ref err) => write!(f, "Error correction error: {}", err),
+            CommandError::Ancillary(ref err) => write!(f, "Ancillary chunk error: {}", err),
+            CommandError::Apng(ref err) => write!(f, "APNG error: {}", err),
+            CommandError::Report(ref err) => write!(f, "Report error: {}", err),
+            CommandError::Assembly(ref err) => write!(f, "Assembly error: {}", err),
+            CommandError::Batch(ref err) => write!(f, "Batch error: {}", err),
+            CommandError::Shell(ref err) => write!(f, "Shell error: {}", err),
+            CommandError::Search(ref err) => write!(f, "Search error: {}", err),
+            CommandError::BatchFailed { failed, total } => {
+                write!(f, "{} of {} files failed.", failed, total)
+            }
+            CommandError::NoMatches => write!(f, "Pattern doesn't match any chunk."),
+            CommandError::InvalidArgument(ref msg) => write!(f, "Invalid argument: {}", msg),
         }
     }
 }
@@ -62,14 +200,28 @@ impl error::Error for CommandError {
             CommandError::Filesystem(ref err) => Some(err),
             CommandError::Png(ref err) => Some(err),
             CommandError::Chunk(ref err) => Some(err),
+            CommandError::Shamir(ref err) => Some(err),
+            CommandError::Exif(ref err) => Some(err),
+            CommandError::Text(ref err) => Some(err),
+            CommandError::Xmp(ref err) => Some(err),
+            CommandError::ReedSolomon(ref err) => Some(err),
+            CommandError::Ancillary(ref err) => Some(err),
+            CommandError::Apng(ref err) => Some(err),
+            CommandError::Report(ref err) => Some(err),
+            CommandError::Assembly(ref err) => Some(err),
+            CommandError::Batch(ref err) => Some(err),
+            CommandError::Shell(ref err) => Some(err),
+            CommandError::Search(ref err) => Some(err),
+            CommandError::BatchFailed { .. } => None,
+            CommandError::NoMatches => None,
+            CommandError::InvalidArgument(_) => None,
         }
     }
 }
 
-/// Encodes a message into a PNG file and saves the result
-pub fn encode(args: EncodeArgs) -> Result<(), CommandError> {
-    let mut png = Png::try_from(fs::read(&args.file_path)?.as_slice())?;
-    let chunk_type = ChunkType::from_str(&args.chunk_type)?;
+/// Parses a chunk type and checks that it is valid to be written to a PNG file
+fn valid_chunk_type(chunk_type: &str) -> Result<ChunkType, CommandError> {
+    let chunk_type = ChunkType::from_str(chunk_type)?;
 
     if !chunk_type.is_valid() {
         return Err(CommandError::Chunk(ChunkError::ChunkType(
@@ -77,48 +229,1260 @@ pub fn encode(args: EncodeArgs) -> Result<(), CommandError> {
         )));
     }
 
-    png.append_chunk(Chunk::new(chunk_type, args.message.as_bytes().to_vec()));
+    Ok(chunk_type)
+}
+
+/// Refuses to edit a critical chunk, which corrupts the image, unless the edit is forced
+fn unless_critical(chunk_type: &ChunkType, force: bool) -> Result<(), CommandError> {
+    match chunk_type.is_critical() && !force {
+        true => Err(CommandError::InvalidArgument(format!(
+            "{} is a critical chunk type, editing it corrupts the image, use --force to edit it anyway",
+            chunk_type
+        ))),
+        false => Ok(()),
+    }
+}
+
+/// Encodes a message into a PNG file and saves the result
+pub fn encode(args: EncodeArgs) -> Result<(), CommandError> {
+    let mut png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+    let chunk_type = valid_chunk_type(&args.chunk_type)?;
 
-    match args.output_file_path {
-        Some(p) => fs::write(p, png.as_bytes())?,
-        None => fs::write(&args.file_path, png.as_bytes())?,
+    let data = match args.ecc {
+        Some(parity) => reed_solomon::encode(args.message.as_bytes(), parity)?,
+        None => args.message.as_bytes().to_vec(),
     };
 
+    png.insert_chunk_ordered(Chunk::new(chunk_type, data))?;
+
+    let write = WriteArgs {
+        output: args.output_file_path.or(args.write.output),
+        ..args.write
+    };
+    save(&args.file_path, &png, &write)?;
+
     Ok(())
 }
 
 /// Searches for a message hidden in a PNG file and prints the message if one is found
 pub fn decode(args: DecodeArgs) -> Result<(), CommandError> {
-    let png = Png::try_from(fs::read(&args.file_path)?.as_slice())?;
+    let bytes = output::read(&args.file_path)?;
+    // NOTE: with error correction the chunk data may be corrupted, so the chunk CRC can't be trusted
+    let mut png = match args.ecc {
+        Some(_) => Png::try_from_unverified(bytes.as_slice())?,
+        None => Png::try_from(bytes.as_slice())?,
+    };
+    if args.trailing_chunks {
+        png.read_trailing_chunks();
+    }
 
     let chunk = png
         .chunk_by_type(&args.chunk_type)
         .ok_or(CommandError::Png(PngError::NotFoundChunk))?;
 
+    let (message, repaired) = match args.ecc {
+        Some(parity) => {
+            let (message, repaired) = reed_solomon::decode(chunk.data(), parity)?;
+            (message, Some(repaired))
+        }
+        None => (chunk.data().to_vec(), None),
+    };
+
+    if let Some(format) = args.format {
+        let report = DecodeReport {
+            schema_version: SCHEMA_VERSION,
+            file: args.file_path.display().to_string(),
+            chunk_type: args.chunk_type,
+            message: String::from_utf8_lossy(&message).into_owned(),
+            repaired_symbols: repaired,
+        };
+        return print_report(&report, format);
+    }
+
+    if let Some(repaired) = repaired {
+        eprintln!("Repaired {} corrupted symbols.", repaired);
+    }
     println!(
         "{}",
-        std::str::from_utf8(chunk.data()).unwrap_or("No encoded message.")
+        std::str::from_utf8(&message).unwrap_or("No encoded message.")
+    );
+
+    Ok(())
+}
+
+/// Runs the operation on every file of the batch, printing a per-file summary if the operation isn't run on a single file
+pub fn batch<A: FileCommand>(
+    args: A,
+    command: fn(A) -> Result<(), CommandError>,
+) -> Result<(), CommandError> {
+    let batch_args = args.batch();
+    let mut patterns = vec![args.file_path().to_path_buf()];
+    patterns.extend(batch_args.file_paths.iter().cloned());
+    if patterns.len() == 1 && !batch::is_pattern(args.file_path()) {
+        return command(args);
+    }
+
+    let file_paths = batch::expand(&patterns, batch_args.recursive)?;
+    if file_paths.len() > 1 && args.output_file_path().is_some() {
+        return Err(CommandError::InvalidArgument(String::from(
+            "a single output file can't be used with more than one file",
+        )));
+    }
+    // NOTE: files written to an output directory would overwrite each other, so every file gets its own subdirectory
+    let output_dirs = match (file_paths.len() > 1, args.output_dir_path()) {
+        (true, Some(output_dir_path)) => Some(batch_output_dirs(&file_paths, output_dir_path)?),
+        _ => None,
+    };
+    let jobs = batch_args
+        .jobs
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |jobs| jobs.get()));
+
+    let statuses = batch::run(
+        &file_paths,
+        jobs,
+        batch_args.continue_on_error,
+        |file_path| {
+            let args = args.with_file_path(file_path.to_path_buf());
+            match output_dirs.as_ref().and_then(|dirs| dirs.get(file_path)) {
+                Some(output_dir_path) => {
+                    command(args.with_output_dir_path(output_dir_path.clone()))
+                }
+                None => command(args),
+            }
+        },
+    );
+
+    let (mut succeeded, mut failed) = (0, 0);
+    for (file_path, status) in file_paths.iter().zip(&statuses) {
+        match status {
+            Status::Succeeded => {
+                succeeded += 1;
+                eprintln!("ok      {}", file_path.display());
+            }
+            Status::Failed(err) => {
+                failed += 1;
+                eprintln!("failed  {}: {}", file_path.display(), err);
+            }
+            Status::Skipped => eprintln!("skipped {}", file_path.display()),
+        }
+    }
+    eprintln!(
+        "{} files: {} succeeded, {} failed, {} skipped",
+        file_paths.len(),
+        succeeded,
+        failed,
+        file_paths.len() - succeeded - failed
     );
 
+    match failed {
+        0 => Ok(()),
+        failed => Err(CommandError::BatchFailed {
+            failed,
+            total: file_paths.len(),
+        }),
+    }
+}
+
+/// Returns the output subdirectory of every file of a batch, named after the file, rejecting files with the same name
+fn batch_output_dirs(
+    file_paths: &[PathBuf],
+    output_dir_path: &Path,
+) -> Result<HashMap<PathBuf, PathBuf>, CommandError> {
+    let mut output_dirs = HashMap::new();
+    let mut names = HashSet::new();
+    for file_path in file_paths {
+        let name = file_path.file_stem().unwrap_or(file_path.as_os_str());
+        if !names.insert(name.to_owned()) {
+            return Err(CommandError::InvalidArgument(format!(
+                "more than one file is named {}, their output files would overwrite each other",
+                name.to_string_lossy()
+            )));
+        }
+        output_dirs.insert(file_path.clone(), output_dir_path.join(name));
+    }
+    Ok(output_dirs)
+}
+
+/// Returns the path the modified PNG file is written to
+fn output_path<'a>(file_path: &'a Path, write: &'a WriteArgs) -> Result<&'a Path, CommandError> {
+    match write.output.as_deref() {
+        Some(output_path) => Ok(output_path),
+        None if write.in_place && output::is_standard_stream(file_path) => {
+            Err(CommandError::InvalidArgument(String::from(
+                "the standard input can't be modified in place",
+            )))
+        }
+        None => Ok(file_path),
+    }
+}
+
+/// Returns where the messages of an operation that modifies the PNG file are written, so that they never mix with the
+/// PNG file written to the standard output
+fn message_output(file_path: &Path, write: &WriteArgs) -> Box<dyn Write> {
+    match output_path(file_path, write) {
+        Ok(path) if output::is_standard_stream(path) => Box::new(io::stderr()),
+        _ => Box::new(io::stdout()),
+    }
+}
+
+/// Saves the modified PNG file, or lists the changes that would be saved on a dry run
+fn save(file_path: &Path, png: &Png, write: &WriteArgs) -> Result<(), CommandError> {
+    let output_path = output_path(file_path, write)?;
+    if !write.dry_run {
+        output::write_atomic(
+            output_path,
+            &png.as_bytes(),
+            write.backup.as_deref(),
+            write.preserve,
+        )?;
+        return Ok(());
+    }
+
+    let original = Png::try_from_unverified(&output::read(file_path)?)?;
+    let changes = diff::diff_chunks(original.chunks(), png.chunks());
+    let trailer = diff::diff_trailers(&original, png);
+    let mut messages = message_output(file_path, write);
+    writeln!(messages, "Would write {}", output_path.display())?;
+    if changes.is_empty() && trailer.is_none() {
+        writeln!(messages, "  no changes")?;
+    }
+    for change in changes {
+        writeln!(messages, "  {}", change)?;
+    }
+    if let Some(change) = trailer {
+        writeln!(messages, "  {}", change)?;
+    }
+
+    Ok(())
+}
+
+/// Prints the report in the machine readable output format
+fn print_report<R: Report>(report: &R, format: OutputFormat) -> Result<(), CommandError> {
+    let output = match format {
+        OutputFormat::Json => report.to_json()?,
+        OutputFormat::Jsonl => report.to_jsonl()?,
+        OutputFormat::Yaml => report.to_yaml()?,
+    };
+    println!("{}", output.trim_end());
+
     Ok(())
 }
 
 /// Removes a chunk from a PNG file and saves the result
 pub fn remove(args: RemoveArgs) -> Result<(), CommandError> {
-    let mut png = Png::try_from(fs::read(&args.file_path)?.as_slice())?;
+    let mut png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
 
     png.remove_chunk(&args.chunk_type)?;
 
-    fs::write(&args.file_path, png.as_bytes())?;
+    save(&args.file_path, &png, &args.write)?;
 
     Ok(())
 }
 
 /// Prints all of the chunks in a PNG file
 pub fn print_chunks(args: PrintArgs) -> Result<(), CommandError> {
-    let png = Png::try_from(fs::read(args.file_path)?.as_slice())?;
+    let bytes = output::read(&args.file_path)?;
+
+    if let Some(format) = args.format {
+        // NOTE: chunks with a corrupted CRC are reported through the crc_valid field
+        let png = Png::try_from_unverified(bytes.as_slice())?;
+        return print_report(&PrintReport::new(&args.file_path, &png, args.data), format);
+    }
+
+    if let Some(reference) = args.chunk {
+        // NOTE: the CRC status is part of the detail, so a corrupted CRC doesn't fail
+        let png = Png::try_from_unverified(bytes.as_slice())?;
+        let indexes: Vec<usize> = match reference.parse::<usize>() {
+            Ok(index) if index < png.chunks().len() => vec![index],
+            Ok(index) => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "chunk index {} out of range, the file has {} chunks",
+                    index,
+                    png.chunks().len()
+                )))
+            }
+            Err(_) => {
+                let chunk_type = ChunkType::from_str(&reference)?;
+                png.chunks()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, c)| *c.chunk_type() == chunk_type)
+                    .map(|(index, _)| index)
+                    .collect()
+            }
+        };
+        if indexes.is_empty() {
+            return Err(CommandError::Png(PngError::NotFoundChunk));
+        }
+        let details: Vec<String> = indexes
+            .into_iter()
+            .map(|index| detail::describe_chunk(&png, index, args.hexdump, args.max_bytes))
+            .collect();
+        print!("{}", details.join("\n"));
+        return Ok(());
+    }
+
+    let png = Png::try_from(bytes.as_slice())?;
+    if !args.verbose {
+        png.chunks().iter().for_each(|c| println!("{}", c));
+        print_trailer(&png, false);
+        return Ok(());
+    }
+
+    let header = png
+        .chunk_by_type(ImageHeader::CHUNK_TYPE)
+        .and_then(|c| ImageHeader::try_from(c.data()).ok());
+    for chunk in png.chunks() {
+        print!("{}", chunk);
+        let chunk_type = chunk.chunk_type().to_string();
+        if chunk_type == ImageHeader::CHUNK_TYPE {
+            match ImageHeader::try_from(chunk.data()) {
+                Ok(header) => println!("  Header: {}", header),
+                Err(err) => println!("  Decoding error: {}", err),
+            }
+        } else {
+            match Ancillary::parse(&chunk_type, chunk.data(), header.as_ref()) {
+                Ok(Some(ancillary)) => println!("  {}", ancillary),
+                Ok(None) => {}
+                Err(err) => println!("  Decoding error: {}", err),
+            }
+        }
+        println!();
+    }
+    print_trailer(&png, true);
+
+    Ok(())
+}
+
+const TRAILER_PREVIEW_BYTES: usize = 64;
+
+/// Prints the data after the last chunk if there is any, with a hex dump of its first bytes if verbose
+fn print_trailer(png: &Png, verbose: bool) {
+    if png.trailer().is_empty() {
+        return;
+    }
+    println!("Trailer {{");
+    println!("  Offset: {}", png.trailer_offset());
+    println!("  Data: {} bytes", png.trailer().len());
+    println!("}}");
+    if verbose {
+        let shown = png.trailer().len().min(TRAILER_PREVIEW_BYTES);
+        print!(
+            "{}",
+            hexdump::hexdump(&png.trailer()[..shown], png.trailer_offset())
+        );
+        if shown < png.trailer().len() {
+            println!("  ... {} more bytes", png.trailer().len() - shown);
+        }
+    }
+}
+
+/// Splits a message into secret shares and encodes one share into each of the PNG files
+pub fn split_encode(args: SplitEncodeArgs) -> Result<(), CommandError> {
+    if args.file_paths.len() != args.shares as usize {
+        return Err(CommandError::InvalidArgument(format!(
+            "{} shares requested but {} files provided",
+            args.shares,
+            args.file_paths.len()
+        )));
+    }
+
+    if args.write.output.is_some() {
+        return Err(CommandError::InvalidArgument(String::from(
+            "shares are written to the PNG files they are encoded in",
+        )));
+    }
+
+    valid_chunk_type(&args.chunk_type)?;
+    let shares = shamir::split(args.message.as_bytes(), args.shares, args.threshold)?;
+
+    let mut pngs = vec![];
+    for path in args.file_paths.iter() {
+        pngs.push(Png::try_from(output::read(path)?.as_slice())?);
+    }
+
+    let chunk_type = ChunkType::from_str(&args.chunk_type)?;
+    for ((path, mut png), share) in args.file_paths.iter().zip(pngs).zip(shares) {
+        // NOTE: the shares of a previous split are replaced, so that they can't be combined instead of the new ones
+        png.remove_chunks(|chunk| *chunk.chunk_type() == chunk_type);
+        png.insert_chunk_ordered(Chunk::new(chunk_type.clone(), share.as_bytes()))?;
+        save(path, &png, &args.write)?;
+    }
+
+    Ok(())
+}
+
+/// Reconstructs a message from the secret shares encoded in the PNG files and prints the message
+pub fn combine_decode(args: CombineDecodeArgs) -> Result<(), CommandError> {
+    let chunk_type = valid_chunk_type(&args.chunk_type)?;
+    let mut shares = vec![];
+    for path in args.file_paths.iter() {
+        let png = Png::try_from(output::read(path)?.as_slice())?;
+        for (index, chunk) in png.chunks_of_type(Some(&chunk_type)) {
+            match Share::try_from(chunk.data()) {
+                Ok(share) => shares.push(share),
+                Err(err) => eprintln!("skipped {} chunk {}: {}", path.display(), index, err),
+            }
+        }
+    }
+
+    let sets = shamir::group(&shares).len();
+    if sets > 1 {
+        eprintln!("Found shares of {} different splits.", sets);
+    }
+    let secret = shamir::combine_sets(&shares)?;
+
+    if let Some(format) = args.format {
+        let report = CombineReport {
+            schema_version: SCHEMA_VERSION,
+            files: args
+                .file_paths
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect(),
+            chunk_type: args.chunk_type,
+            shares: shares.len(),
+            message: String::from_utf8_lossy(&secret).into_owned(),
+        };
+        return print_report(&report, format);
+    }
+
+    println!(
+        "{}",
+        std::str::from_utf8(&secret).unwrap_or("No encoded message.")
+    );
+
+    Ok(())
+}
+
+/// Replaces the data of a chunk in a PNG file and saves the result
+pub fn replace(args: ReplaceArgs) -> Result<(), CommandError> {
+    let mut png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+    let chunk_type = valid_chunk_type(&args.chunk_type)?;
+    unless_critical(&chunk_type, args.force)?;
+
+    png.replace_chunk(Chunk::new(chunk_type, args.message.as_bytes().to_vec()))?;
+
+    save(&args.file_path, &png, &args.write)?;
+
+    Ok(())
+}
+
+/// Changes the type of a chunk in a PNG file and saves the result
+pub fn rename_chunk(args: RenameChunkArgs) -> Result<(), CommandError> {
+    let mut png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+    let new_chunk_type = valid_chunk_type(&args.new_chunk_type)?;
+    unless_critical(&ChunkType::from_str(&args.chunk_type)?, args.force)?;
+    unless_critical(&new_chunk_type, args.force)?;
+
+    png.rename_chunk(&args.chunk_type, new_chunk_type)?;
+
+    save(&args.file_path, &png, &args.write)?;
+
+    Ok(())
+}
+
+/// Moves a chunk before or after another chunk in a PNG file and saves the result
+pub fn move_chunk(args: MoveArgs) -> Result<(), CommandError> {
+    let mut png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+
+    let (anchor_type, after) = match (&args.before, &args.after) {
+        (Some(before), None) => (before, false),
+        (None, Some(after)) => (after, true),
+        _ => {
+            return Err(CommandError::InvalidArgument(String::from(
+                "exactly one of --before or --after must be provided",
+            )))
+        }
+    };
+
+    let from = png
+        .chunk_position(&args.chunk_type)?
+        .ok_or(PngError::NotFoundChunk)?;
+    let anchor = png
+        .chunk_position(anchor_type)?
+        .ok_or(PngError::NotFoundChunk)?;
+
+    if from == anchor {
+        return Err(CommandError::InvalidArgument(String::from(
+            "chunk can't be moved relative to itself",
+        )));
+    }
+
+    // NOTE: removing the moved chunk shifts the anchor to the left if the anchor is after it
+    let anchor = if from < anchor { anchor - 1 } else { anchor };
+    let to = if after { anchor + 1 } else { anchor };
+
+    png.move_chunk(from, to)?;
+
+    save(&args.file_path, &png, &args.write)?;
+
+    Ok(())
+}
+
+/// Writes the chunks of a PNG file, optionally filtered by type, to separate files
+pub fn extract(args: ExtractArgs) -> Result<(), CommandError> {
+    let png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+    let chunk_type = args
+        .chunk_type
+        .as_deref()
+        .map(ChunkType::from_str)
+        .transpose()?;
+
+    fs::create_dir_all(&args.output_dir_path)?;
+
+    let chunks = png.chunks_of_type(chunk_type.as_ref());
+    if chunks.is_empty() {
+        return Err(CommandError::Png(PngError::NotFoundChunk));
+    }
+
+    for (index, chunk) in chunks {
+        let (extension, bytes) = match args.record {
+            true => ("chunk", chunk.as_bytes()),
+            false => ("bin", chunk.data().to_vec()),
+        };
+        let path =
+            args.output_dir_path
+                .join(format!("{}_{}.{}", index, chunk.chunk_type(), extension));
+        fs::write(&path, bytes)?;
+        println!("{}", path.display());
+    }
+
+    Ok(())
+}
+
+/// Inserts previously extracted chunk records into a PNG file and saves the result
+pub fn import(args: ImportArgs) -> Result<(), CommandError> {
+    let mut png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+
+    for path in args.chunk_file_paths.iter() {
+        png.import_chunk_record(&fs::read(path)?)?;
+    }
+
+    save(&args.file_path, &png, &args.write)?;
+
+    Ok(())
+}
+
+/// Copies the ancillary chunks of one PNG file into another PNG file and saves the result
+pub fn transplant(args: TransplantArgs) -> Result<(), CommandError> {
+    let source = Png::try_from(output::read(&args.source_file_path)?.as_slice())?;
+    let mut png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+
+    let report = png.transplant(&source, args.force)?;
+
+    let mut messages = message_output(&args.file_path, &args.write);
+    for line in report.iter() {
+        writeln!(messages, "{}", line)?;
+    }
+
+    save(&args.file_path, &png, &args.write)?;
+
+    Ok(())
+}
+
+/// Textual chunk types
+const TEXT_CHUNK_TYPES: [&[u8; 4]; 3] = [b"tEXt", b"zTXt", b"iTXt"];
+
+/// Removes the ancillary chunks selected by the preset from a PNG file and saves the result
+pub fn strip(args: StripArgs) -> Result<(), CommandError> {
+    let mut png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+    let keep = args
+        .keep
+        .iter()
+        .map(|t| ChunkType::from_str(t))
+        .collect::<Result<Vec<ChunkType>, ChunkTypeError>>()?;
+
+    let is_stripped = |chunk: &Chunk| {
+        let chunk_type = chunk.chunk_type();
+        let selected = match args.preset {
+            StripPreset::Ancillary => true,
+            StripPreset::Private => !chunk_type.is_public(),
+            StripPreset::Text => TEXT_CHUNK_TYPES.contains(&&chunk_type.bytes()),
+            StripPreset::Exif => &chunk_type.bytes() == b"eXIf",
+        };
+        !chunk_type.is_critical() && selected && !keep.contains(chunk_type)
+    };
+
+    let removed = match args.write.dry_run {
+        true => png
+            .chunks()
+            .iter()
+            .filter(|c| is_stripped(c))
+            .cloned()
+            .collect(),
+        false => png.remove_chunks(is_stripped),
+    };
+
+    let prefix = if args.write.dry_run {
+        "Would remove"
+    } else {
+        "Removed"
+    };
+    let mut messages = message_output(&args.file_path, &args.write);
+    for c in removed.iter() {
+        writeln!(
+            messages,
+            "{} {}: {} bytes",
+            prefix,
+            c.chunk_type(),
+            c.as_bytes().len()
+        )?;
+    }
+    writeln!(
+        messages,
+        "{} {} chunks, saving {} bytes",
+        prefix,
+        removed.len(),
+        removed.iter().map(|c| c.as_bytes().len()).sum::<usize>()
+    )?;
+
+    if !args.write.dry_run {
+        save(&args.file_path, &png, &args.write)?;
+    }
+
+    Ok(())
+}
+
+/// Inspects or edits the EXIF metadata of a PNG file
+pub fn exif(args: ExifArgs) -> Result<(), CommandError> {
+    match args.command {
+        ExifCommand::Show(args) => batch(args, exif_show),
+        ExifCommand::Remove(args) => batch(args, exif_remove),
+    }
+}
+
+/// Prints the entries of an image file directory and of its sub-directories
+fn print_ifd(ifd: &Ifd, byte_order: ByteOrder) {
+    println!("{}:", ifd.kind());
+    for entry in ifd.entries() {
+        match entry.sub_ifd() {
+            Some(_) => println!("  {:#06x} {}", entry.tag(), entry.name(ifd.kind())),
+            None => println!(
+                "  {:#06x} {}: {}",
+                entry.tag(),
+                entry.name(ifd.kind()),
+                entry.value_string(byte_order)
+            ),
+        }
+    }
+    ifd.entries()
+        .iter()
+        .filter_map(|e| e.sub_ifd())
+        .for_each(|sub_ifd| print_ifd(sub_ifd, byte_order));
+}
 
-    png.chunks().iter().for_each(|c| println!("{}", c));
+/// Prints the EXIF metadata of a PNG file
+fn exif_show(args: ExifShowArgs) -> Result<(), CommandError> {
+    let png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+    let chunk = png
+        .chunk_by_type(Exif::CHUNK_TYPE)
+        .ok_or(CommandError::Png(PngError::NotFoundChunk))?;
+    let exif = Exif::try_from(chunk.data())?;
+
+    if let Some(format) = args.format {
+        return print_report(&ExifReport::new(&args.file_path, &exif), format);
+    }
+
+    exif.ifds()
+        .iter()
+        .for_each(|ifd| print_ifd(ifd, exif.byte_order()));
 
     Ok(())
 }
+
+/// Removes the matching EXIF entries from a PNG file and saves the result
+fn exif_remove(args: ExifRemoveArgs) -> Result<(), CommandError> {
+    let mut png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+    let chunk = png
+        .chunk_by_type(Exif::CHUNK_TYPE)
+        .ok_or(CommandError::Png(PngError::NotFoundChunk))?;
+    let mut exif = Exif::try_from(chunk.data())?;
+
+    let removed = exif.remove_entries(|kind, entry| {
+        let name = entry.name(kind);
+        let tag = format!("{:#06x}", entry.tag());
+        args.tags
+            .iter()
+            .any(|p| exif::matches_pattern(p, &name) || exif::matches_pattern(p, &tag))
+    });
+
+    png.replace_chunk(Chunk::new(
+        ChunkType::from_str(Exif::CHUNK_TYPE)?,
+        exif.as_bytes(),
+    ))?;
+    writeln!(
+        message_output(&args.file_path, &args.write),
+        "Removed {} EXIF entries",
+        removed
+    )?;
+
+    save(&args.file_path, &png, &args.write)?;
+
+    Ok(())
+}
+
+/// Reads or writes the XMP metadata of a PNG file
+pub fn xmp(args: XmpArgs) -> Result<(), CommandError> {
+    match args.command {
+        XmpCommand::Get(args) => batch(args, xmp_get),
+        XmpCommand::Set(args) => batch(args, xmp_set),
+        XmpCommand::Merge(args) => batch(args, xmp_merge),
+    }
+}
+
+/// Searches for the `iTXt` chunk that stores the XMP packet, returning its index and content
+fn find_xmp(png: &Png) -> Result<Option<(usize, InternationalText)>, CommandError> {
+    let chunk_type = ChunkType::from_str(InternationalText::CHUNK_TYPE)?;
+    for (index, chunk) in png.chunks().iter().enumerate() {
+        if *chunk.chunk_type() != chunk_type {
+            continue;
+        }
+        // NOTE: unrelated iTXt chunks that fail to parse don't prevent finding the XMP packet
+        let Ok(text) = InternationalText::try_from(chunk.data()) else {
+            continue;
+        };
+        if text.keyword() == Xmp::KEYWORD {
+            return Ok(Some((index, text)));
+        }
+    }
+    Ok(None)
+}
+
+/// Writes the XMP packet to the PNG file, updating the existing XMP chunk in place
+fn write_xmp(png: &mut Png, xmp: &Xmp) -> Result<(), CommandError> {
+    let (index, mut text) = match find_xmp(png)? {
+        Some((index, text)) => (Some(index), text),
+        None => (None, InternationalText::new(Xmp::KEYWORD, "")),
+    };
+    text.set_text(xmp.packet());
+
+    let chunk = Chunk::new(
+        ChunkType::from_str(InternationalText::CHUNK_TYPE)?,
+        text.as_bytes()?,
+    );
+    match index {
+        Some(index) => {
+            png.replace_chunk_at(index, chunk)?;
+        }
+        None => png.insert_chunk_ordered(chunk)?,
+    };
+
+    Ok(())
+}
+
+/// Prints the XMP packet of a PNG file
+fn xmp_get(args: XmpGetArgs) -> Result<(), CommandError> {
+    let png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+    let (_, text) = find_xmp(&png)?.ok_or(CommandError::Png(PngError::NotFoundChunk))?;
+
+    if let Some(format) = args.format {
+        let report = XmpReport {
+            schema_version: SCHEMA_VERSION,
+            file: args.file_path.display().to_string(),
+            packet: text.text().to_owned(),
+        };
+        return print_report(&report, format);
+    }
+
+    println!("{}", text.text());
+
+    Ok(())
+}
+
+/// Replaces the XMP packet of a PNG file and saves the result
+fn xmp_set(args: XmpSetArgs) -> Result<(), CommandError> {
+    let mut png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+    let packet = fs::read_to_string(&args.xmp_file_path)?;
+
+    write_xmp(&mut png, &Xmp::new(&packet))?;
+
+    save(&args.file_path, &png, &args.write)?;
+
+    Ok(())
+}
+
+/// Merges properties into the XMP packet of a PNG file and saves the result
+fn xmp_merge(args: XmpMergeArgs) -> Result<(), CommandError> {
+    let mut png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+    let mut xmp = match find_xmp(&png)? {
+        Some((_, text)) => Xmp::new(text.text()),
+        None => Xmp::empty(),
+    };
+
+    for property in args.properties.iter() {
+        let (name, value) = property.split_once('=').ok_or_else(|| {
+            CommandError::InvalidArgument(format!(
+                "property {} is not in the name=value format",
+                property
+            ))
+        })?;
+        xmp.set_property(name, value)?;
+    }
+
+    write_xmp(&mut png, &xmp)?;
+
+    save(&args.file_path, &png, &args.write)?;
+
+    Ok(())
+}
+
+/// Creates a chunk from its typed content
+fn ancillary_chunk(ancillary: &Ancillary) -> Result<Chunk, CommandError> {
+    Ok(Chunk::new(
+        ChunkType::from_str(ancillary.chunk_type())?,
+        ancillary.as_bytes()?,
+    ))
+}
+
+/// Writes the typed chunk into the PNG file, replacing the chunk of the same type if it is already present
+fn write_ancillary(png: &mut Png, ancillary: &Ancillary) -> Result<(), CommandError> {
+    let chunk = ancillary_chunk(ancillary)?;
+    // NOTE: the chunk is reinserted rather than replaced in place, so that a misplaced chunk ends up before the image data
+    if png.chunk_position(ancillary.chunk_type())?.is_some() {
+        png.remove_chunk(ancillary.chunk_type())?;
+    }
+    png.insert_chunk_ordered(chunk)?;
+    Ok(())
+}
+
+/// Sets the physical resolution of the PNG file in dots per inch
+pub fn set_dpi(args: SetDpiArgs) -> Result<(), CommandError> {
+    if args.dpi == 0 {
+        return Err(CommandError::InvalidArgument(String::from(
+            "DPI must be greater than zero",
+        )));
+    }
+    let mut png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+
+    let dimensions = PhysicalDimensions::from_dpi(args.dpi);
+    write_ancillary(&mut png, &Ancillary::PhysicalDimensions(dimensions))?;
+
+    save(&args.file_path, &png, &args.write)?;
+
+    Ok(())
+}
+
+/// Sets the time of the last image modification of the PNG file
+pub fn touch(args: TouchArgs) -> Result<(), CommandError> {
+    let time = match args.time {
+        Some(time) => Time::from_str(&time)?,
+        None => Time::now(),
+    };
+    let mut png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+
+    write_ancillary(&mut png, &Ancillary::Time(time))?;
+
+    save(&args.file_path, &png, &args.write)?;
+
+    Ok(())
+}
+
+/// Inspects the animation of an APNG file
+pub fn apng(args: ApngArgs) -> Result<(), CommandError> {
+    match args.command {
+        ApngCommand::Info(args) => batch(args, apng_info),
+        ApngCommand::Extract(args) => batch(args, apng_extract),
+        ApngCommand::Build(args) => apng_build(args),
+    }
+}
+
+/// Prints the frames of an APNG file
+fn apng_info(args: ApngInfoArgs) -> Result<(), CommandError> {
+    let png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+    let animation = Animation::try_from(&png)?;
+    if let Some(format) = args.format {
+        return print_report(&ApngReport::new(&args.file_path, &png, &animation), format);
+    }
+    let offsets = png.chunk_offsets();
+
+    match animation.control.num_plays {
+        0 => println!("Frames: {}, plays: infinite", animation.control.num_frames),
+        plays => println!("Frames: {}, plays: {}", animation.control.num_frames, plays),
+    }
+    for (index, frame) in animation.frames.iter().enumerate() {
+        let control = &frame.control;
+        println!(
+            "Frame {}{}: offset {}, {}x{} at ({}, {}), delay {} ms, dispose {}, blend {}",
+            index,
+            if frame.is_default_image {
+                " (default image)"
+            } else {
+                ""
+            },
+            offsets[frame.chunk_index],
+            control.width,
+            control.height,
+            control.x_offset,
+            control.y_offset,
+            control.delay_ms(),
+            control.dispose_op,
+            control.blend_op
+        );
+    }
+
+    Ok(())
+}
+
+/// Writes every frame of an APNG file as a standalone PNG file
+fn apng_extract(args: ApngExtractArgs) -> Result<(), CommandError> {
+    let png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+    let animation = Animation::try_from(&png)?;
+
+    fs::create_dir_all(&args.output_dir_path)?;
+
+    for (index, frame) in animation.frames.iter().enumerate() {
+        let path = args.output_dir_path.join(format!("frame_{}.png", index));
+        fs::write(&path, frame.to_png(&png)?.as_bytes())?;
+        println!("{}", path.display());
+    }
+
+    Ok(())
+}
+
+/// Converts a delay such as `100ms` or `1.5s` to the numerator and denominator of the frame delay in seconds
+fn parse_delay(delay: &str) -> Result<(u16, u16), CommandError> {
+    let invalid = || CommandError::InvalidArgument(format!("invalid delay {}", delay));
+    let (value, scale) = match delay.strip_suffix("ms") {
+        Some(value) => (value, 1.0),
+        None => match delay.strip_suffix('s') {
+            Some(value) => (value, 1000.0),
+            None => (delay, 1.0),
+        },
+    };
+    let milliseconds = value.trim().parse::<f64>().map_err(|_| invalid())? * scale;
+    if !milliseconds.is_finite() || milliseconds < 0.0 {
+        return Err(invalid());
+    }
+
+    let milliseconds = milliseconds.round() as u64;
+    if let Ok(milliseconds) = u16::try_from(milliseconds) {
+        return Ok((milliseconds, 1000));
+    }
+    let centiseconds = u16::try_from((milliseconds + 5) / 10).map_err(|_| invalid())?;
+    Ok((centiseconds, 100))
+}
+
+/// Builds an APNG file from PNG files of the frames
+fn apng_build(args: ApngBuildArgs) -> Result<(), CommandError> {
+    let (delay_num, delay_den) = parse_delay(&args.delay)?;
+    let frames = args
+        .frame_file_paths
+        .iter()
+        .map(|path| Ok(Png::try_from(output::read(path)?.as_slice())?))
+        .collect::<Result<Vec<Png>, CommandError>>()?;
+
+    let apng = Animation::build(&frames, delay_num, delay_den, args.loops)?;
+
+    output::write(&args.output_file_path, &apng.as_bytes())?;
+
+    Ok(())
+}
+
+/// Converts a PNG file to its assembly listing
+pub fn disassemble(args: DisassembleArgs) -> Result<(), CommandError> {
+    // NOTE: corrupted CRCs are preserved in the listing, so that the file can be assembled byte identical
+    let png = Png::try_from_unverified(output::read(&args.file_path)?.as_slice())?;
+
+    let listing = assembly::disassemble(&png);
+
+    match args.output_file_path {
+        Some(path) => output::write(&path, listing.as_bytes())?,
+        None => print!("{}", listing),
+    }
+
+    Ok(())
+}
+
+/// Builds a PNG file from its assembly listing
+pub fn assemble(args: AssembleArgs) -> Result<(), CommandError> {
+    let png = assembly::assemble(&fs::read_to_string(&args.source_file_path)?)?;
+
+    output::write(&args.output_file_path, &png.as_bytes())?;
+
+    Ok(())
+}
+
+/// Prints a diff friendly summary of a PNG file
+pub fn textconv(args: TextconvArgs) -> Result<(), CommandError> {
+    let png = Png::try_from_unverified(output::read(&args.file_path)?.as_slice())?;
+
+    print!("{}", summary::summarize(&png));
+
+    Ok(())
+}
+
+/// Compares two PNG files and prints the differences
+pub fn diff(args: DiffArgs) -> Result<(), CommandError> {
+    let old = Png::try_from_unverified(output::read(&args.old_file_path)?.as_slice())?;
+    let new = Png::try_from_unverified(output::read(&args.new_file_path)?.as_slice())?;
+
+    let diff = PngDiff::new(&args.old_file_path, &old, &args.new_file_path, &new);
+    match args.format {
+        Some(format) => print_report(&diff, format)?,
+        None => println!("{}", diff),
+    }
+
+    Ok(())
+}
+
+/// Starts an interactive shell to explore and edit a PNG file
+pub fn shell(args: ShellArgs) -> Result<(), CommandError> {
+    let png = Png::try_from_unverified(fs::read(&args.file_path)?.as_slice())?;
+
+    shell::run(Session::new(args.file_path, png))?;
+
+    Ok(())
+}
+
+/// Opens a full screen terminal browser of the chunks of a PNG file
+pub fn view(args: ViewArgs) -> Result<(), CommandError> {
+    let png = Png::try_from_unverified(fs::read(&args.file_path)?.as_slice())?;
+
+    view::run(Viewer::new(args.file_path.display().to_string(), png))?;
+
+    Ok(())
+}
+
+/// Searches a pattern in the chunks of PNG files, printing every match
+pub fn grep(args: GrepArgs) -> Result<(), CommandError> {
+    let pattern = search::pattern(&args.pattern, args.hex, args.ignore_case)?;
+    let file_paths = batch::expand(&args.file_paths, args.recursive)?;
+
+    let (mut failed, mut matched) = (0, false);
+    for file_path in &file_paths {
+        // NOTE: files that can't be read are reported and skipped, so that one broken file doesn't stop the search
+        let png = output::read(file_path)
+            .map_err(CommandError::from)
+            .and_then(|bytes| Ok(Png::try_from_unverified(bytes.as_slice())?));
+        match png {
+            Ok(png) => {
+                let matches = search::search(&png, &pattern, args.ecc);
+                matched |= !matches.is_empty();
+                matches
+                    .iter()
+                    .for_each(|m| println!("{}:{}", file_path.display(), m));
+            }
+            Err(err) => {
+                failed += 1;
+                eprintln!("failed  {}: {}", file_path.display(), err);
+            }
+        }
+    }
+
+    match (failed, matched) {
+        (0, true) => Ok(()),
+        (0, false) => Err(CommandError::NoMatches),
+        (failed, _) => Err(CommandError::BatchFailed {
+            failed,
+            total: file_paths.len(),
+        }),
+    }
+}
+
+/// Recovers the PNG files found in arbitrary binary data, reporting the damaged ones separately
+pub fn carve(args: CarveArgs) -> Result<(), CommandError> {
+    // NOTE: the standard input can't be rewound, so it is read whole, files are streamed
+    if output::is_standard_stream(&args.blob_path) {
+        let mut reader = Cursor::new(output::read(&args.blob_path)?);
+        carve_from(&mut reader, &args)
+    } else {
+        let mut reader = BufReader::new(File::open(&args.blob_path)?);
+        carve_from(&mut reader, &args)
+    }
+}
+
+fn carve_from<R: Read + Seek>(reader: &mut R, args: &CarveArgs) -> Result<(), CommandError> {
+    let (damaged, recovered): (Vec<_>, Vec<_>) = carve::scan(reader)?
+        .into_iter()
+        .partition(|candidate| candidate.is_damaged());
+    fs::create_dir_all(&args.output_dir)?;
+
+    for candidate in &recovered {
+        let path = args
+            .output_dir
+            .join(format!("carved_{:08x}.png", candidate.offset));
+        candidate.copy(reader, &mut File::create(&path)?)?;
+        println!(
+            "recovered {:#010x}: {} bytes, {} chunks -> {}",
+            candidate.offset,
+            candidate.length,
+            candidate.chunks,
+            path.display()
+        );
+    }
+
+    for candidate in &damaged {
+        print!(
+            "damaged   {:#010x}: {} bytes, {} valid chunks, {}",
+            candidate.offset,
+            candidate.length,
+            candidate.chunks,
+            candidate.damage.as_ref().unwrap()
+        );
+        if args.keep_damaged && candidate.chunks > 0 {
+            let path = args
+                .output_dir
+                .join(format!("carved_{:08x}.damaged.png", candidate.offset));
+            candidate.copy(reader, &mut File::create(&path)?)?;
+            print!(" -> {}", path.display());
+        }
+        println!();
+    }
+
+    eprintln!("{} recovered, {} damaged", recovered.len(), damaged.len());
+
+    Ok(())
+}
+
+/// Dispatches the trailer subcommands
+pub fn trailer(args: TrailerArgs) -> Result<(), CommandError> {
+    match args.command {
+        TrailerCommand::Show(args) => batch(args, trailer_show),
+        TrailerCommand::Strip(args) => batch(args, trailer_strip),
+        TrailerCommand::Set(args) => batch(args, trailer_set),
+    }
+}
+
+/// Prints the data after the last chunk of a PNG file, or writes it raw to the output file
+fn trailer_show(args: TrailerShowArgs) -> Result<(), CommandError> {
+    let png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+
+    if let Some(path) = args.output_file_path {
+        output::write(&path, png.trailer())?;
+        return Ok(());
+    }
+
+    if png.trailer().is_empty() {
+        println!("No trailing data.");
+        return Ok(());
+    }
+
+    println!(
+        "Trailer: {} bytes at offset {}",
+        png.trailer().len(),
+        png.trailer_offset()
+    );
+    print!("{}", hexdump::hexdump(png.trailer(), png.trailer_offset()));
+
+    Ok(())
+}
+
+/// Removes the data after the last chunk of a PNG file and saves the result
+fn trailer_strip(args: TrailerStripArgs) -> Result<(), CommandError> {
+    let mut png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+
+    let removed = png.set_trailer(vec![]);
+    writeln!(
+        message_output(&args.file_path, &args.write),
+        "Removed {} bytes of trailing data.",
+        removed.len()
+    )?;
+
+    save(&args.file_path, &png, &args.write)?;
+
+    Ok(())
+}
+
+/// Replaces the data after the last chunk of a PNG file and saves the result
+fn trailer_set(args: TrailerSetArgs) -> Result<(), CommandError> {
+    let mut png = Png::try_from(output::read(&args.file_path)?.as_slice())?;
+
+    png.set_trailer(output::read(&args.data_file_path)?);
+
+    save(&args.file_path, &png, &args.write)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::PngMeArgs;
+    use clap::Parser;
+    use std::process;
+
+    fn write_args(output: Option<&str>, in_place: bool, dry_run: bool) -> WriteArgs {
+        WriteArgs {
+            output: output.map(PathBuf::from),
+            in_place,
+            backup: None,
+            preserve: false,
+            dry_run,
+        }
+    }
+
+    fn testing_png() -> Png {
+        Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![0; 13]),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"message".to_vec()),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ])
+    }
+
+    #[test]
+    fn test_output_path() {
+        let file_path = Path::new("image.png");
+        let output = write_args(Some("out.png"), false, false);
+        assert_eq!(
+            output_path(file_path, &output).unwrap(),
+            Path::new("out.png")
+        );
+        let default = write_args(None, false, false);
+        assert_eq!(output_path(file_path, &default).unwrap(), file_path);
+        let in_place = write_args(None, true, false);
+        assert_eq!(output_path(file_path, &in_place).unwrap(), file_path);
+
+        let stdout = write_args(Some("-"), false, false);
+        assert!(output::is_standard_stream(
+            output_path(file_path, &stdout).unwrap()
+        ));
+        assert!(output::is_standard_stream(
+            output_path(Path::new("-"), &default).unwrap()
+        ));
+        assert!(matches!(
+            output_path(Path::new("-"), &in_place),
+            Err(CommandError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_output_arguments_conflict() {
+        let parse = |args: &[&str]| {
+            PngMeArgs::try_parse_from(
+                ["pngme", "encode", "a.png", "ruSt", "hi"]
+                    .iter()
+                    .chain(args),
+            )
+        };
+        assert!(parse(&["-o", "b.png"]).is_ok());
+        assert!(parse(&["b.png"]).is_ok());
+        assert!(parse(&["b.png", "-o", "c.png"]).is_err());
+        assert!(parse(&["b.png", "--in-place"]).is_err());
+        assert!(parse(&["-o", "b.png", "--in-place"]).is_err());
+    }
+
+    #[test]
+    fn test_save_dry_run() {
+        let dir = std::env::temp_dir().join(format!("pngme-commands-dry-run-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("image.png");
+        let original = testing_png();
+        fs::write(&path, original.as_bytes()).unwrap();
+
+        let mut png = original.clone();
+        png.remove_chunk("ruSt").unwrap();
+        save(&path, &png, &write_args(None, false, true)).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), original.as_bytes());
+
+        let output = dir.join("out.png");
+        let write = write_args(Some(output.to_str().unwrap()), false, true);
+        save(&path, &png, &write).unwrap();
+        assert!(!output.exists());
+
+        save(&path, &png, &write_args(None, false, false)).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), png.as_bytes());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,211 @@
+/*!
+ * # Detail crate
+ *
+ * Defines the detailed view of a single chunk of a PNG file: the absolute file offsets of its fields, the four property
+ * bits of its type with their meaning, the decoded data and, optionally, an annotated hex and ASCII dump of the chunk.
+ *
+ * The property bits are bit 5 of each of the four bytes of the chunk type, i.e. the case of each letter, as described
+ * in the [PNG specification](http://www.libpng.org/pub/png/spec/1.2/PNG-Structure.html).
+ *
+ */
+
+use crate::chunk::Chunk;
+use crate::chunk_type::ChunkType;
+use crate::hexdump;
+use crate::png::Png;
+use crate::summary;
+use std::fmt::Write;
+
+const LENGTH_SIZE: usize = 4;
+const TYPE_SIZE: usize = 4;
+
+/// Names and explanations of each property bit, when the bit is 0 and when it is 1
+const PROPERTIES: [[(&str, &str); 2]; 4] = [
+    [
+        (
+            "critical",
+            "decoders must understand the chunk to display the image",
+        ),
+        ("ancillary", "decoders may safely ignore the chunk"),
+    ],
+    [
+        ("public", "defined by the PNG specification or registered"),
+        ("private", "defined by an application"),
+    ],
+    [
+        ("reserved", "valid in the current PNG version"),
+        ("reserved", "invalid, must be uppercase"),
+    ],
+    [
+        (
+            "unsafe to copy",
+            "editors must drop the chunk if they modified the critical chunks",
+        ),
+        (
+            "safe to copy",
+            "editors may copy the chunk even if the image data was modified",
+        ),
+    ],
+];
+
+/// Bit 5 of each byte of the chunk type
+const PROPERTY_BIT: u8 = 1 << 5;
+
+/// Decodes the four property bits of the chunk type, one line for each bit with its explanation
+pub fn properties(chunk_type: &ChunkType) -> Vec<String> {
+    chunk_type
+        .bytes()
+        .iter()
+        .zip(PROPERTIES)
+        .map(|(&byte, names)| {
+            let bit = usize::from(byte & PROPERTY_BIT != 0);
+            let (name, explanation) = names[bit];
+            format!("{} bit {}: {} ({})", byte as char, bit, name, explanation)
+        })
+        .collect()
+}
+
+/// Lists the chunk data with absolute file offsets, limited to the maximum number of bytes if specified
+fn annotated_dump(chunk: &Chunk, offset: usize, max_bytes: Option<usize>) -> String {
+    let data_offset = offset + LENGTH_SIZE + TYPE_SIZE;
+    let crc_offset = data_offset + chunk.data().len();
+    let shown = max_bytes.map_or(chunk.data().len(), |max| max.min(chunk.data().len()));
+
+    let mut dump = String::new();
+    let _ = writeln!(
+        dump,
+        "{}",
+        hexdump::hexdump(&chunk.length().to_be_bytes(), offset).trim_end()
+    );
+    let _ = writeln!(dump, "  ^ length");
+    let _ = writeln!(
+        dump,
+        "{}",
+        hexdump::hexdump(&chunk.chunk_type().bytes(), offset + LENGTH_SIZE).trim_end()
+    );
+    let _ = writeln!(dump, "  ^ type");
+    dump.push_str(&hexdump::hexdump(&chunk.data()[..shown], data_offset));
+    if shown < chunk.data().len() {
+        let _ = writeln!(dump, "  ... {} more bytes", chunk.data().len() - shown);
+    }
+    let _ = writeln!(dump, "  ^ data");
+    let _ = writeln!(
+        dump,
+        "{}",
+        hexdump::hexdump(&chunk.crc().to_be_bytes(), crc_offset).trim_end()
+    );
+    let _ = writeln!(dump, "  ^ crc");
+    dump
+}
+
+/**
+ * Describes the chunk at the index in detail, with its absolute file offsets, property bits and decoded data.
+ *
+ * If the hex dump is requested, the whole chunk is listed with absolute file offsets, showing at most the maximum
+ * number of data bytes if specified.
+ */
+pub fn describe_chunk(png: &Png, index: usize, hexdump: bool, max_bytes: Option<usize>) -> String {
+    let chunk = &png.chunks()[index];
+    let offset = png.chunk_offsets()[index];
+    let data_offset = offset + LENGTH_SIZE + TYPE_SIZE;
+    let crc_offset = data_offset + chunk.data().len();
+
+    let mut detail = String::new();
+    let _ = writeln!(
+        detail,
+        "Chunk {} at offset {:#x} ({})",
+        index, offset, offset
+    );
+    let _ = writeln!(detail, "  Length: {} at {:#x}", chunk.length(), offset);
+    let _ = writeln!(
+        detail,
+        "  Type: {} at {:#x}",
+        chunk.chunk_type(),
+        offset + LENGTH_SIZE
+    );
+    let _ = writeln!(
+        detail,
+        "  Data: {} bytes at {:#x}",
+        chunk.data().len(),
+        data_offset
+    );
+    let _ = writeln!(
+        detail,
+        "  Crc: {:#010x} at {:#x}, {}",
+        chunk.crc(),
+        crc_offset,
+        match chunk.has_valid_crc() {
+            true => "valid",
+            false => "invalid",
+        }
+    );
+
+    let _ = writeln!(detail, "Properties:");
+    for property in properties(chunk.chunk_type()) {
+        let _ = writeln!(detail, "  {}", property);
+    }
+
+    let decoded = summary::describe(png, chunk);
+    if !decoded.is_empty() {
+        let _ = writeln!(detail, "Decoded:");
+        for line in decoded.lines() {
+            let _ = writeln!(detail, "  {}", line);
+        }
+    }
+
+    if hexdump {
+        let _ = writeln!(detail, "Hexdump:");
+        detail.push_str(&annotated_dump(chunk, offset, max_bytes));
+    }
+    detail
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn testing_png() -> Png {
+        Png::from_chunks(vec![
+            Chunk::new(
+                ChunkType::from_str("IHDR").unwrap(),
+                vec![0, 0, 0, 4, 0, 0, 0, 3, 8, 2, 0, 0, 0],
+            ),
+            Chunk::with_crc(ChunkType::from_str("ruSt").unwrap(), (0..40).collect(), 0),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ])
+    }
+
+    #[test]
+    fn test_properties() {
+        let bits = properties(&ChunkType::from_str("ruSt").unwrap());
+        assert!(bits[0].starts_with("r bit 1: ancillary"));
+        assert!(bits[1].starts_with("u bit 1: private"));
+        assert!(bits[2].starts_with("S bit 0: reserved (valid"));
+        assert!(bits[3].starts_with("t bit 1: safe to copy"));
+
+        let bits = properties(&ChunkType::from_str("IHDR").unwrap());
+        assert!(bits[0].starts_with("I bit 0: critical"));
+        assert!(bits[3].starts_with("R bit 0: unsafe to copy"));
+    }
+
+    #[test]
+    fn test_describe_chunk() {
+        let detail = describe_chunk(&testing_png(), 1, false, None);
+        assert!(detail.starts_with("Chunk 1 at offset 0x21 (33)\n"));
+        assert!(detail.contains("  Data: 40 bytes at 0x29\n"));
+        assert!(detail.contains("  Crc: 0x00000000 at 0x51, invalid\n"));
+        assert!(!detail.contains("Hexdump:"));
+    }
+
+    #[test]
+    fn test_describe_chunk_hexdump() {
+        let detail = describe_chunk(&testing_png(), 1, true, Some(20));
+        assert!(detail.contains("00000021  00 00 00 28"));
+        assert!(detail.contains("00000025  72 75 53 74"));
+        assert!(detail.contains("00000039  10 11 12 13"));
+        assert!(detail.contains("  ... 20 more bytes\n"));
+        assert!(detail.contains("00000051  00 00 00 00"));
+        assert!(!detail.contains("00000049"));
+    }
+}
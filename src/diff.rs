@@ -0,0 +1,621 @@
+/*!
+ * # Diff crate
+ *
+ * Defines the comparison of two PNG files. Chunk lists are aligned on equal chunks, and the remaining chunks are
+ * reported as reordered, changed, added or removed. Decoded chunks, e.g. textual chunks, are compared line by line,
+ * the image headers field by field and the image data by decoded pixels, so that recompressing the image isn't
+ * reported as a change of the image itself. The data after the last chunk is compared by its length and SHA-256 digest.
+ *
+ */
+
+use crate::chunk::Chunk;
+use crate::image_header::ImageHeader;
+use crate::pixels;
+use crate::png::Png;
+use crate::report::{Report, SCHEMA_VERSION};
+use crate::summary;
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum ChunkChange {
+    Added {
+        new_index: usize,
+        #[serde(rename = "type")]
+        chunk_type: String,
+    },
+    Removed {
+        old_index: usize,
+        #[serde(rename = "type")]
+        chunk_type: String,
+    },
+    Reordered {
+        old_index: usize,
+        new_index: usize,
+        #[serde(rename = "type")]
+        chunk_type: String,
+    },
+    Changed {
+        old_index: usize,
+        new_index: usize,
+        #[serde(rename = "type")]
+        chunk_type: String,
+        old_length: u32,
+        new_length: u32,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        text: Vec<String>,
+    },
+}
+
+impl ChunkChange {
+    /// Position of the change, the index in the new file or the index in the old file for removed chunks
+    fn position(&self) -> usize {
+        match *self {
+            ChunkChange::Added { new_index, .. } => new_index,
+            ChunkChange::Removed { old_index, .. } => old_index,
+            ChunkChange::Reordered { new_index, .. } => new_index,
+            ChunkChange::Changed { new_index, .. } => new_index,
+        }
+    }
+}
+
+impl fmt::Display for ChunkChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChunkChange::Added {
+                new_index,
+                chunk_type,
+            } => write!(f, "+ {} added at {}", chunk_type, new_index),
+            ChunkChange::Removed {
+                old_index,
+                chunk_type,
+            } => write!(f, "- {} removed from {}", chunk_type, old_index),
+            ChunkChange::Reordered {
+                old_index,
+                new_index,
+                chunk_type,
+            } => write!(
+                f,
+                "~ {} moved from {} to {}",
+                chunk_type, old_index, new_index
+            ),
+            ChunkChange::Changed {
+                old_index,
+                new_index,
+                chunk_type,
+                old_length,
+                new_length,
+                text,
+            } => {
+                write!(
+                    f,
+                    "* {} changed at {} -> {} ({} -> {} bytes)",
+                    chunk_type, old_index, new_index, old_length, new_length
+                )?;
+                text.iter().try_for_each(|line| write!(f, "\n    {}", line))
+            }
+        }
+    }
+}
+
+/// Change of the data after the last chunk, identified by its length and SHA-256 digest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TrailerChange {
+    pub old_length: usize,
+    pub new_length: usize,
+    pub old_sha256: String,
+    pub new_sha256: String,
+}
+
+impl fmt::Display for TrailerChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "* trailer changed ({} -> {} bytes, sha256 {} -> {})",
+            self.old_length, self.new_length, self.old_sha256, self.new_sha256
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum PixelComparison {
+    Identical,
+    Different { pixels: usize, total: usize },
+    Incomparable { reason: String },
+}
+
+impl fmt::Display for PixelComparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PixelComparison::Identical => write!(f, "Pixels: identical"),
+            PixelComparison::Different { pixels, total } => {
+                write!(f, "Pixels: {} of {} pixels differ", pixels, total)
+            }
+            PixelComparison::Incomparable { reason } => {
+                write!(f, "Pixels: not compared, {}", reason)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PngDiff {
+    pub schema_version: u32,
+    pub old_file: String,
+    pub new_file: String,
+    pub header: Vec<FieldChange>,
+    pub chunks: Vec<ChunkChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trailer: Option<TrailerChange>,
+    pub pixels: PixelComparison,
+}
+
+impl Report for PngDiff {}
+
+impl PngDiff {
+    pub fn new(old_path: &Path, old: &Png, new_path: &Path, new: &Png) -> PngDiff {
+        PngDiff {
+            schema_version: SCHEMA_VERSION,
+            old_file: old_path.display().to_string(),
+            new_file: new_path.display().to_string(),
+            header: diff_headers(old, new),
+            chunks: diff_chunks(old.chunks(), new.chunks()),
+            trailer: diff_trailers(old, new),
+            pixels: compare_pixels(old, new),
+        }
+    }
+
+    /// Returns whether the files contain the same chunks in the same order and the same trailing data
+    pub fn is_identical(&self) -> bool {
+        self.chunks.is_empty() && self.trailer.is_none()
+    }
+}
+
+impl fmt::Display for PngDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "--- {}", self.old_file)?;
+        writeln!(f, "+++ {}", self.new_file)?;
+        for change in &self.header {
+            writeln!(f, "IHDR {}: {} -> {}", change.field, change.old, change.new)?;
+        }
+        if self.chunks.is_empty() {
+            writeln!(f, "Chunks: identical")?;
+        }
+        for change in &self.chunks {
+            writeln!(f, "{}", change)?;
+        }
+        if let Some(change) = &self.trailer {
+            writeln!(f, "{}", change)?;
+        }
+        write!(f, "{}", self.pixels)
+    }
+}
+
+/// Maximum number of cells of the table of subsequence lengths, e.g. two texts of 4096 changed lines each
+const MAX_TABLE_CELLS: usize = 1 << 24;
+
+/**
+ * Returns the index pairs of the longest common subsequence of equal items.
+ *
+ * The common prefix and suffix are matched directly. If the remaining middle parts are too large for the table of
+ * subsequence lengths, none of their items are matched, i.e. they are reported as entirely changed.
+ */
+fn longest_common_subsequence<T, F>(old: &[T], new: &[T], equal: F) -> Vec<(usize, usize)>
+where
+    F: Fn(&T, &T) -> bool,
+{
+    let prefix = old.iter().zip(new).take_while(|(a, b)| equal(a, b)).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| equal(a, b))
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut pairs: Vec<(usize, usize)> = (0..prefix).map(|i| (i, i)).collect();
+    if old_middle.len().saturating_mul(new_middle.len()) <= MAX_TABLE_CELLS {
+        pairs.extend(
+            table_subsequence(old_middle, new_middle, &equal)
+                .into_iter()
+                .map(|(i, j)| (prefix + i, prefix + j)),
+        );
+    }
+    pairs.extend((0..suffix).map(|k| (old.len() - suffix + k, new.len() - suffix + k)));
+    pairs
+}
+
+/// Returns the index pairs of the longest common subsequence, using the full table of subsequence lengths
+fn table_subsequence<T, F>(old: &[T], new: &[T], equal: &F) -> Vec<(usize, usize)>
+where
+    F: Fn(&T, &T) -> bool,
+{
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if equal(&old[i], &new[j]) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if equal(&old[i], &new[j]) {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// Returns a unified line diff of the two texts, unchanged lines are prefixed with a space
+fn diff_lines(old: &str, new: &str) -> Vec<String> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for (old_index, new_index) in longest_common_subsequence(&old, &new, |a, b| a == b)
+        .into_iter()
+        .chain(std::iter::once((old.len(), new.len())))
+    {
+        lines.extend(old[i..old_index].iter().map(|line| format!("-{}", line)));
+        lines.extend(new[j..new_index].iter().map(|line| format!("+{}", line)));
+        if old_index < old.len() {
+            lines.push(format!(" {}", old[old_index]));
+        }
+        (i, j) = (old_index + 1, new_index + 1);
+    }
+    lines
+}
+
+/// Decodes a chunk to the lines of its decoded values, e.g. the keyword and text of a textual chunk
+fn decode_text(chunk: &Chunk, chunks: &[Chunk]) -> Option<String> {
+    let header = chunks
+        .iter()
+        .find(|c| c.chunk_type().to_string() == ImageHeader::CHUNK_TYPE)
+        .and_then(|c| ImageHeader::try_from(c.data()).ok());
+    let values = summary::decode_chunk(chunk, header.as_ref())?.ok()?;
+    Some(summary::decoded_text(&values))
+}
+
+fn changed(old: &[Chunk], old_index: usize, new: &[Chunk], new_index: usize) -> ChunkChange {
+    let (old_chunk, new_chunk) = (&old[old_index], &new[new_index]);
+    let text = match (decode_text(old_chunk, old), decode_text(new_chunk, new)) {
+        (Some(old_text), Some(new_text)) => diff_lines(&old_text, &new_text),
+        _ => Vec::new(),
+    };
+    ChunkChange::Changed {
+        old_index,
+        new_index,
+        chunk_type: new_chunk.chunk_type().to_string(),
+        old_length: old_chunk.length(),
+        new_length: new_chunk.length(),
+        text,
+    }
+}
+
+/// Aligns the chunk lists and returns the changes, ordered by their position
+pub fn diff_chunks(old: &[Chunk], new: &[Chunk]) -> Vec<ChunkChange> {
+    let mut old_unmatched: Vec<Option<usize>> = (0..old.len()).map(Some).collect();
+    let mut new_unmatched: Vec<Option<usize>> = (0..new.len()).map(Some).collect();
+    for (i, j) in longest_common_subsequence(old, new, |a, b| a == b) {
+        old_unmatched[i] = None;
+        new_unmatched[j] = None;
+    }
+
+    let mut changes = Vec::new();
+    // NOTE: equal chunks outside of the common subsequence were moved, chunks of the same type were changed
+    for is_change in [false, true] {
+        for i in old_unmatched.clone().into_iter().flatten() {
+            let matches = |j: &usize| match is_change {
+                true => old[i].chunk_type() == new[*j].chunk_type(),
+                false => old[i] == new[*j],
+            };
+            let found = new_unmatched.iter().flatten().copied().find(matches);
+            if let Some(j) = found {
+                old_unmatched[i] = None;
+                new_unmatched[j] = None;
+                changes.push(if is_change {
+                    changed(old, i, new, j)
+                } else {
+                    ChunkChange::Reordered {
+                        old_index: i,
+                        new_index: j,
+                        chunk_type: new[j].chunk_type().to_string(),
+                    }
+                });
+            }
+        }
+    }
+
+    changes.extend(
+        old_unmatched
+            .into_iter()
+            .flatten()
+            .map(|i| ChunkChange::Removed {
+                old_index: i,
+                chunk_type: old[i].chunk_type().to_string(),
+            }),
+    );
+    changes.extend(
+        new_unmatched
+            .into_iter()
+            .flatten()
+            .map(|j| ChunkChange::Added {
+                new_index: j,
+                chunk_type: new[j].chunk_type().to_string(),
+            }),
+    );
+    changes.sort_by_key(|change| change.position());
+    changes
+}
+
+/// Compares the data after the last chunk, returning the change if it differs
+pub fn diff_trailers(old: &Png, new: &Png) -> Option<TrailerChange> {
+    (old.trailer() != new.trailer()).then(|| TrailerChange {
+        old_length: old.trailer().len(),
+        new_length: new.trailer().len(),
+        old_sha256: summary::sha256(old.trailer()),
+        new_sha256: summary::sha256(new.trailer()),
+    })
+}
+
+fn header_fields(header: &ImageHeader) -> [(&'static str, String); 7] {
+    [
+        ("width", header.width.to_string()),
+        ("height", header.height.to_string()),
+        ("bit depth", header.bit_depth.to_string()),
+        ("color type", header.color_type.to_string()),
+        ("compression", header.compression_method.to_string()),
+        ("filter", header.filter_method.to_string()),
+        ("interlace", header.interlace_method.to_string()),
+    ]
+}
+
+/// Compares the image headers field by field
+pub fn diff_headers(old: &Png, new: &Png) -> Vec<FieldChange> {
+    let parse = |png: &Png| {
+        png.chunk_by_type(ImageHeader::CHUNK_TYPE)
+            .and_then(|c| ImageHeader::try_from(c.data()).ok())
+    };
+    match (parse(old), parse(new)) {
+        (Some(old), Some(new)) => header_fields(&old)
+            .into_iter()
+            .zip(header_fields(&new))
+            .filter(|((_, old_value), (_, new_value))| old_value != new_value)
+            .map(|((field, old_value), (_, new_value))| FieldChange {
+                field: field.to_owned(),
+                old: old_value,
+                new: new_value,
+            })
+            .collect(),
+        (None, None) => Vec::new(),
+        (old, new) => {
+            let describe = |header: Option<ImageHeader>| match header {
+                Some(_) => String::from("valid"),
+                None => String::from("missing or invalid"),
+            };
+            vec![FieldChange {
+                field: String::from("header"),
+                old: describe(old),
+                new: describe(new),
+            }]
+        }
+    }
+}
+
+/// Compares the decoded pixels of the images
+pub fn compare_pixels(old: &Png, new: &Png) -> PixelComparison {
+    let (old, new) = match (pixels::decode(old), pixels::decode(new)) {
+        (Ok(old), Ok(new)) => (old, new),
+        (Err(err), _) | (_, Err(err)) => {
+            return PixelComparison::Incomparable {
+                reason: err.to_string(),
+            }
+        }
+    };
+    if (old.width, old.height) != (new.width, new.height) {
+        return PixelComparison::Incomparable {
+            reason: format!(
+                "image size changed from {}x{} to {}x{}",
+                old.width, old.height, new.width, new.height
+            ),
+        };
+    }
+
+    let different = old
+        .pixels
+        .iter()
+        .zip(&new.pixels)
+        .filter(|(a, b)| a != b)
+        .count();
+    match different {
+        0 => PixelComparison::Identical,
+        pixels => PixelComparison::Different {
+            pixels,
+            total: old.pixels.len(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_type::ChunkType;
+    use crate::text::{self, Text};
+    use std::str::FromStr;
+
+    fn chunk(chunk_type: &str, data: Vec<u8>) -> Chunk {
+        Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data)
+    }
+
+    fn testing_png(extra: Vec<Chunk>, raw: &[u8]) -> Png {
+        let mut chunks = vec![chunk("IHDR", vec![0, 0, 0, 2, 0, 0, 0, 1, 8, 0, 0, 0, 0])];
+        chunks.extend(extra);
+        chunks.push(chunk("IDAT", text::deflate(raw).unwrap()));
+        chunks.push(chunk("IEND", vec![]));
+        Png::from_chunks(chunks)
+    }
+
+    #[test]
+    fn test_diff_identical() {
+        let png = testing_png(vec![chunk("gAMA", vec![0, 0, 177, 143])], &[0, 1, 2]);
+        let diff = PngDiff::new(Path::new("a.png"), &png, Path::new("b.png"), &png);
+        assert!(diff.is_identical());
+        assert!(diff.header.is_empty());
+        assert_eq!(diff.pixels, PixelComparison::Identical);
+    }
+
+    #[test]
+    fn test_diff_chunks() {
+        let gamma = chunk("gAMA", vec![0, 0, 177, 143]);
+        let time = chunk("tIME", vec![7, 231, 1, 2, 3, 4, 5]);
+        let old = testing_png(
+            vec![
+                gamma.clone(),
+                time.clone(),
+                chunk("tEXt", Text::new("Comment", "same\nold").as_bytes()),
+                chunk("ruSt", vec![1]),
+            ],
+            &[0, 1, 2],
+        );
+        let new = testing_png(
+            vec![
+                time,
+                gamma,
+                chunk("tEXt", Text::new("Comment", "same\nnew").as_bytes()),
+                chunk("ruSx", vec![1]),
+            ],
+            &[0, 1, 2],
+        );
+        let changes = diff_chunks(old.chunks(), new.chunks());
+        assert!(changes.contains(&ChunkChange::Reordered {
+            old_index: 1,
+            new_index: 2,
+            chunk_type: String::from("gAMA"),
+        }));
+        assert!(changes.contains(&ChunkChange::Changed {
+            old_index: 3,
+            new_index: 3,
+            chunk_type: String::from("tEXt"),
+            old_length: 16,
+            new_length: 16,
+            text: vec![
+                String::from(" Comment: same"),
+                String::from("-old"),
+                String::from("+new"),
+            ],
+        }));
+        assert!(changes.contains(&ChunkChange::Removed {
+            old_index: 4,
+            chunk_type: String::from("ruSt"),
+        }));
+        assert!(changes.contains(&ChunkChange::Added {
+            new_index: 4,
+            chunk_type: String::from("ruSx"),
+        }));
+        assert_eq!(changes.len(), 4);
+    }
+
+    #[test]
+    fn test_diff_trailers() {
+        let old = testing_png(vec![], &[0, 1, 2]);
+        let mut new = old.clone();
+        new.set_trailer(b"PK\x03\x04".to_vec());
+
+        let diff = PngDiff::new(Path::new("a.png"), &old, Path::new("b.png"), &new);
+        assert!(diff.chunks.is_empty());
+        assert!(!diff.is_identical());
+        assert_eq!(
+            diff.trailer,
+            Some(TrailerChange {
+                old_length: 0,
+                new_length: 4,
+                old_sha256: summary::sha256(&[]),
+                new_sha256: summary::sha256(b"PK\x03\x04"),
+            })
+        );
+        assert_eq!(diff_trailers(&new, &new.clone()), None);
+    }
+
+    #[test]
+    fn test_longest_common_subsequence() {
+        let old = [1, 2, 3, 4, 5, 6];
+        let new = [1, 2, 7, 4, 8, 6];
+        assert_eq!(
+            longest_common_subsequence(&old, &new, |a, b| a == b),
+            vec![(0, 0), (1, 1), (3, 3), (5, 5)]
+        );
+
+        // Middle parts too large for the table are reported as entirely changed
+        let old: Vec<u32> = (0..5000).collect();
+        let new: Vec<u32> = (0..5000)
+            .map(|i| if i % 2 == 0 { i } else { i + 10000 })
+            .collect();
+        let mut old_longer = old.clone();
+        old_longer.push(99999);
+        let mut new_longer = new.clone();
+        new_longer.push(99999);
+        assert_eq!(
+            longest_common_subsequence(&old_longer, &new_longer, |a, b| a == b),
+            vec![(0, 0), (5000, 5000)]
+        );
+    }
+
+    #[test]
+    fn test_compare_pixels_ignores_compression() {
+        let old = testing_png(vec![], &[0, 1, 2]);
+        // Same pixels stored with the sub filter
+        let new = testing_png(vec![], &[1, 1, 1]);
+        assert_eq!(compare_pixels(&old, &new), PixelComparison::Identical);
+        assert_eq!(diff_chunks(old.chunks(), new.chunks()).len(), 1);
+
+        let different = testing_png(vec![], &[0, 1, 3]);
+        assert_eq!(
+            compare_pixels(&old, &different),
+            PixelComparison::Different {
+                pixels: 1,
+                total: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_diff_headers() {
+        let old = testing_png(vec![], &[0, 1, 2]);
+        let mut new = old.clone();
+        new.replace_chunk(chunk("IHDR", vec![0, 0, 0, 1, 0, 0, 0, 2, 8, 0, 0, 0, 0]))
+            .unwrap();
+        let changes = diff_headers(&old, &new);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].field, "width");
+        assert_eq!(
+            (changes[0].old.as_str(), changes[0].new.as_str()),
+            ("2", "1")
+        );
+        assert!(matches!(
+            compare_pixels(&old, &new),
+            PixelComparison::Incomparable { .. }
+        ));
+    }
+}
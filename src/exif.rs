@@ -0,0 +1,670 @@
+/*!
+ * # EXIF crate
+ *
+ * Defines the parsing and serialization of the EXIF data stored in the `eXIf` chunk, as specified in the
+ * [PNG extensions](https://ftp-osl.osuosl.org/pub/libpng/documents/pngext-1.5.0.html#C.eXIf).
+ * The data is a TIFF structure: a header followed by a chain of image file directories (IFDs) whose entries can point to the
+ * Exif, GPS and Interoperability sub-IFDs.
+ *
+ * Entry values are kept in the byte order of the original data, so serialization only has to recompute the offsets.
+ * NOTE: offsets stored inside of opaque values, e.g. a maker note, are not relocated.
+ *
+ */
+
+use std::error;
+use std::fmt;
+
+const LITTLE_ENDIAN_MARKER: [u8; 2] = *b"II";
+const BIG_ENDIAN_MARKER: [u8; 2] = *b"MM";
+const TIFF_MAGIC: u16 = 42;
+const HEADER_LEN: usize = 8;
+const ENTRY_LEN: usize = 12;
+const MAX_IFD_COUNT: usize = 32;
+
+const EXIF_IFD_TAG: u16 = 0x8769;
+const GPS_IFD_TAG: u16 = 0x8825;
+const INTEROP_IFD_TAG: u16 = 0xa005;
+const THUMBNAIL_OFFSET_TAG: u16 = 0x0201;
+const THUMBNAIL_LENGTH_TAG: u16 = 0x0202;
+
+const LONG_TYPE: u16 = 4;
+
+#[derive(Debug)]
+pub enum ExifError {
+    InvalidHeader,
+    OutOfBounds,
+    UnknownType(u16),
+}
+
+impl fmt::Display for ExifError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ExifError::InvalidHeader => write!(f, "Invalid TIFF header of the EXIF data."),
+            ExifError::OutOfBounds => write!(f, "Offset points outside of the EXIF data."),
+            ExifError::UnknownType(t) => write!(f, "Unknown EXIF field type {}.", t),
+        }
+    }
+}
+
+impl error::Error for ExifError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ExifError::InvalidHeader => None,
+            ExifError::OutOfBounds => None,
+            ExifError::UnknownType(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    Little,
+    Big,
+}
+
+impl ByteOrder {
+    fn u16(&self, bytes: &[u8]) -> u16 {
+        let bytes = [bytes[0], bytes[1]];
+        match self {
+            ByteOrder::Little => u16::from_le_bytes(bytes),
+            ByteOrder::Big => u16::from_be_bytes(bytes),
+        }
+    }
+
+    fn u32(&self, bytes: &[u8]) -> u32 {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        match self {
+            ByteOrder::Little => u32::from_le_bytes(bytes),
+            ByteOrder::Big => u32::from_be_bytes(bytes),
+        }
+    }
+
+    fn u16_bytes(&self, value: u16) -> [u8; 2] {
+        match self {
+            ByteOrder::Little => value.to_le_bytes(),
+            ByteOrder::Big => value.to_be_bytes(),
+        }
+    }
+
+    fn u32_bytes(&self, value: u32) -> [u8; 4] {
+        match self {
+            ByteOrder::Little => value.to_le_bytes(),
+            ByteOrder::Big => value.to_be_bytes(),
+        }
+    }
+}
+
+/// Kind of an IFD, which defines the namespace of the tags of its entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IfdKind {
+    Image(usize),
+    Exif,
+    Gps,
+    Interop,
+}
+
+impl fmt::Display for IfdKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IfdKind::Image(index) => write!(f, "IFD{}", index),
+            IfdKind::Exif => write!(f, "Exif"),
+            IfdKind::Gps => write!(f, "GPS"),
+            IfdKind::Interop => write!(f, "Interop"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    tag: u16,
+    field_type: u16,
+    count: u32,
+    value: Vec<u8>,
+    sub_ifd: Option<Ifd>,
+}
+
+impl Entry {
+    pub fn tag(&self) -> u16 {
+        self.tag
+    }
+
+    pub fn sub_ifd(&self) -> Option<&Ifd> {
+        self.sub_ifd.as_ref()
+    }
+
+    /// Returns the name of the entry tag, in the namespace of the IFD that contains the entry
+    pub fn name(&self, kind: IfdKind) -> String {
+        tag_name(kind, self.tag)
+            .map(String::from)
+            .unwrap_or_else(|| match kind {
+                IfdKind::Gps => format!("GPSTag{:#06x}", self.tag),
+                _ => format!("Tag{:#06x}", self.tag),
+            })
+    }
+
+    /// Formats the entry value in human readable format
+    pub fn value_string(&self, byte_order: ByteOrder) -> String {
+        const MAX_VALUES: usize = 16;
+        let size = type_size(self.field_type).unwrap_or(1);
+        let values = self.value.chunks(size).take(MAX_VALUES);
+        let mut result = match self.field_type {
+            2 => String::from_utf8_lossy(&self.value)
+                .trim_end_matches('\0')
+                .to_string(),
+            3 => join(values.map(|v| byte_order.u16(v).to_string())),
+            4 | 13 => join(values.map(|v| byte_order.u32(v).to_string())),
+            5 => join(values.map(|v| format!("{}/{}", byte_order.u32(v), byte_order.u32(&v[4..])))),
+            8 => join(values.map(|v| (byte_order.u16(v) as i16).to_string())),
+            9 => join(values.map(|v| (byte_order.u32(v) as i32).to_string())),
+            10 => join(values.map(|v| {
+                format!(
+                    "{}/{}",
+                    byte_order.u32(v) as i32,
+                    byte_order.u32(&v[4..]) as i32
+                )
+            })),
+            _ if self.value.len() <= MAX_VALUES => {
+                join(self.value.iter().map(|b| format!("{:02x}", b)))
+            }
+            _ => format!("<{} bytes>", self.value.len()),
+        };
+        if self.field_type != 2 && self.value.len() / size > MAX_VALUES {
+            result.push_str(", ...");
+        }
+        result
+    }
+}
+
+fn join(values: impl Iterator<Item = String>) -> String {
+    values.collect::<Vec<String>>().join(", ")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ifd {
+    kind: IfdKind,
+    entries: Vec<Entry>,
+    thumbnail: Option<Vec<u8>>,
+}
+
+impl Ifd {
+    pub fn kind(&self) -> IfdKind {
+        self.kind
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        self.entries.as_slice()
+    }
+
+    /// Removes the entries, and the sub-IFDs they point to, whose tag name matches the predicate
+    fn remove_entries<F>(&mut self, predicate: &mut F) -> usize
+    where
+        F: FnMut(IfdKind, &Entry) -> bool,
+    {
+        let kind = self.kind;
+        let before = self.entries.len();
+        self.entries.retain(|e| !predicate(kind, e));
+        let mut removed = before - self.entries.len();
+
+        if !self.entries.iter().any(|e| e.tag == THUMBNAIL_OFFSET_TAG) {
+            self.thumbnail = None;
+            self.entries.retain(|e| e.tag != THUMBNAIL_LENGTH_TAG);
+        }
+
+        for entry in self.entries.iter_mut() {
+            if let Some(sub_ifd) = entry.sub_ifd.as_mut() {
+                removed += sub_ifd.remove_entries(predicate);
+            }
+        }
+        removed
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exif {
+    byte_order: ByteOrder,
+    ifds: Vec<Ifd>,
+}
+
+impl Exif {
+    pub const CHUNK_TYPE: &'static str = "eXIf";
+
+    pub fn byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
+
+    pub fn ifds(&self) -> &[Ifd] {
+        self.ifds.as_slice()
+    }
+
+    /// Removes all of the entries whose tag name matches the predicate and returns the number of removed entries
+    pub fn remove_entries<F>(&mut self, mut predicate: F) -> usize
+    where
+        F: FnMut(IfdKind, &Entry) -> bool,
+    {
+        self.ifds
+            .iter_mut()
+            .map(|ifd| ifd.remove_entries(&mut predicate))
+            .sum()
+    }
+
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let marker = match self.byte_order {
+            ByteOrder::Little => LITTLE_ENDIAN_MARKER,
+            ByteOrder::Big => BIG_ENDIAN_MARKER,
+        };
+        let mut bytes: Vec<u8> = marker.to_vec();
+        bytes.extend(self.byte_order.u16_bytes(TIFF_MAGIC));
+        bytes.extend(self.byte_order.u32_bytes(HEADER_LEN as u32));
+
+        let mut next_pointer: Option<usize> = None;
+        for ifd in self.ifds.iter() {
+            let offset = self.write_ifd(&mut bytes, ifd);
+            if let Some(pointer) = next_pointer {
+                self.patch_u32(&mut bytes, pointer, offset as u32);
+            }
+            next_pointer = Some(offset + 2 + ifd.entries.len() * ENTRY_LEN);
+        }
+        bytes
+    }
+
+    fn patch_u32(&self, bytes: &mut [u8], position: usize, value: u32) {
+        bytes[position..position + 4].copy_from_slice(&self.byte_order.u32_bytes(value));
+    }
+
+    /// Writes the IFD, followed by its out of line values and sub-IFDs, and returns the offset of the IFD
+    fn write_ifd(&self, bytes: &mut Vec<u8>, ifd: &Ifd) -> usize {
+        if bytes.len() % 2 == 1 {
+            bytes.push(0);
+        }
+        let offset = bytes.len();
+        bytes.extend(self.byte_order.u16_bytes(ifd.entries.len() as u16));
+        bytes.resize(offset + 2 + ifd.entries.len() * ENTRY_LEN + 4, 0);
+
+        for (i, entry) in ifd.entries.iter().enumerate() {
+            let position = offset + 2 + i * ENTRY_LEN;
+            bytes[position..position + 2].copy_from_slice(&self.byte_order.u16_bytes(entry.tag));
+            bytes[position + 2..position + 4]
+                .copy_from_slice(&self.byte_order.u16_bytes(entry.field_type));
+            self.patch_u32(bytes, position + 4, entry.count);
+
+            let value_position = position + 8;
+            if let Some(sub_ifd) = entry.sub_ifd.as_ref() {
+                let sub_offset = self.write_ifd(bytes, sub_ifd);
+                self.patch_u32(bytes, value_position, sub_offset as u32);
+            } else if entry.tag == THUMBNAIL_OFFSET_TAG && ifd.thumbnail.is_some() {
+                let thumbnail = ifd.thumbnail.as_deref().unwrap_or_default();
+                let thumbnail_offset = bytes.len();
+                bytes.extend(thumbnail);
+                self.patch_u32(bytes, value_position, thumbnail_offset as u32);
+            } else if entry.value.len() <= 4 {
+                bytes[value_position..value_position + entry.value.len()]
+                    .copy_from_slice(&entry.value);
+            } else {
+                if bytes.len() % 2 == 1 {
+                    bytes.push(0);
+                }
+                let value_offset = bytes.len();
+                bytes.extend(&entry.value);
+                self.patch_u32(bytes, value_position, value_offset as u32);
+            }
+        }
+
+        offset
+    }
+}
+
+impl TryFrom<&[u8]> for Exif {
+    type Error = ExifError;
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        if value.len() < HEADER_LEN {
+            return Err(ExifError::InvalidHeader);
+        }
+
+        let byte_order = match [value[0], value[1]] {
+            LITTLE_ENDIAN_MARKER => ByteOrder::Little,
+            BIG_ENDIAN_MARKER => ByteOrder::Big,
+            _ => return Err(ExifError::InvalidHeader),
+        };
+        if byte_order.u16(&value[2..]) != TIFF_MAGIC {
+            return Err(ExifError::InvalidHeader);
+        }
+
+        let mut parser = Parser {
+            data: value,
+            byte_order,
+            visited: vec![],
+        };
+        let mut ifds = vec![];
+        let mut offset = byte_order.u32(&value[4..]) as usize;
+        while offset != 0 && ifds.len() < MAX_IFD_COUNT {
+            let (ifd, next) = parser.ifd(offset, IfdKind::Image(ifds.len()))?;
+            ifds.push(ifd);
+            offset = next;
+        }
+
+        Ok(Exif { byte_order, ifds })
+    }
+}
+
+struct Parser<'a> {
+    data: &'a [u8],
+    byte_order: ByteOrder,
+    visited: Vec<usize>,
+}
+
+impl Parser<'_> {
+    fn slice(&self, offset: usize, len: usize) -> Result<&[u8], ExifError> {
+        self.data
+            .get(offset..offset.checked_add(len).ok_or(ExifError::OutOfBounds)?)
+            .ok_or(ExifError::OutOfBounds)
+    }
+
+    fn ifd(&mut self, offset: usize, kind: IfdKind) -> Result<(Ifd, usize), ExifError> {
+        if self.visited.contains(&offset) {
+            return Err(ExifError::OutOfBounds);
+        }
+        self.visited.push(offset);
+
+        let count = self.byte_order.u16(self.slice(offset, 2)?) as usize;
+        let mut entries = vec![];
+        let mut thumbnail: (Option<usize>, Option<usize>) = (None, None);
+
+        for i in 0..count {
+            let raw = self.slice(offset + 2 + i * ENTRY_LEN, ENTRY_LEN)?;
+            let tag = self.byte_order.u16(raw);
+            let field_type = self.byte_order.u16(&raw[2..]);
+            let count = self.byte_order.u32(&raw[4..]);
+            let len =
+                type_size(field_type).ok_or(ExifError::UnknownType(field_type))? * count as usize;
+            let value = match len {
+                0..=4 => raw[8..8 + len].to_vec(),
+                _ => self
+                    .slice(self.byte_order.u32(&raw[8..]) as usize, len)?
+                    .to_vec(),
+            };
+
+            let sub_kind = match (kind, tag) {
+                (IfdKind::Image(_), EXIF_IFD_TAG) => Some(IfdKind::Exif),
+                (IfdKind::Image(_), GPS_IFD_TAG) => Some(IfdKind::Gps),
+                (IfdKind::Exif, INTEROP_IFD_TAG) => Some(IfdKind::Interop),
+                _ => None,
+            };
+            let sub_ifd = match sub_kind {
+                Some(sub_kind) if len == 4 => {
+                    Some(self.ifd(self.byte_order.u32(&value) as usize, sub_kind)?.0)
+                }
+                _ => None,
+            };
+
+            if matches!(kind, IfdKind::Image(_)) && len == 4 && field_type == LONG_TYPE {
+                match tag {
+                    THUMBNAIL_OFFSET_TAG => {
+                        thumbnail.0 = Some(self.byte_order.u32(&value) as usize)
+                    }
+                    THUMBNAIL_LENGTH_TAG => {
+                        thumbnail.1 = Some(self.byte_order.u32(&value) as usize)
+                    }
+                    _ => (),
+                }
+            }
+
+            entries.push(Entry {
+                tag,
+                field_type,
+                count,
+                value,
+                sub_ifd,
+            });
+        }
+
+        let thumbnail = match thumbnail {
+            (Some(offset), Some(len)) => Some(self.slice(offset, len)?.to_vec()),
+            _ => None,
+        };
+        let next = self
+            .byte_order
+            .u32(self.slice(offset + 2 + count * ENTRY_LEN, 4)?) as usize;
+
+        Ok((
+            Ifd {
+                kind,
+                entries,
+                thumbnail,
+            },
+            next,
+        ))
+    }
+}
+
+/// Returns the size in bytes of a single value of the field type
+fn type_size(field_type: u16) -> Option<usize> {
+    match field_type {
+        1 | 2 | 6 | 7 => Some(1),
+        3 | 8 => Some(2),
+        4 | 9 | 11 | 13 => Some(4),
+        5 | 10 | 12 => Some(8),
+        _ => None,
+    }
+}
+
+/// Returns the name of the well known tags
+fn tag_name(kind: IfdKind, tag: u16) -> Option<&'static str> {
+    let name = match kind {
+        IfdKind::Gps => match tag {
+            0x0000 => "GPSVersionID",
+            0x0001 => "GPSLatitudeRef",
+            0x0002 => "GPSLatitude",
+            0x0003 => "GPSLongitudeRef",
+            0x0004 => "GPSLongitude",
+            0x0005 => "GPSAltitudeRef",
+            0x0006 => "GPSAltitude",
+            0x0007 => "GPSTimeStamp",
+            0x0008 => "GPSSatellites",
+            0x0009 => "GPSStatus",
+            0x000a => "GPSMeasureMode",
+            0x000b => "GPSDOP",
+            0x000c => "GPSSpeedRef",
+            0x000d => "GPSSpeed",
+            0x0010 => "GPSImgDirectionRef",
+            0x0011 => "GPSImgDirection",
+            0x0012 => "GPSMapDatum",
+            0x001b => "GPSProcessingMethod",
+            0x001d => "GPSDateStamp",
+            _ => return None,
+        },
+        IfdKind::Interop => match tag {
+            0x0001 => "InteropIndex",
+            0x0002 => "InteropVersion",
+            _ => return None,
+        },
+        IfdKind::Image(_) | IfdKind::Exif => match tag {
+            0x010e => "ImageDescription",
+            0x010f => "Make",
+            0x0110 => "Model",
+            0x0112 => "Orientation",
+            0x011a => "XResolution",
+            0x011b => "YResolution",
+            0x0128 => "ResolutionUnit",
+            0x0131 => "Software",
+            0x0132 => "DateTime",
+            0x013b => "Artist",
+            0x0201 => "ThumbnailOffset",
+            0x0202 => "ThumbnailLength",
+            0x0213 => "YCbCrPositioning",
+            0x8298 => "Copyright",
+            0x829a => "ExposureTime",
+            0x829d => "FNumber",
+            0x8769 => "ExifOffset",
+            0x8822 => "ExposureProgram",
+            0x8825 => "GPSInfo",
+            0x8827 => "ISO",
+            0x9000 => "ExifVersion",
+            0x9003 => "DateTimeOriginal",
+            0x9004 => "DateTimeDigitized",
+            0x9010 => "OffsetTime",
+            0x9011 => "OffsetTimeOriginal",
+            0x9012 => "OffsetTimeDigitized",
+            0x9101 => "ComponentsConfiguration",
+            0x9201 => "ShutterSpeedValue",
+            0x9202 => "ApertureValue",
+            0x9204 => "ExposureBiasValue",
+            0x9207 => "MeteringMode",
+            0x9209 => "Flash",
+            0x920a => "FocalLength",
+            0x927c => "MakerNote",
+            0x9286 => "UserComment",
+            0x9290 => "SubSecTime",
+            0x9291 => "SubSecTimeOriginal",
+            0x9292 => "SubSecTimeDigitized",
+            0xa000 => "FlashpixVersion",
+            0xa001 => "ColorSpace",
+            0xa002 => "PixelXDimension",
+            0xa003 => "PixelYDimension",
+            0xa005 => "InteropOffset",
+            0xa402 => "ExposureMode",
+            0xa403 => "WhiteBalance",
+            0xa420 => "ImageUniqueID",
+            0xa430 => "CameraOwnerName",
+            0xa431 => "BodySerialNumber",
+            0xa432 => "LensSpecification",
+            0xa433 => "LensMake",
+            0xa434 => "LensModel",
+            0xa435 => "LensSerialNumber",
+            _ => return None,
+        },
+    };
+    Some(name)
+}
+
+/// Matches a tag name against a pattern where `*` matches any sequence of characters, ignoring case
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_ascii_lowercase();
+    let name = name.to_ascii_lowercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !name.starts_with(first) || name.len() < first.len() + last.len() || !name.ends_with(last) {
+        return false;
+    }
+
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(bytes: &mut Vec<u8>, tag: u16, field_type: u16, count: u32, value: [u8; 4]) {
+        bytes.extend(tag.to_be_bytes());
+        bytes.extend(field_type.to_be_bytes());
+        bytes.extend(count.to_be_bytes());
+        bytes.extend(value);
+    }
+
+    /// Big endian EXIF with IFD0 { Make, ExifOffset, GPSInfo }, Exif { DateTimeOriginal }, GPS { GPSLatitudeRef }
+    fn testing_exif() -> Vec<u8> {
+        let mut bytes: Vec<u8> = b"MM".to_vec();
+        bytes.extend(42u16.to_be_bytes());
+        bytes.extend(8u32.to_be_bytes());
+
+        // IFD0 at 8, 3 entries, ends at 8 + 2 + 36 + 4 = 50
+        bytes.extend(3u16.to_be_bytes());
+        entry(&mut bytes, 0x010f, 2, 6, 50u32.to_be_bytes());
+        entry(&mut bytes, 0x8769, 4, 1, 56u32.to_be_bytes());
+        entry(&mut bytes, 0x8825, 4, 1, 94u32.to_be_bytes());
+        bytes.extend(0u32.to_be_bytes());
+        // Make value at 50
+        bytes.extend(b"Canon\0");
+        // Exif IFD at 56, 1 entry, ends at 56 + 18 = 74
+        bytes.extend(1u16.to_be_bytes());
+        entry(&mut bytes, 0x9003, 2, 20, 74u32.to_be_bytes());
+        bytes.extend(0u32.to_be_bytes());
+        // DateTimeOriginal value at 74
+        bytes.extend(b"2023:01:02 03:04:05\0");
+        // GPS IFD at 94
+        bytes.extend(1u16.to_be_bytes());
+        entry(&mut bytes, 0x0001, 2, 2, *b"N\0\0\0");
+        bytes.extend(0u32.to_be_bytes());
+
+        bytes
+    }
+
+    fn names(exif: &Exif) -> Vec<String> {
+        fn walk(ifd: &Ifd, names: &mut Vec<String>) {
+            for entry in ifd.entries() {
+                names.push(entry.name(ifd.kind()));
+                if let Some(sub_ifd) = entry.sub_ifd() {
+                    walk(sub_ifd, names);
+                }
+            }
+        }
+        let mut result = vec![];
+        exif.ifds().iter().for_each(|ifd| walk(ifd, &mut result));
+        result
+    }
+
+    #[test]
+    fn test_parse_exif() {
+        let exif = Exif::try_from(testing_exif().as_slice()).unwrap();
+        assert_eq!(exif.byte_order(), ByteOrder::Big);
+        assert_eq!(exif.ifds().len(), 1);
+        assert_eq!(
+            names(&exif),
+            vec![
+                "Make",
+                "ExifOffset",
+                "DateTimeOriginal",
+                "GPSInfo",
+                "GPSLatitudeRef"
+            ]
+        );
+        let make = &exif.ifds()[0].entries()[0];
+        assert_eq!(make.value_string(ByteOrder::Big), "Canon");
+    }
+
+    #[test]
+    fn test_serialize_roundtrip() {
+        let bytes = testing_exif();
+        let exif = Exif::try_from(bytes.as_slice()).unwrap();
+        let serialized = exif.as_bytes();
+        assert_eq!(Exif::try_from(serialized.as_slice()).unwrap(), exif);
+    }
+
+    #[test]
+    fn test_remove_entries() {
+        let mut exif = Exif::try_from(testing_exif().as_slice()).unwrap();
+        let removed = exif.remove_entries(|kind, e| matches_pattern("GPS*", &e.name(kind)));
+        assert_eq!(removed, 1);
+        let removed = exif.remove_entries(|kind, e| matches_pattern("DateTime*", &e.name(kind)));
+        assert_eq!(removed, 1);
+
+        let reparsed = Exif::try_from(exif.as_bytes().as_slice()).unwrap();
+        assert_eq!(names(&reparsed), vec!["Make", "ExifOffset"]);
+    }
+
+    #[test]
+    fn test_invalid_exif() {
+        assert!(Exif::try_from(&b"XX\0*\0\0\0\x08"[..]).is_err());
+        assert!(Exif::try_from(&b"MM\0*\0\0\0\xff"[..]).is_err());
+    }
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("GPS*", "GPSLatitude"));
+        assert!(matches_pattern("gps*", "GPSInfo"));
+        assert!(matches_pattern("*Serial*", "BodySerialNumber"));
+        assert!(matches_pattern("Make", "Make"));
+        assert!(!matches_pattern("Make", "MakerNote"));
+        assert!(!matches_pattern("GPS*", "Make"));
+    }
+}
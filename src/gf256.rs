@@ -0,0 +1,112 @@
+/*!
+ * # GF(256) crate
+ *
+ * Defines the arithmetic of the finite field GF(2^8) that is used by the secret sharing and error correction crates.
+ * The field is generated by the polynomial x^8 + x^4 + x^3 + x^2 + 1 (0x11d) with generator element 2.
+ *
+ */
+
+const PRIMITIVE_POLYNOMIAL: u16 = 0x11d;
+const FIELD_SIZE: usize = 256;
+const FIELD_ORDER: usize = FIELD_SIZE - 1;
+
+/**
+ * Precomputed exponent and logarithm tables of the field.
+ *
+ * The exponent table is doubled in size so that the sum of two logarithms can be used as an index without a modulo.
+ */
+struct Tables {
+    exp: [u8; FIELD_ORDER * 2],
+    log: [u8; FIELD_SIZE],
+}
+
+const TABLES: Tables = build_tables();
+
+const fn build_tables() -> Tables {
+    let mut exp = [0u8; FIELD_ORDER * 2];
+    let mut log = [0u8; FIELD_SIZE];
+    let mut value: u16 = 1;
+    let mut i = 0;
+    while i < FIELD_ORDER {
+        exp[i] = value as u8;
+        exp[i + FIELD_ORDER] = value as u8;
+        log[value as usize] = i as u8;
+        value <<= 1;
+        if value & 0x100 != 0 {
+            value ^= PRIMITIVE_POLYNOMIAL;
+        }
+        i += 1;
+    }
+    Tables { exp, log }
+}
+
+/// Adds two field elements, which is the same operation as subtraction
+pub fn add(a: u8, b: u8) -> u8 {
+    a ^ b
+}
+
+/// Multiplies two field elements
+pub fn mul(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    TABLES.exp[TABLES.log[a as usize] as usize + TABLES.log[b as usize] as usize]
+}
+
+/// Divides two field elements, panics on division by zero
+pub fn div(a: u8, b: u8) -> u8 {
+    assert!(b != 0, "division by zero in GF(256)");
+    if a == 0 {
+        return 0;
+    }
+    TABLES.exp[(TABLES.log[a as usize] as usize + FIELD_ORDER - TABLES.log[b as usize] as usize)
+        % FIELD_ORDER]
+}
+
+/// Raises the generator element to the given power
+pub fn exp(power: usize) -> u8 {
+    TABLES.exp[power % FIELD_ORDER]
+}
+
+/// Evaluates a polynomial, given with the constant coefficient first, at point `x`
+pub fn poly_eval(coefficients: &[u8], x: u8) -> u8 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(0, |acc, &c| add(mul(acc, x), c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_is_xor() {
+        assert_eq!(add(0x53, 0xca), 0x99);
+        assert_eq!(add(0x53, 0x53), 0);
+    }
+
+    #[test]
+    fn test_mul_identity_and_zero() {
+        for a in 0..=255u8 {
+            assert_eq!(mul(a, 1), a);
+            assert_eq!(mul(a, 0), 0);
+        }
+    }
+
+    #[test]
+    fn test_mul_div_roundtrip() {
+        for a in 0..=255u8 {
+            for b in 1..=255u8 {
+                assert_eq!(div(mul(a, b), b), a);
+            }
+        }
+    }
+
+    #[test]
+    fn test_poly_eval() {
+        // 3 + 2x + x^2 at x = 2 is 3 ^ mul(2, 2) ^ mul(2, 2) = 3
+        assert_eq!(poly_eval(&[3, 2, 1], 2), 3);
+        assert_eq!(poly_eval(&[7], 42), 7);
+    }
+}
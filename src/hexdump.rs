@@ -0,0 +1,65 @@
+/*!
+ * # Hexdump crate
+ *
+ * Defines the canonical hex and ASCII listing of binary data, with 16 bytes per line, as written by `hexdump -C`.
+ *
+ */
+
+use std::fmt::Write;
+
+const BYTES_PER_LINE: usize = 16;
+
+/// Returns the printable ASCII representation of the byte
+fn printable(byte: u8) -> char {
+    match byte {
+        0x20..=0x7e => byte as char,
+        _ => '.',
+    }
+}
+
+/// Lists the data in lines of 16 bytes, the offsets of the lines start at the specified offset
+pub fn hexdump(data: &[u8], offset: usize) -> String {
+    let mut listing = String::new();
+    for (line, bytes) in data.chunks(BYTES_PER_LINE).enumerate() {
+        let _ = write!(listing, "{:08x} ", offset + line * BYTES_PER_LINE);
+        for column in 0..BYTES_PER_LINE {
+            if column % 8 == 0 {
+                listing.push(' ');
+            }
+            match bytes.get(column) {
+                Some(byte) => {
+                    let _ = write!(listing, "{:02x} ", byte);
+                }
+                None => listing.push_str("   "),
+            }
+        }
+        let ascii: String = bytes.iter().map(|&b| printable(b)).collect();
+        let _ = writeln!(listing, " |{}|", ascii);
+    }
+    listing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hexdump() {
+        let data: Vec<u8> = b"IHDR".iter().copied().chain(0..14).collect();
+        let listing = hexdump(&data, 8);
+        let lines: Vec<&str> = listing.lines().collect();
+        assert_eq!(
+            lines[0],
+            "00000008  49 48 44 52 00 01 02 03  04 05 06 07 08 09 0a 0b  |IHDR............|"
+        );
+        assert_eq!(
+            lines[1],
+            "00000018  0c 0d                                             |..|"
+        );
+    }
+
+    #[test]
+    fn test_hexdump_empty() {
+        assert_eq!(hexdump(&[], 0), "");
+    }
+}
@@ -0,0 +1,177 @@
+/*!
+ * # Image header crate
+ *
+ * Defines the content of the `IHDR` chunk as specified in [PNG specification](http://www.libpng.org/pub/png/spec/1.2/PNG-Chunks.html#C.IHDR).
+ *
+ */
+
+use std::error;
+use std::fmt;
+
+const IMAGE_HEADER_LEN: usize = 13;
+
+#[derive(Debug)]
+pub enum ImageHeaderError {
+    InvalidLength,
+    InvalidColorType(u8),
+}
+
+impl fmt::Display for ImageHeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ImageHeaderError::InvalidLength => write!(f, "Invalid length of the IHDR chunk data."),
+            ImageHeaderError::InvalidColorType(t) => write!(f, "Invalid color type {}.", t),
+        }
+    }
+}
+
+impl error::Error for ImageHeaderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ImageHeaderError::InvalidLength => None,
+            ImageHeaderError::InvalidColorType(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorType {
+    Grayscale = 0,
+    Rgb = 2,
+    Indexed = 3,
+    GrayscaleAlpha = 4,
+    Rgba = 6,
+}
+
+impl ColorType {
+    /// Returns the number of samples per pixel
+    pub fn channels(&self) -> usize {
+        match self {
+            ColorType::Grayscale | ColorType::Indexed => 1,
+            ColorType::GrayscaleAlpha => 2,
+            ColorType::Rgb => 3,
+            ColorType::Rgba => 4,
+        }
+    }
+}
+
+impl TryFrom<u8> for ColorType {
+    type Error = ImageHeaderError;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ColorType::Grayscale),
+            2 => Ok(ColorType::Rgb),
+            3 => Ok(ColorType::Indexed),
+            4 => Ok(ColorType::GrayscaleAlpha),
+            6 => Ok(ColorType::Rgba),
+            _ => Err(ImageHeaderError::InvalidColorType(value)),
+        }
+    }
+}
+
+impl fmt::Display for ColorType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorType::Grayscale => write!(f, "grayscale"),
+            ColorType::Rgb => write!(f, "RGB"),
+            ColorType::Indexed => write!(f, "indexed"),
+            ColorType::GrayscaleAlpha => write!(f, "grayscale with alpha"),
+            ColorType::Rgba => write!(f, "RGBA"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageHeader {
+    pub width: u32,
+    pub height: u32,
+    pub bit_depth: u8,
+    pub color_type: ColorType,
+    pub compression_method: u8,
+    pub filter_method: u8,
+    pub interlace_method: u8,
+}
+
+impl ImageHeader {
+    pub const CHUNK_TYPE: &'static str = "IHDR";
+
+    pub fn as_bytes(&self) -> Vec<u8> {
+        self.width
+            .to_be_bytes()
+            .iter()
+            .chain(self.height.to_be_bytes().iter())
+            .chain(
+                [
+                    self.bit_depth,
+                    self.color_type as u8,
+                    self.compression_method,
+                    self.filter_method,
+                    self.interlace_method,
+                ]
+                .iter(),
+            )
+            .copied()
+            .collect()
+    }
+}
+
+impl TryFrom<&[u8]> for ImageHeader {
+    type Error = ImageHeaderError;
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        if value.len() != IMAGE_HEADER_LEN {
+            return Err(ImageHeaderError::InvalidLength);
+        }
+
+        Ok(ImageHeader {
+            width: u32::from_be_bytes([value[0], value[1], value[2], value[3]]),
+            height: u32::from_be_bytes([value[4], value[5], value[6], value[7]]),
+            bit_depth: value[8],
+            color_type: ColorType::try_from(value[9])?,
+            compression_method: value[10],
+            filter_method: value[11],
+            interlace_method: value[12],
+        })
+    }
+}
+
+impl fmt::Display for ImageHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}x{}, bit depth {}, color type {}, compression {}, filter {}, interlace {}",
+            self.width,
+            self.height,
+            self.bit_depth,
+            self.color_type,
+            self.compression_method,
+            self.filter_method,
+            match self.interlace_method {
+                0 => "none",
+                1 => "Adam7",
+                _ => "unknown",
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_header_roundtrip() {
+        let bytes = [0, 0, 0, 50, 0, 0, 0, 40, 8, 6, 0, 0, 0];
+        let header = ImageHeader::try_from(&bytes[..]).unwrap();
+        assert_eq!(header.width, 50);
+        assert_eq!(header.height, 40);
+        assert_eq!(header.color_type, ColorType::Rgba);
+        assert_eq!(header.color_type.channels(), 4);
+        assert_eq!(header.as_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_invalid_image_header() {
+        assert!(ImageHeader::try_from(&[0, 0, 0, 50][..]).is_err());
+        assert!(ImageHeader::try_from(&[0, 0, 0, 50, 0, 0, 0, 40, 8, 5, 0, 0, 0][..]).is_err());
+    }
+}
@@ -7,19 +7,86 @@
  * - decode
  * - remove
  * - print
+ * - split-encode
+ * - combine-decode
+ * - replace
+ * - rename-chunk
+ * - move
+ * - extract
+ * - import
+ * - transplant
+ * - strip
+ * - exif
+ * - xmp
+ * - set-dpi
+ * - touch
+ * - apng
+ * - disassemble
+ * - assemble
+ * - textconv
+ * - diff
+ * - shell
+ * - view
+ * - grep
+ * - carve
+ * - trailer
  *
  */
 
+/// Ancillary crate used as module
+pub mod ancillary;
+/// APNG crate used as module
+pub mod apng;
 /// Args crate used as module
 mod args;
+/// Assembly crate used as module
+pub mod assembly;
+/// Batch crate used as module
+mod batch;
+/// Carve crate used as module
+mod carve;
 /// Chunk crate used as module
-mod chunk;
+pub mod chunk;
 /// Chunk type crate used as module
-mod chunk_type;
+pub mod chunk_type;
 /// Commands crate used as module
 mod commands;
+/// Detail crate used as module
+mod detail;
+/// Diff crate used as module
+pub mod diff;
+/// EXIF crate used as module
+mod exif;
+/// GF(256) crate used as module
+mod gf256;
+/// Hexdump crate used as module
+mod hexdump;
+/// Image header crate used as module
+pub mod image_header;
+/// Output crate used as module
+mod output;
+/// Pixels crate used as module
+pub mod pixels;
 /// PNG crate used as module
-mod png;
+pub mod png;
+/// Reed-Solomon crate used as module
+mod reed_solomon;
+/// Report crate used as module
+pub mod report;
+/// Search crate used as module
+mod search;
+/// Shamir crate used as module
+mod shamir;
+/// Shell crate used as module
+mod shell;
+/// Summary crate used as module
+mod summary;
+/// Text crate used as module
+pub mod text;
+/// View crate used as module
+mod view;
+/// XMP crate used as module
+mod xmp;
 
 use args::PngMeArgs;
 use clap::Parser;
@@ -82,10 +149,33 @@ impl Config {
  */
 pub fn run(config: Config) -> Result<(), ConfigError> {
     match config.args {
-        PngMeArgs::Encode(args) => commands::encode(args)?,
-        PngMeArgs::Decode(args) => commands::decode(args)?,
-        PngMeArgs::Remove(args) => commands::remove(args)?,
-        PngMeArgs::Print(args) => commands::print_chunks(args)?,
+        PngMeArgs::Encode(args) => commands::batch(args, commands::encode)?,
+        PngMeArgs::Decode(args) => commands::batch(args, commands::decode)?,
+        PngMeArgs::Remove(args) => commands::batch(args, commands::remove)?,
+        PngMeArgs::Print(args) => commands::batch(args, commands::print_chunks)?,
+        PngMeArgs::SplitEncode(args) => commands::split_encode(args)?,
+        PngMeArgs::CombineDecode(args) => commands::combine_decode(args)?,
+        PngMeArgs::Replace(args) => commands::batch(args, commands::replace)?,
+        PngMeArgs::RenameChunk(args) => commands::batch(args, commands::rename_chunk)?,
+        PngMeArgs::Move(args) => commands::batch(args, commands::move_chunk)?,
+        PngMeArgs::Extract(args) => commands::batch(args, commands::extract)?,
+        PngMeArgs::Import(args) => commands::batch(args, commands::import)?,
+        PngMeArgs::Transplant(args) => commands::batch(args, commands::transplant)?,
+        PngMeArgs::Strip(args) => commands::batch(args, commands::strip)?,
+        PngMeArgs::Exif(args) => commands::exif(args)?,
+        PngMeArgs::Xmp(args) => commands::xmp(args)?,
+        PngMeArgs::SetDpi(args) => commands::batch(args, commands::set_dpi)?,
+        PngMeArgs::Touch(args) => commands::batch(args, commands::touch)?,
+        PngMeArgs::Apng(args) => commands::apng(args)?,
+        PngMeArgs::Disassemble(args) => commands::batch(args, commands::disassemble)?,
+        PngMeArgs::Assemble(args) => commands::assemble(args)?,
+        PngMeArgs::Textconv(args) => commands::batch(args, commands::textconv)?,
+        PngMeArgs::Diff(args) => commands::diff(args)?,
+        PngMeArgs::Shell(args) => commands::shell(args)?,
+        PngMeArgs::View(args) => commands::view(args)?,
+        PngMeArgs::Grep(args) => commands::grep(args)?,
+        PngMeArgs::Carve(args) => commands::carve(args)?,
+        PngMeArgs::Trailer(args) => commands::trailer(args)?,
     };
 
     Ok(())
@@ -0,0 +1,265 @@
+/*!
+ * # Output crate
+ *
+ * Defines the writing of the modified PNG files. Files are written to a temporary file in the same directory, synced
+ * to the disk and renamed over the original file, so that a crash or a full disk never leaves a partially written file.
+ *
+ * The `-` path stands for the standard input when reading and for the standard output when writing, so that the files
+ * can be processed in shell pipelines.
+ *
+ */
+
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+const STANDARD_STREAM: &str = "-";
+
+/// Counter that keeps the temporary files of the threads of the same process apart
+static TEMPORARY_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Standard input, read at most once and shared by every reader
+static STANDARD_INPUT: OnceLock<Vec<u8>> = OnceLock::new();
+
+/// Returns whether the path stands for the standard input or output
+pub fn is_standard_stream(path: &Path) -> bool {
+    path == Path::new(STANDARD_STREAM)
+}
+
+/// Reads the whole file, or the standard input if the path is `-`
+pub fn read(path: &Path) -> io::Result<Vec<u8>> {
+    if !is_standard_stream(path) {
+        return fs::read(path);
+    }
+
+    if let Some(bytes) = STANDARD_INPUT.get() {
+        return Ok(bytes.clone());
+    }
+    let mut bytes = Vec::new();
+    io::stdin().lock().read_to_end(&mut bytes)?;
+    Ok(STANDARD_INPUT.get_or_init(|| bytes).clone())
+}
+
+/// Writes the whole file, or the standard output if the path is `-`
+pub fn write(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if !is_standard_stream(path) {
+        return fs::write(path, contents);
+    }
+
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(contents)?;
+    stdout.flush()
+}
+
+/// Returns the path with the suffix appended to its file name
+pub fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().map(OsString::from).unwrap_or_default();
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+/// Creates a new temporary file next to the path
+fn create_temporary(path: &Path) -> io::Result<(PathBuf, File)> {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    loop {
+        let temporary = parent_dir(path).join(format!(
+            ".{}.{}.{}.tmp",
+            file_name,
+            process::id(),
+            TEMPORARY_COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temporary)
+        {
+            Ok(file) => return Ok((temporary, file)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn write_temporary(
+    file: &mut File,
+    contents: &[u8],
+    original: Option<&fs::Metadata>,
+    preserve_metadata: bool,
+) -> io::Result<()> {
+    file.write_all(contents)?;
+    if let Some(metadata) = original {
+        file.set_permissions(metadata.permissions())?;
+        if preserve_metadata {
+            file.set_modified(metadata.modified()?)?;
+        }
+    }
+    file.sync_all()
+}
+
+/**
+ * Atomically replaces the contents of the file, creating it if it doesn't exist, or writes them to the standard output
+ * if the path is `-`.
+ *
+ * If the path is a symbolic link, the file it points to is replaced and the link is kept. The permissions of the
+ * original file are always kept, and its modification time as well if the metadata should be preserved.
+ *
+ * If a backup suffix is given, the original file is copied to the path with the suffix appended before it is replaced.
+ */
+pub fn write_atomic(
+    path: &Path,
+    contents: &[u8],
+    backup_suffix: Option<&str>,
+    preserve_metadata: bool,
+) -> io::Result<()> {
+    if is_standard_stream(path) {
+        return write(path, contents);
+    }
+
+    let (target, original) = match fs::canonicalize(path) {
+        Ok(target) => {
+            let metadata = fs::metadata(&target)?;
+            (target, Some(metadata))
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => (path.to_path_buf(), None),
+        Err(err) => return Err(err),
+    };
+
+    let (temporary, mut file) = create_temporary(&target)?;
+    let written = write_temporary(&mut file, contents, original.as_ref(), preserve_metadata);
+    drop(file);
+    if let Err(err) = written {
+        let _ = fs::remove_file(&temporary);
+        return Err(err);
+    }
+
+    if let (Some(suffix), Some(_)) = (backup_suffix, &original) {
+        if let Err(err) = fs::copy(&target, with_suffix(path, suffix)) {
+            let _ = fs::remove_file(&temporary);
+            return Err(err);
+        }
+    }
+
+    if let Err(err) = fs::rename(&temporary, &target) {
+        let _ = fs::remove_file(&temporary);
+        return Err(err);
+    }
+
+    // NOTE: syncing the directory persists the rename itself, which isn't supported on every platform
+    #[cfg(unix)]
+    File::open(parent_dir(&target))?.sync_all()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    fn testing_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pngme-output-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_write_atomic_with_backup() {
+        let dir = testing_dir("backup");
+        let path = dir.join("image.png");
+        fs::write(&path, b"original").unwrap();
+
+        write_atomic(&path, b"modified", Some(".bak"), false).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"modified");
+        assert_eq!(fs::read(dir.join("image.png.bak")).unwrap(), b"original");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_atomic_preserves_metadata() {
+        let dir = testing_dir("preserve");
+        let path = dir.join("image.png");
+        fs::write(&path, b"original").unwrap();
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        write_atomic(&path, b"modified", None, true).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), modified);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = testing_dir("permissions");
+        let path = dir.join("image.png");
+        fs::write(&path, b"original").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+
+        write_atomic(&path, b"modified", None, false).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_follows_symlink() {
+        let dir = testing_dir("symlink");
+        fs::create_dir(dir.join("target")).unwrap();
+        let target = dir.join("target/image.png");
+        let link = dir.join("link.png");
+        fs::write(&target, b"original").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        write_atomic(&link, b"modified", Some(".bak"), false).unwrap();
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read(&target).unwrap(), b"modified");
+        assert_eq!(fs::read(dir.join("link.png.bak")).unwrap(), b"original");
+        assert_eq!(fs::read_dir(dir.join("target")).unwrap().count(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_atomic_creates_file() {
+        let dir = testing_dir("create");
+        let path = dir.join("image.png");
+        write_atomic(&path, b"new", Some(".bak"), true).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        assert!(!dir.join("image.png.bak").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_write_file() {
+        let dir = testing_dir("read-write");
+        let path = dir.join("image.png");
+        write(&path, b"contents").unwrap();
+        assert_eq!(read(&path).unwrap(), b"contents");
+        assert!(is_standard_stream(Path::new("-")));
+        assert!(!is_standard_stream(&dir.join("-")));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,445 @@
+/*!
+ * # Pixels crate
+ *
+ * Defines the decoding of the image data as specified in [PNG specification](http://www.libpng.org/pub/png/spec/1.2/PNG-Filters.html).
+ *
+ * Pixels of every color type and bit depth, interlaced or not, are decoded to 16 bit RGBA samples, so that the images
+ * stored with different color types can be compared with each other.
+ *
+ */
+
+use crate::image_header::{ColorType, ImageHeader, ImageHeaderError};
+use crate::png::Png;
+use crate::text;
+use crate::text::TextError;
+use std::error;
+use std::fmt;
+
+const IMAGE_DATA: [u8; 4] = *b"IDAT";
+const PALETTE: &str = "PLTE";
+const TRANSPARENCY: &str = "tRNS";
+const OPAQUE: u16 = u16::MAX;
+/// Maximum number of pixels decoded, e.g. 8192x8192, so that a forged image header can't exhaust the memory
+const MAX_PIXELS: usize = 1 << 26;
+/// Starting column, starting row, column increment and row increment of the Adam7 passes
+const ADAM7_PASSES: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+#[derive(Debug)]
+pub enum PixelError {
+    MissingImageHeader,
+    UnsupportedFormat,
+    UnknownFilter(u8),
+    TruncatedData,
+    InvalidPaletteIndex(u8),
+    ImageTooLarge(u32, u32),
+    ExcessData,
+    ImageHeader(ImageHeaderError),
+    Compression(TextError),
+}
+
+impl From<ImageHeaderError> for PixelError {
+    fn from(item: ImageHeaderError) -> PixelError {
+        PixelError::ImageHeader(item)
+    }
+}
+
+impl From<TextError> for PixelError {
+    fn from(item: TextError) -> PixelError {
+        PixelError::Compression(item)
+    }
+}
+
+impl fmt::Display for PixelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PixelError::MissingImageHeader => write!(f, "PNG file doesn't contain an IHDR chunk."),
+            PixelError::UnsupportedFormat => {
+                write!(f, "Unsupported bit depth, compression or filter method.")
+            }
+            PixelError::UnknownFilter(filter) => write!(f, "Unknown filter type {}.", filter),
+            PixelError::TruncatedData => write!(f, "Image data is shorter than the image size."),
+            PixelError::InvalidPaletteIndex(index) => {
+                write!(f, "Palette index {} is out of the palette.", index)
+            }
+            PixelError::ImageTooLarge(width, height) => write!(
+                f,
+                "Image of {}x{} pixels exceeds the limit of {} pixels.",
+                width, height, MAX_PIXELS
+            ),
+            PixelError::ExcessData => write!(f, "Image data is longer than the image size."),
+            PixelError::ImageHeader(ref err) => write!(f, "Image header error: {}", err),
+            PixelError::Compression(ref err) => write!(f, "Compression error: {}", err),
+        }
+    }
+}
+
+impl error::Error for PixelError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            PixelError::MissingImageHeader => None,
+            PixelError::UnsupportedFormat => None,
+            PixelError::UnknownFilter(_) => None,
+            PixelError::TruncatedData => None,
+            PixelError::InvalidPaletteIndex(_) => None,
+            PixelError::ImageTooLarge(..) => None,
+            PixelError::ExcessData => None,
+            PixelError::ImageHeader(ref err) => Some(err),
+            PixelError::Compression(ref err) => Some(err),
+        }
+    }
+}
+
+/// Decoded image, stored row by row as 16 bit RGBA samples
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u16; 4]>,
+}
+
+impl Image {
+    pub fn pixel(&self, x: usize, y: usize) -> [u16; 4] {
+        self.pixels[y * self.width + x]
+    }
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let p = left as i16 + up as i16 - up_left as i16;
+    let (pa, pb, pc) = (
+        (p - left as i16).abs(),
+        (p - up as i16).abs(),
+        (p - up_left as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        left
+    } else if pb <= pc {
+        up
+    } else {
+        up_left
+    }
+}
+
+/// Reverses the filter of a scanline in place, using the already unfiltered previous scanline
+fn unfilter(filter: u8, line: &mut [u8], previous: &[u8], bpp: usize) -> Result<(), PixelError> {
+    for i in 0..line.len() {
+        let left = if i >= bpp { line[i - bpp] } else { 0 };
+        let up = previous.get(i).copied().unwrap_or(0);
+        let up_left = if i >= bpp {
+            previous.get(i - bpp).copied().unwrap_or(0)
+        } else {
+            0
+        };
+        let predictor = match filter {
+            0 => 0,
+            1 => left,
+            2 => up,
+            3 => ((left as u16 + up as u16) / 2) as u8,
+            4 => paeth(left, up, up_left),
+            _ => return Err(PixelError::UnknownFilter(filter)),
+        };
+        line[i] = line[i].wrapping_add(predictor);
+    }
+    Ok(())
+}
+
+/// Reads the samples of a single unfiltered scanline
+fn samples(line: &[u8], bit_depth: u8, count: usize) -> Vec<u16> {
+    match bit_depth {
+        16 => line
+            .chunks_exact(2)
+            .take(count)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect(),
+        8 => line.iter().take(count).map(|&b| b as u16).collect(),
+        depth => {
+            let per_byte = 8 / depth as usize;
+            let mask = (1u16 << depth) - 1;
+            (0..count)
+                .map(|i| {
+                    let byte = line[i / per_byte] as u16;
+                    let shift = 8 - depth as usize * (i % per_byte + 1);
+                    (byte >> shift) & mask
+                })
+                .collect()
+        }
+    }
+}
+
+/// Converts the samples of a pixel to 16 bit RGBA
+struct Converter {
+    header: ImageHeader,
+    palette: Vec<[u16; 4]>,
+    transparent: Option<Vec<u16>>,
+}
+
+impl Converter {
+    fn new(png: &Png, header: ImageHeader) -> Converter {
+        let alphas = png
+            .chunk_by_type(TRANSPARENCY)
+            .map(|c| c.data().to_vec())
+            .unwrap_or_default();
+        let palette = png
+            .chunk_by_type(PALETTE)
+            .map(|c| {
+                c.data()
+                    .chunks_exact(3)
+                    .enumerate()
+                    .map(|(i, rgb)| {
+                        let alpha = alphas.get(i).map_or(OPAQUE, |&a| a as u16 * 257);
+                        [
+                            rgb[0] as u16 * 257,
+                            rgb[1] as u16 * 257,
+                            rgb[2] as u16 * 257,
+                            alpha,
+                        ]
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let transparent = match header.color_type {
+            ColorType::Grayscale | ColorType::Rgb => png.chunk_by_type(TRANSPARENCY).map(|c| {
+                c.data()
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                    .collect()
+            }),
+            _ => None,
+        };
+        Converter {
+            header,
+            palette,
+            transparent,
+        }
+    }
+
+    fn scale(&self, sample: u16) -> u16 {
+        let max = (1u32 << self.header.bit_depth) - 1;
+        (sample as u32 * OPAQUE as u32 / max) as u16
+    }
+
+    fn convert(&self, pixel: &[u16]) -> Result<[u16; 4], PixelError> {
+        let alpha = match &self.transparent {
+            Some(key) if key.as_slice() == pixel => 0,
+            _ => OPAQUE,
+        };
+        Ok(match self.header.color_type {
+            ColorType::Grayscale => {
+                let gray = self.scale(pixel[0]);
+                [gray, gray, gray, alpha]
+            }
+            ColorType::GrayscaleAlpha => {
+                let gray = self.scale(pixel[0]);
+                [gray, gray, gray, self.scale(pixel[1])]
+            }
+            ColorType::Rgb => [
+                self.scale(pixel[0]),
+                self.scale(pixel[1]),
+                self.scale(pixel[2]),
+                alpha,
+            ],
+            ColorType::Rgba => [
+                self.scale(pixel[0]),
+                self.scale(pixel[1]),
+                self.scale(pixel[2]),
+                self.scale(pixel[3]),
+            ],
+            ColorType::Indexed => *self
+                .palette
+                .get(pixel[0] as usize)
+                .ok_or(PixelError::InvalidPaletteIndex(pixel[0] as u8))?,
+        })
+    }
+}
+
+/// Decodes the image data of the PNG file
+pub fn decode(png: &Png) -> Result<Image, PixelError> {
+    let header = ImageHeader::try_from(
+        png.chunk_by_type(ImageHeader::CHUNK_TYPE)
+            .ok_or(PixelError::MissingImageHeader)?
+            .data(),
+    )?;
+    if !matches!(header.bit_depth, 1 | 2 | 4 | 8 | 16)
+        || header.compression_method != 0
+        || header.filter_method != 0
+    {
+        return Err(PixelError::UnsupportedFormat);
+    }
+
+    let (width, height) = (header.width as usize, header.height as usize);
+    let pixel_count = width
+        .checked_mul(height)
+        .filter(|&count| count <= MAX_PIXELS)
+        .ok_or(PixelError::ImageTooLarge(header.width, header.height))?;
+    let channels = header.color_type.channels();
+    let bits_per_pixel = channels * header.bit_depth as usize;
+    let bpp = bits_per_pixel.div_ceil(8);
+    let converter = Converter::new(png, header);
+
+    let passes: Vec<(usize, usize, usize, usize)> = match header.interlace_method {
+        0 => vec![(0, 0, 1, 1)],
+        1 => ADAM7_PASSES.to_vec(),
+        _ => return Err(PixelError::UnsupportedFormat),
+    };
+    // Width, height and scanline length of every pass, empty passes have no scanlines at all
+    let pass_sizes: Vec<(usize, usize, usize)> = passes
+        .iter()
+        .map(|&(x_start, y_start, x_step, y_step)| {
+            let pass_width = width.saturating_sub(x_start).div_ceil(x_step);
+            let pass_height = height.saturating_sub(y_start).div_ceil(y_step);
+            match pass_width == 0 || pass_height == 0 {
+                true => (0, 0, 0),
+                false => (
+                    pass_width,
+                    pass_height,
+                    (pass_width * bits_per_pixel).div_ceil(8),
+                ),
+            }
+        })
+        .collect();
+    let expected_len: usize = pass_sizes
+        .iter()
+        .map(|&(_, pass_height, line_len)| pass_height * (1 + line_len))
+        .sum();
+
+    let compressed: Vec<u8> = png
+        .chunks()
+        .iter()
+        .filter(|c| c.chunk_type().bytes() == IMAGE_DATA)
+        .flat_map(|c| c.data().iter().copied())
+        .collect();
+    let data = match text::inflate_limited(&compressed, expected_len) {
+        Err(TextError::InflateLimit(_)) => return Err(PixelError::ExcessData),
+        result => result?,
+    };
+    if data.len() < expected_len {
+        return Err(PixelError::TruncatedData);
+    }
+
+    let mut pixels = vec![[0, 0, 0, OPAQUE]; pixel_count];
+    let mut offset = 0;
+    for ((x_start, y_start, x_step, y_step), (pass_width, pass_height, line_len)) in
+        passes.into_iter().zip(pass_sizes)
+    {
+        if pass_width == 0 {
+            continue;
+        }
+
+        let mut previous = vec![0; line_len];
+        for row in 0..pass_height {
+            let filter = *data.get(offset).ok_or(PixelError::TruncatedData)?;
+            let mut line = data
+                .get(offset + 1..offset + 1 + line_len)
+                .ok_or(PixelError::TruncatedData)?
+                .to_vec();
+            offset += 1 + line_len;
+            unfilter(filter, &mut line, &previous, bpp)?;
+
+            let line_samples = samples(&line, header.bit_depth, pass_width * channels);
+            for (column, pixel) in line_samples.chunks_exact(channels).enumerate() {
+                let (x, y) = (x_start + column * x_step, y_start + row * y_step);
+                pixels[y * width + x] = converter.convert(pixel)?;
+            }
+            previous = line;
+        }
+    }
+
+    Ok(Image {
+        width,
+        height,
+        pixels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::Chunk;
+    use crate::chunk_type::ChunkType;
+    use std::str::FromStr;
+
+    fn chunk(chunk_type: &str, data: Vec<u8>) -> Chunk {
+        Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data)
+    }
+
+    fn testing_png(header: [u8; 13], extra: Vec<Chunk>, raw: &[u8]) -> Png {
+        let mut chunks = vec![chunk("IHDR", header.to_vec())];
+        chunks.extend(extra);
+        chunks.push(chunk("IDAT", text::deflate(raw).unwrap()));
+        chunks.push(chunk("IEND", vec![]));
+        Png::from_chunks(chunks)
+    }
+
+    #[test]
+    fn test_decode_filtered_rgb() {
+        // 2x2 RGB, first row with the sub filter, second row with the up filter
+        let raw = [1, 10, 20, 30, 5, 5, 5, 2, 1, 1, 1, 0, 0, 0];
+        let png = testing_png([0, 0, 0, 2, 0, 0, 0, 2, 8, 2, 0, 0, 0], vec![], &raw);
+        let image = decode(&png).unwrap();
+        assert_eq!(image.pixel(0, 0), [10 * 257, 20 * 257, 30 * 257, OPAQUE]);
+        assert_eq!(image.pixel(1, 0), [15 * 257, 25 * 257, 35 * 257, OPAQUE]);
+        assert_eq!(image.pixel(0, 1), [11 * 257, 21 * 257, 31 * 257, OPAQUE]);
+        assert_eq!(image.pixel(1, 1), [15 * 257, 25 * 257, 35 * 257, OPAQUE]);
+    }
+
+    #[test]
+    fn test_decode_palette_with_transparency() {
+        // 3x1 image with 2 bit palette indices 0, 1 and 2
+        let raw = [0, 0b0001_1000];
+        let png = testing_png(
+            [0, 0, 0, 3, 0, 0, 0, 1, 2, 3, 0, 0, 0],
+            vec![
+                chunk("PLTE", vec![255, 0, 0, 0, 255, 0, 0, 0, 255]),
+                chunk("tRNS", vec![0]),
+            ],
+            &raw,
+        );
+        let image = decode(&png).unwrap();
+        assert_eq!(image.pixel(0, 0), [OPAQUE, 0, 0, 0]);
+        assert_eq!(image.pixel(1, 0), [0, OPAQUE, 0, OPAQUE]);
+        assert_eq!(image.pixel(2, 0), [0, 0, OPAQUE, OPAQUE]);
+    }
+
+    #[test]
+    fn test_decode_interlaced_matches_non_interlaced() {
+        // 3x3 grayscale image with values 0 to 8, stored with and without Adam7 interlacing
+        let plain: Vec<u8> = (0..3)
+            .flat_map(|y| [0, 3 * y, 3 * y + 1, 3 * y + 2])
+            .collect();
+        let interlaced = [0, 0, 0, 2, 0, 6, 8, 0, 1, 0, 7, 0, 3, 4, 5];
+        let png = testing_png([0, 0, 0, 3, 0, 0, 0, 3, 8, 0, 0, 0, 0], vec![], &plain);
+        let interlaced_png =
+            testing_png([0, 0, 0, 3, 0, 0, 0, 3, 8, 0, 0, 0, 1], vec![], &interlaced);
+        assert_eq!(decode(&png).unwrap(), decode(&interlaced_png).unwrap());
+    }
+
+    #[test]
+    fn test_decode_truncated_data() {
+        let png = testing_png([0, 0, 0, 2, 0, 0, 0, 2, 8, 0, 0, 0, 0], vec![], &[0, 1, 2]);
+        assert!(matches!(decode(&png), Err(PixelError::TruncatedData)));
+
+        let png = testing_png([0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 0, 0], vec![], &[0, 1, 2]);
+        assert!(matches!(decode(&png), Err(PixelError::ExcessData)));
+    }
+
+    #[test]
+    fn test_decode_too_large() {
+        // 2000000x2000000 grayscale image, rejected before the image data is inflated
+        let png = testing_png(
+            [0, 0x1e, 0x84, 0x80, 0, 0x1e, 0x84, 0x80, 8, 0, 0, 0, 0],
+            vec![],
+            &[0, 1],
+        );
+        assert!(matches!(
+            decode(&png),
+            Err(PixelError::ImageTooLarge(2000000, 2000000))
+        ));
+    }
+}
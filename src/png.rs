@@ -4,6 +4,10 @@
  * Defines the PNG file representation as defined in [PNG specification](http://www.libpng.org/pub/png/spec/1.2/PNG-Structure.html).
  * Utilizes tha chunk type and chunk crates.
  *
+ * All of the data after the `IEND` chunk, e.g. an archive appended to make a polyglot file, is kept as the trailer of the
+ * PNG file and written back byte for byte, even if it starts with a well formed chunk. Chunks appended after the `IEND`
+ * chunk by earlier versions of encode are only read as chunks on request.
+ *
  */
 
 use crate::chunk::{Chunk, ChunkError};
@@ -20,6 +24,8 @@ pub type Result<T> = std::result::Result<T, PngError>;
 pub enum PngError {
     NotFoundChunk,
     InvalidHeader,
+    InvalidIndex,
+    CriticalChunk(String),
     Chunk(ChunkError),
 }
 
@@ -44,6 +50,12 @@ impl fmt::Display for PngError {
                 "Invalid header for PNG. Check PNG. Check PNG Specification for more details."
             ),
             PngError::NotFoundChunk => write!(f, "Cannot find chunk with specified ChunkType."),
+            PngError::InvalidIndex => write!(f, "Chunk index is out of bounds."),
+            PngError::CriticalChunk(ref chunk_type) => write!(
+                f,
+                "Cannot insert critical chunk {} by its position, it must be handled explicitly.",
+                chunk_type
+            ),
         }
     }
 }
@@ -54,38 +66,223 @@ impl error::Error for PngError {
             PngError::Chunk(ref err) => Some(err),
             PngError::InvalidHeader => None,
             PngError::NotFoundChunk => None,
+            PngError::InvalidIndex => None,
+            PngError::CriticalChunk(_) => None,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Png {
     header: &'static [u8; 8],
     chunk_list: Vec<Chunk>,
+    trailer: Vec<u8>,
 }
 
 impl Png {
     pub const STANDARD_HEADER: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+    /// Ancillary chunk types that must precede the PLTE chunk
+    const BEFORE_PALETTE: [&'static [u8; 4]; 5] = [b"cHRM", b"gAMA", b"iCCP", b"sBIT", b"sRGB"];
+    const IMAGE_TRAILER: &'static [u8; 4] = b"IEND";
+    /// Public ancillary chunk types that may appear multiple times
+    const MULTIPLE_CHUNK_TYPES: [&'static [u8; 4]; 4] = [b"sPLT", b"tEXt", b"zTXt", b"iTXt"];
 
     pub fn from_chunks(chunks: Vec<Chunk>) -> Png {
         Png {
             header: &Png::STANDARD_HEADER,
             chunk_list: chunks,
+            trailer: vec![],
         }
     }
 
+    /// Returns the data after the last chunk
+    pub fn trailer(&self) -> &[u8] {
+        self.trailer.as_slice()
+    }
+
+    /// Replaces the data after the last chunk and returns the previous data
+    pub fn set_trailer(&mut self, trailer: Vec<u8>) -> Vec<u8> {
+        mem::replace(&mut self.trailer, trailer)
+    }
+
     pub fn append_chunk(&mut self, chunk: Chunk) {
         self.chunk_list.push(chunk)
     }
 
     pub fn remove_chunk(&mut self, chunk_type: &str) -> Result<Chunk> {
-        let chunk_type = ChunkType::from_str(chunk_type)?;
+        let index = self
+            .chunk_position(chunk_type)?
+            .ok_or(PngError::NotFoundChunk)?;
+        Ok(self.chunk_list.remove(index))
+    }
+
+    pub fn remove_chunk_at(&mut self, index: usize) -> Result<Chunk> {
+        if index >= self.chunk_list.len() {
+            return Err(PngError::InvalidIndex);
+        }
+        Ok(self.chunk_list.remove(index))
+    }
+
+    /// Removes all of the chunks that match the predicate and returns them
+    pub fn remove_chunks<F>(&mut self, mut predicate: F) -> Vec<Chunk>
+    where
+        F: FnMut(&Chunk) -> bool,
+    {
+        let (removed, kept) = mem::take(&mut self.chunk_list)
+            .into_iter()
+            .partition(|c| predicate(c));
+        self.chunk_list = kept;
+        removed
+    }
+
+    /// Replaces the first chunk of the same type as the provided chunk, keeping its position
+    pub fn replace_chunk(&mut self, chunk: Chunk) -> Result<Chunk> {
         let index = self
             .chunk_list
             .iter()
-            .position(|x| *x.chunk_type() == chunk_type)
+            .position(|x| x.chunk_type() == chunk.chunk_type())
             .ok_or(PngError::NotFoundChunk)?;
-        Ok(self.chunk_list.remove(index))
+        Ok(mem::replace(&mut self.chunk_list[index], chunk))
+    }
+
+    pub fn replace_chunk_at(&mut self, index: usize, chunk: Chunk) -> Result<Chunk> {
+        let old = self
+            .chunk_list
+            .get_mut(index)
+            .ok_or(PngError::InvalidIndex)?;
+        Ok(mem::replace(old, chunk))
+    }
+
+    /// Changes the type of the first chunk of the specified type, keeping its data and position
+    pub fn rename_chunk(&mut self, chunk_type: &str, new_chunk_type: ChunkType) -> Result<()> {
+        let index = self
+            .chunk_position(chunk_type)?
+            .ok_or(PngError::NotFoundChunk)?;
+        let data = self.chunk_list[index].data().to_vec();
+        self.chunk_list[index] = Chunk::new(new_chunk_type, data);
+        Ok(())
+    }
+
+    pub fn insert_chunk_at(&mut self, index: usize, chunk: Chunk) -> Result<()> {
+        if index > self.chunk_list.len() {
+            return Err(PngError::InvalidIndex);
+        }
+        self.chunk_list.insert(index, chunk);
+        Ok(())
+    }
+
+    /**
+     * Inserts an ancillary chunk before the chunks it must precede according to the PNG specification.
+     *
+     * Critical chunks are rejected, since their position and number are fixed by the image itself.
+     */
+    pub fn insert_chunk_ordered(&mut self, chunk: Chunk) -> Result<()> {
+        if chunk.chunk_type().is_critical() {
+            return Err(PngError::CriticalChunk(chunk.chunk_type().to_string()));
+        }
+        let anchors: &[&[u8; 4]] = if Png::BEFORE_PALETTE.contains(&&chunk.chunk_type().bytes()) {
+            &[b"PLTE", b"IDAT", b"IEND"]
+        } else {
+            &[b"IDAT", b"IEND"]
+        };
+
+        let index = self
+            .chunk_list
+            .iter()
+            .position(|x| anchors.contains(&&x.chunk_type().bytes()))
+            .unwrap_or(self.chunk_list.len());
+        self.insert_chunk_at(index, chunk)
+    }
+
+    /// Returns the chunks of the specified type, or all chunks, together with their index
+    pub fn chunks_of_type(&self, chunk_type: Option<&ChunkType>) -> Vec<(usize, &Chunk)> {
+        self.chunk_list
+            .iter()
+            .enumerate()
+            .filter(|(_, chunk)| chunk_type.is_none_or(|t| t == chunk.chunk_type()))
+            .collect()
+    }
+
+    /// Parses a complete chunk record, with length, type, data and CRC, and inserts it by its type
+    pub fn import_chunk_record(&mut self, record: &[u8]) -> Result<()> {
+        let chunk = Chunk::try_from(record)?;
+        self.insert_chunk_ordered(chunk)
+    }
+
+    /**
+     * Copies the ancillary chunks of the source PNG file, each right before the chunk that follows it in the source,
+     * returning a line for every copied or skipped chunk, in the order of the source.
+     *
+     * Chunks already present are skipped, and unsafe to copy chunks are only copied if the critical chunks of both files
+     * are identical or if the copy is forced. Public chunk types that may only appear once replace the existing chunk.
+     */
+    pub fn transplant(&mut self, source: &Png, force: bool) -> Result<Vec<String>> {
+        let critical_unchanged = source
+            .chunks()
+            .iter()
+            .filter(|c| c.chunk_type().is_critical())
+            .eq(self
+                .chunk_list
+                .iter()
+                .filter(|c| c.chunk_type().is_critical()));
+
+        // NOTE: source is walked backwards, so every copied chunk is inserted right before the chunk that follows it in the source
+        let mut anchor = self.chunk_list.len();
+        let mut report = vec![];
+        for chunk in source.chunks().iter().rev() {
+            let chunk_type = chunk.chunk_type();
+            if chunk_type.is_critical() {
+                if let Some(index) = self.chunk_position(&chunk_type.to_string())? {
+                    anchor = index;
+                }
+                continue;
+            }
+
+            if let Some(index) = self.chunk_list.iter().position(|c| c == chunk) {
+                anchor = anchor.min(index);
+                report.push(format!("Skipped {}: already present", chunk_type));
+                continue;
+            }
+
+            if !chunk_type.is_safe_to_copy() && !critical_unchanged && !force {
+                report.push(format!("Skipped {}: unsafe to copy", chunk_type));
+                continue;
+            }
+
+            if chunk_type.is_public() && !Png::MULTIPLE_CHUNK_TYPES.contains(&&chunk_type.bytes()) {
+                if let Some(index) = self.chunk_position(&chunk_type.to_string())? {
+                    self.remove_chunk(&chunk_type.to_string())?;
+                    if index < anchor {
+                        anchor -= 1;
+                    }
+                }
+            }
+
+            self.insert_chunk_at(anchor, chunk.clone())?;
+            report.push(format!("Copied {}", chunk_type));
+        }
+
+        report.reverse();
+        Ok(report)
+    }
+
+    /// Moves the chunk at index `from` so that it ends up at index `to`
+    pub fn move_chunk(&mut self, from: usize, to: usize) -> Result<()> {
+        if from >= self.chunk_list.len() || to >= self.chunk_list.len() {
+            return Err(PngError::InvalidIndex);
+        }
+        let chunk = self.chunk_list.remove(from);
+        self.chunk_list.insert(to, chunk);
+        Ok(())
+    }
+
+    /// Returns the index of the first chunk of the specified type
+    pub fn chunk_position(&self, chunk_type: &str) -> Result<Option<usize>> {
+        let chunk_type = ChunkType::from_str(chunk_type)?;
+        Ok(self
+            .chunk_list
+            .iter()
+            .position(|x| *x.chunk_type() == chunk_type))
     }
 
     #[allow(dead_code)]
@@ -93,6 +290,23 @@ impl Png {
         self.header
     }
 
+    /// Returns the byte offset of each chunk from the start of the PNG file
+    pub fn chunk_offsets(&self) -> Vec<usize> {
+        self.chunk_list
+            .iter()
+            .scan(self.header.len(), |offset, chunk| {
+                let chunk_offset = *offset;
+                *offset += Png::chunk_size(chunk);
+                Some(chunk_offset)
+            })
+            .collect()
+    }
+
+    /// Returns the byte offset of the trailer from the start of the PNG file
+    pub fn trailer_offset(&self) -> usize {
+        self.header.len() + self.chunk_list.iter().map(Png::chunk_size).sum::<usize>()
+    }
+
     pub fn chunks(&self) -> &[Chunk] {
         self.chunk_list.as_slice()
     }
@@ -110,13 +324,21 @@ impl Png {
         self.chunk_list
             .iter()
             .for_each(|x| bytes.extend(x.as_bytes()));
+        bytes.extend(&self.trailer);
         bytes
     }
 }
 
-impl TryFrom<&[u8]> for Png {
-    type Error = PngError;
-    fn try_from(value: &[u8]) -> Result<Self> {
+impl Png {
+    /// Parses a PNG while keeping the chunks whose CRC doesn't match the chunk data
+    pub fn try_from_unverified(value: &[u8]) -> Result<Png> {
+        Png::parse(value, Chunk::try_from_unverified)
+    }
+
+    fn parse(
+        value: &[u8],
+        parse_chunk: fn(&[u8]) -> std::result::Result<Chunk, ChunkError>,
+    ) -> Result<Png> {
         let mut value_iter = value.iter().copied();
 
         let header: Vec<_> = value_iter
@@ -131,16 +353,56 @@ impl TryFrom<&[u8]> for Png {
         let mut body: Vec<_> = value_iter.borrow_mut().collect();
         let mut chunks = vec![];
         while !body.is_empty() {
-            let chunk = Chunk::try_from(body.as_slice())?;
-            let chunk_size = mem::size_of_val(&chunk.length())
-                + mem::size_of_val(chunk.chunk_type())
-                + mem::size_of_val(&chunk.crc())
-                + chunk.length() as usize;
-            body.drain(..chunk_size);
+            let chunk = parse_chunk(body.as_slice())?;
+            body.drain(..Png::chunk_size(&chunk));
+            let is_last = chunk.chunk_type().bytes() == *Png::IMAGE_TRAILER;
             chunks.push(chunk);
+            if is_last {
+                break;
+            }
+        }
+
+        Ok(Png {
+            header: &Png::STANDARD_HEADER,
+            chunk_list: chunks,
+            trailer: body,
+        })
+    }
+
+    /**
+     * Moves the well formed chunks with a valid CRC at the start of the trailer to the end of the chunk list and
+     * returns their number, e.g. to read the messages appended after the `IEND` chunk by earlier versions of encode.
+     */
+    pub fn read_trailing_chunks(&mut self) -> usize {
+        let mut count = 0;
+        while let Some(chunk) = Png::trailing_chunk(&self.trailer) {
+            self.trailer.drain(..Png::chunk_size(&chunk));
+            self.chunk_list.push(chunk);
+            count += 1;
         }
+        count
+    }
 
-        Ok(Png::from_chunks(chunks))
+    /// Parses a well formed chunk with a valid CRC at the start of the data after the `IEND` chunk
+    fn trailing_chunk(body: &[u8]) -> Option<Chunk> {
+        Chunk::try_from(body)
+            .ok()
+            .filter(|chunk| Png::chunk_size(chunk) <= body.len())
+    }
+
+    /// Returns the number of bytes the chunk occupies in the PNG file
+    fn chunk_size(chunk: &Chunk) -> usize {
+        mem::size_of_val(&chunk.length())
+            + mem::size_of_val(chunk.chunk_type())
+            + mem::size_of_val(&chunk.crc())
+            + chunk.length() as usize
+    }
+}
+
+impl TryFrom<&[u8]> for Png {
+    type Error = PngError;
+    fn try_from(value: &[u8]) -> Result<Self> {
+        Png::parse(value, |bytes| Chunk::try_from(bytes))
     }
 }
 
@@ -148,8 +410,8 @@ impl fmt::Display for Png {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "[header: {:?}, body: {:?}]",
-            self.header, self.chunk_list
+            "[header: {:?}, body: {:?}, trailer: {:?}]",
+            self.header, self.chunk_list, self.trailer
         )
     }
 }
@@ -163,13 +425,11 @@ mod tests {
     use std::str::FromStr;
 
     fn testing_chunks() -> Vec<Chunk> {
-        let mut chunks = Vec::new();
-
-        chunks.push(chunk_from_strings("FrSt", "I am the first chunk").unwrap());
-        chunks.push(chunk_from_strings("miDl", "I am another chunk").unwrap());
-        chunks.push(chunk_from_strings("LASt", "I am the last chunk").unwrap());
-
-        chunks
+        vec![
+            chunk_from_strings("FrSt", "I am the first chunk").unwrap(),
+            chunk_from_strings("miDl", "I am another chunk").unwrap(),
+            chunk_from_strings("LASt", "I am the last chunk").unwrap(),
+        ]
     }
 
     fn testing_png() -> Png {
@@ -252,6 +512,19 @@ mod tests {
         assert!(png.is_err());
     }
 
+    #[test]
+    fn test_unverified_chunk() {
+        let mut bytes = PNG_FILE.to_vec();
+        // corrupt the last byte of the sRGB chunk data
+        bytes[41] ^= 0xff;
+
+        assert!(Png::try_from(bytes.as_ref()).is_err());
+
+        let png = Png::try_from_unverified(bytes.as_ref()).unwrap();
+        assert!(!png.chunk_by_type("sRGB").unwrap().has_valid_crc());
+        assert_eq!(png.as_bytes(), bytes);
+    }
+
     #[test]
     fn test_list_chunks() {
         let png = testing_png();
@@ -285,6 +558,248 @@ mod tests {
         assert!(chunk.is_none());
     }
 
+    #[test]
+    fn test_remove_chunk_at() {
+        let mut png = testing_png();
+        let removed = png.remove_chunk_at(1).unwrap();
+        assert_eq!(&removed.chunk_type().to_string(), "miDl");
+        assert_eq!(png.chunks().len(), 2);
+        assert!(png.remove_chunk_at(2).is_err());
+    }
+
+    #[test]
+    fn test_remove_chunks() {
+        let mut png = testing_png();
+        let removed = png.remove_chunks(|c| c.chunk_type().is_critical());
+        assert_eq!(removed.len(), 2);
+        assert_eq!(png.chunks().len(), 1);
+        assert_eq!(&png.chunks()[0].chunk_type().to_string(), "miDl");
+    }
+
+    #[test]
+    fn test_replace_chunk() {
+        let mut png = testing_png();
+        let old = png
+            .replace_chunk(chunk_from_strings("miDl", "I am replaced").unwrap())
+            .unwrap();
+        assert_eq!(&old.data_as_string().unwrap(), "I am another chunk");
+        assert_eq!(png.chunks().len(), 3);
+        assert_eq!(png.chunk_position("miDl").unwrap(), Some(1));
+        let chunk = png.chunk_by_type("miDl").unwrap();
+        assert_eq!(&chunk.data_as_string().unwrap(), "I am replaced");
+    }
+
+    #[test]
+    fn test_replace_missing_chunk() {
+        let mut png = testing_png();
+        let result = png.replace_chunk(chunk_from_strings("TeSt", "Message").unwrap());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_replace_chunk_at() {
+        let mut png = testing_png();
+        let old = png
+            .replace_chunk_at(2, chunk_from_strings("TeSt", "Message").unwrap())
+            .unwrap();
+        assert_eq!(&old.chunk_type().to_string(), "LASt");
+        assert_eq!(png.chunk_position("TeSt").unwrap(), Some(2));
+        assert!(png
+            .replace_chunk_at(3, chunk_from_strings("TeSt", "Message").unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_rename_chunk() {
+        let mut png = testing_png();
+        png.rename_chunk("miDl", ChunkType::from_str("ruSt").unwrap())
+            .unwrap();
+        assert!(png.chunk_by_type("miDl").is_none());
+        assert_eq!(png.chunk_position("ruSt").unwrap(), Some(1));
+        let chunk = png.chunk_by_type("ruSt").unwrap();
+        assert_eq!(&chunk.data_as_string().unwrap(), "I am another chunk");
+        assert!(chunk.has_valid_crc());
+    }
+
+    #[test]
+    fn test_insert_chunk_at() {
+        let mut png = testing_png();
+        png.insert_chunk_at(1, chunk_from_strings("TeSt", "Message").unwrap())
+            .unwrap();
+        assert_eq!(png.chunk_position("TeSt").unwrap(), Some(1));
+        assert_eq!(png.chunk_position("miDl").unwrap(), Some(2));
+        assert!(png
+            .insert_chunk_at(5, chunk_from_strings("TeSt", "Message").unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_insert_chunk_ordered() {
+        let mut png = Png::from_chunks(vec![
+            chunk_from_strings("IHDR", "header").unwrap(),
+            chunk_from_strings("PLTE", "palette").unwrap(),
+            chunk_from_strings("IDAT", "data").unwrap(),
+            chunk_from_strings("IEND", "").unwrap(),
+        ]);
+        png.insert_chunk_ordered(chunk_from_strings("gAMA", "gamma").unwrap())
+            .unwrap();
+        png.insert_chunk_ordered(chunk_from_strings("ruSt", "private").unwrap())
+            .unwrap();
+        let types: Vec<String> = png
+            .chunks()
+            .iter()
+            .map(|c| c.chunk_type().to_string())
+            .collect();
+        assert_eq!(types, vec!["IHDR", "gAMA", "PLTE", "ruSt", "IDAT", "IEND"]);
+
+        assert!(matches!(
+            png.insert_chunk_ordered(chunk_from_strings("IEND", "").unwrap()),
+            Err(PngError::CriticalChunk(_))
+        ));
+        assert_eq!(png.chunks().len(), 6);
+    }
+
+    #[test]
+    fn test_extract_import_chunk_records() {
+        let mut png = Png::from_chunks(vec![
+            chunk_from_strings("IHDR", "header").unwrap(),
+            chunk_from_strings("tEXt", "Comment\0extracted").unwrap(),
+            chunk_from_strings("IDAT", "data").unwrap(),
+            chunk_from_strings("IEND", "").unwrap(),
+        ]);
+        let original = png.as_bytes();
+        let chunk_type = ChunkType::from_str("tEXt").unwrap();
+        let records: Vec<Vec<u8>> = png
+            .chunks_of_type(Some(&chunk_type))
+            .into_iter()
+            .map(|(index, chunk)| {
+                assert_eq!(index, 1);
+                chunk.as_bytes()
+            })
+            .collect();
+        assert_eq!(records.len(), 1);
+        assert_eq!(png.chunks_of_type(None).len(), 4);
+
+        png.remove_chunk("tEXt").unwrap();
+        png.import_chunk_record(&records[0]).unwrap();
+        assert_eq!(png.as_bytes(), original);
+
+        let mut corrupted = records[0].clone();
+        *corrupted.last_mut().unwrap() ^= 0xff;
+        assert!(png.import_chunk_record(&corrupted).is_err());
+        let header = png.chunks()[0].as_bytes();
+        assert!(matches!(
+            png.import_chunk_record(&header),
+            Err(PngError::CriticalChunk(_))
+        ));
+        assert_eq!(png.as_bytes(), original);
+    }
+
+    fn chunk_types(png: &Png) -> Vec<String> {
+        png.chunks()
+            .iter()
+            .map(|c| c.chunk_type().to_string())
+            .collect()
+    }
+
+    fn transplant_source() -> Png {
+        Png::from_chunks(vec![
+            chunk_from_strings("IHDR", "header").unwrap(),
+            chunk_from_strings("gAMA", "source gamma").unwrap(),
+            chunk_from_strings("tEXt", "Comment\0copied").unwrap(),
+            chunk_from_strings("IDAT", "source data").unwrap(),
+            chunk_from_strings("prVt", "unsafe").unwrap(),
+            chunk_from_strings("IEND", "").unwrap(),
+        ])
+    }
+
+    #[test]
+    fn test_transplant_skips_unsafe_to_copy() {
+        let mut png = Png::from_chunks(vec![
+            chunk_from_strings("IHDR", "header").unwrap(),
+            chunk_from_strings("gAMA", "target gamma").unwrap(),
+            chunk_from_strings("IDAT", "target data").unwrap(),
+            chunk_from_strings("IEND", "").unwrap(),
+        ]);
+        let report = png.transplant(&transplant_source(), false).unwrap();
+        assert_eq!(
+            report,
+            vec!["Skipped gAMA: unsafe to copy", "Copied tEXt", "Copied prVt"]
+        );
+        assert_eq!(
+            chunk_types(&png),
+            vec!["IHDR", "gAMA", "tEXt", "IDAT", "prVt", "IEND"]
+        );
+        assert_eq!(png.chunk_by_type("gAMA").unwrap().data(), b"target gamma");
+    }
+
+    #[test]
+    fn test_transplant_unsafe_to_copy_with_critical_unchanged() {
+        let mut png = Png::from_chunks(vec![
+            chunk_from_strings("IHDR", "header").unwrap(),
+            chunk_from_strings("tEXt", "Comment\0copied").unwrap(),
+            chunk_from_strings("gAMA", "target gamma").unwrap(),
+            chunk_from_strings("IDAT", "source data").unwrap(),
+            chunk_from_strings("IEND", "").unwrap(),
+        ]);
+        let report = png.transplant(&transplant_source(), false).unwrap();
+        assert_eq!(
+            report,
+            vec![
+                "Copied gAMA",
+                "Skipped tEXt: already present",
+                "Copied prVt"
+            ]
+        );
+        assert_eq!(
+            chunk_types(&png),
+            vec!["IHDR", "gAMA", "tEXt", "IDAT", "prVt", "IEND"]
+        );
+        assert_eq!(png.chunk_by_type("gAMA").unwrap().data(), b"source gamma");
+    }
+
+    #[test]
+    fn test_transplant_forced() {
+        let mut png = Png::from_chunks(vec![
+            chunk_from_strings("IHDR", "header").unwrap(),
+            chunk_from_strings("IDAT", "target data").unwrap(),
+            chunk_from_strings("IEND", "").unwrap(),
+        ]);
+        let report = png.transplant(&transplant_source(), true).unwrap();
+        assert_eq!(report, vec!["Copied gAMA", "Copied tEXt", "Copied prVt"]);
+        assert_eq!(
+            chunk_types(&png),
+            vec!["IHDR", "gAMA", "tEXt", "IDAT", "prVt", "IEND"]
+        );
+        assert_eq!(png.chunk_by_type("IDAT").unwrap().data(), b"target data");
+    }
+
+    #[test]
+    fn test_move_chunk() {
+        let mut png = testing_png();
+        png.move_chunk(0, 2).unwrap();
+        let types: Vec<String> = png
+            .chunks()
+            .iter()
+            .map(|c| c.chunk_type().to_string())
+            .collect();
+        assert_eq!(types, vec!["miDl", "LASt", "FrSt"]);
+        assert!(png.move_chunk(0, 3).is_err());
+    }
+
+    #[test]
+    fn test_chunk_offsets() {
+        let png = testing_png();
+        let offsets = png.chunk_offsets();
+        assert_eq!(offsets[0], Png::STANDARD_HEADER.len());
+        assert_eq!(
+            offsets[2],
+            Png::STANDARD_HEADER.len()
+                + png.chunks()[0].as_bytes().len()
+                + png.chunks()[1].as_bytes().len()
+        );
+    }
+
     #[test]
     fn test_png_from_image_file() {
         let png = Png::try_from(&PNG_FILE[..]);
@@ -299,6 +814,69 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_trailer_after_image_trailer() {
+        let mut bytes = PNG_FILE.to_vec();
+        bytes.extend(b"PK\x03\x04 appended archive");
+        let png = Png::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(png.trailer(), b"PK\x03\x04 appended archive");
+        assert_eq!(png.trailer_offset(), PNG_FILE.len());
+        assert_eq!(
+            png.chunks().last().unwrap().chunk_type().to_string(),
+            "IEND"
+        );
+        assert_eq!(png.as_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_strip_trailer() {
+        let mut bytes = PNG_FILE.to_vec();
+        bytes.extend(b"PK\x03\x04 appended archive");
+        let mut png = Png::try_from(bytes.as_slice()).unwrap();
+
+        let removed = png.set_trailer(vec![]);
+        assert_eq!(removed, b"PK\x03\x04 appended archive");
+        assert!(png.trailer().is_empty());
+        assert_eq!(png.as_bytes(), PNG_FILE.to_vec());
+        assert!(png.set_trailer(vec![]).is_empty());
+    }
+
+    #[test]
+    fn test_set_trailer() {
+        let mut bytes = PNG_FILE.to_vec();
+        bytes.extend(b"old trailer");
+        let mut png = Png::try_from(bytes.as_slice()).unwrap();
+
+        assert_eq!(png.set_trailer(b"new".to_vec()), b"old trailer");
+        assert_eq!(png.trailer_offset(), PNG_FILE.len());
+        let parsed = Png::try_from(png.as_bytes().as_slice()).unwrap();
+        assert_eq!(parsed.trailer(), b"new");
+        assert_eq!(parsed.trailer_offset(), PNG_FILE.len());
+        assert_eq!(parsed.chunks(), png.chunks());
+    }
+
+    #[test]
+    fn test_chunks_after_image_trailer() {
+        let mut png = Png::try_from(&PNG_FILE[..]).unwrap();
+        let chunks = png.chunks().len();
+        let appended = chunk_from_strings("ruSt", "secret").unwrap();
+        png.append_chunk(appended.clone());
+        png.set_trailer(b"\0\0\0\x05 trailing".to_vec());
+
+        // Well formed chunks after IEND are kept in the trailer unless they are read on request
+        let mut parsed = Png::try_from(png.as_bytes().as_slice()).unwrap();
+        assert_eq!(parsed.chunks().len(), chunks);
+        assert_eq!(parsed.trailer_offset(), PNG_FILE.len());
+        assert_eq!(parsed.as_bytes(), png.as_bytes());
+
+        assert_eq!(parsed.read_trailing_chunks(), 1);
+        assert_eq!(parsed.chunks().len(), chunks + 1);
+        assert_eq!(parsed.chunks().last(), Some(&appended));
+        assert_eq!(parsed.trailer(), b"\0\0\0\x05 trailing");
+        assert_eq!(parsed.as_bytes(), png.as_bytes());
+        assert_eq!(parsed.read_trailing_chunks(), 0);
+    }
+
     #[test]
     fn test_png_trait_impls() {
         let chunk_bytes: Vec<u8> = testing_chunks()
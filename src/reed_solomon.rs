@@ -0,0 +1,329 @@
+/*!
+ * # Reed-Solomon crate
+ *
+ * Defines a systematic [Reed-Solomon](https://en.wikipedia.org/wiki/Reed%E2%80%93Solomon_error_correction) code over GF(256)
+ * used as forward error correction for the encoded payloads.
+ *
+ * The payload is split into blocks of at most 255 bytes. Every block holds up to `255 - parity` payload bytes followed by
+ * `parity` parity bytes, which allows up to `parity / 2` corrupted bytes per block to be corrected. The last block is a
+ * shortened block, so the block boundaries can be recovered from the payload length and the redundancy level alone.
+ *
+ * Polynomials are represented with the highest degree coefficient first.
+ *
+ */
+
+use crate::gf256;
+use std::error;
+use std::fmt;
+
+const BLOCK_LEN: usize = 255;
+
+#[derive(Debug)]
+pub enum ReedSolomonError {
+    InvalidRedundancy,
+    InvalidLength,
+    TooManyErrors,
+}
+
+impl fmt::Display for ReedSolomonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReedSolomonError::InvalidRedundancy => write!(
+                f,
+                "Invalid redundancy level, number of parity symbols must be between 1 and {}.",
+                BLOCK_LEN - 1
+            ),
+            ReedSolomonError::InvalidLength => write!(
+                f,
+                "Invalid length of the encoded payload for the given redundancy level."
+            ),
+            ReedSolomonError::TooManyErrors => write!(
+                f,
+                "Payload contains more errors than the redundancy level is able to correct."
+            ),
+        }
+    }
+}
+
+impl error::Error for ReedSolomonError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ReedSolomonError::InvalidRedundancy => None,
+            ReedSolomonError::InvalidLength => None,
+            ReedSolomonError::TooManyErrors => None,
+        }
+    }
+}
+
+fn poly_scale(p: &[u8], x: u8) -> Vec<u8> {
+    p.iter().map(|&c| gf256::mul(c, x)).collect()
+}
+
+fn poly_add(p: &[u8], q: &[u8]) -> Vec<u8> {
+    let len = p.len().max(q.len());
+    let mut result = vec![0u8; len];
+    p.iter()
+        .enumerate()
+        .for_each(|(i, &c)| result[i + len - p.len()] = c);
+    q.iter()
+        .enumerate()
+        .for_each(|(i, &c)| result[i + len - q.len()] ^= c);
+    result
+}
+
+fn poly_mul(p: &[u8], q: &[u8]) -> Vec<u8> {
+    let mut result = vec![0u8; p.len() + q.len() - 1];
+    for (j, &qc) in q.iter().enumerate() {
+        for (i, &pc) in p.iter().enumerate() {
+            result[i + j] ^= gf256::mul(pc, qc);
+        }
+    }
+    result
+}
+
+fn poly_eval(p: &[u8], x: u8) -> u8 {
+    p.iter()
+        .fold(0, |acc, &c| gf256::add(gf256::mul(acc, x), c))
+}
+
+/// Returns the remainder of the division of two polynomials, where the divisor is monic
+fn poly_rem(dividend: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = dividend.to_vec();
+    for i in 0..dividend.len().saturating_sub(divisor.len() - 1) {
+        let coefficient = result[i];
+        if coefficient != 0 {
+            for (j, &d) in divisor.iter().enumerate().skip(1) {
+                result[i + j] ^= gf256::mul(d, coefficient);
+            }
+        }
+    }
+    result.split_off(dividend.len().saturating_sub(divisor.len() - 1))
+}
+
+fn generator_poly(parity: usize) -> Vec<u8> {
+    (0..parity).fold(vec![1], |g, i| poly_mul(&g, &[1, gf256::exp(i)]))
+}
+
+fn syndromes(block: &[u8], parity: usize) -> Vec<u8> {
+    (0..parity)
+        .map(|i| poly_eval(block, gf256::exp(i)))
+        .collect()
+}
+
+/// Berlekamp-Massey algorithm, returns the error locator polynomial
+fn error_locator(syndromes: &[u8]) -> Result<Vec<u8>, ReedSolomonError> {
+    let mut locator = vec![1u8];
+    let mut old_locator = vec![1u8];
+
+    for k in 0..syndromes.len() {
+        let mut delta = syndromes[k];
+        for j in (1..locator.len()).filter(|&j| j <= k) {
+            delta ^= gf256::mul(locator[locator.len() - 1 - j], syndromes[k - j]);
+        }
+
+        old_locator.push(0);
+        if delta != 0 {
+            if old_locator.len() > locator.len() {
+                let new_locator = poly_scale(&old_locator, delta);
+                old_locator = poly_scale(&locator, gf256::div(1, delta));
+                locator = new_locator;
+            }
+            locator = poly_add(&locator, &poly_scale(&old_locator, delta));
+        }
+    }
+
+    let leading_zeros = locator.iter().take_while(|&&c| c == 0).count();
+    locator.drain(..leading_zeros);
+
+    if (locator.len() - 1) * 2 > syndromes.len() {
+        return Err(ReedSolomonError::TooManyErrors);
+    }
+
+    Ok(locator)
+}
+
+/// Chien search, returns the positions of the errors counted from the start of the block
+fn error_positions(locator: &[u8], block_len: usize) -> Result<Vec<usize>, ReedSolomonError> {
+    let reversed: Vec<u8> = locator.iter().rev().copied().collect();
+    let positions: Vec<usize> = (0..block_len)
+        .filter(|&i| poly_eval(&reversed, gf256::exp(i)) == 0)
+        .map(|i| block_len - 1 - i)
+        .collect();
+
+    if positions.len() != locator.len() - 1 {
+        return Err(ReedSolomonError::TooManyErrors);
+    }
+
+    Ok(positions)
+}
+
+/// Forney algorithm, corrects the block at the given error positions
+fn correct_errors(block: &mut [u8], syndromes: &[u8], positions: &[usize]) {
+    let coefficient_positions: Vec<usize> = positions.iter().map(|p| block.len() - 1 - p).collect();
+
+    let locator = coefficient_positions.iter().fold(vec![1u8], |acc, &p| {
+        poly_mul(&acc, &poly_add(&[1], &[gf256::exp(p), 0]))
+    });
+
+    // syndromes are reversed and shifted by one degree, as if they were padded with a leading zero
+    let reversed_syndromes: Vec<u8> = syndromes
+        .iter()
+        .rev()
+        .copied()
+        .chain(std::iter::once(0))
+        .collect();
+    let mut modulus = vec![0u8; locator.len() + 1];
+    modulus[0] = 1;
+    let evaluator = poly_rem(&poly_mul(&reversed_syndromes, &locator), &modulus);
+
+    let locations: Vec<u8> = coefficient_positions
+        .iter()
+        .map(|&p| gf256::exp(p))
+        .collect();
+
+    for (i, &x) in locations.iter().enumerate() {
+        let x_inv = gf256::div(1, x);
+        let locator_prime = locations
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .fold(1, |acc, (_, &xj)| {
+                gf256::mul(acc, gf256::add(1, gf256::mul(x_inv, xj)))
+            });
+        let y = gf256::mul(x, poly_eval(&evaluator, x_inv));
+        block[positions[i]] ^= gf256::div(y, locator_prime);
+    }
+}
+
+fn decode_block(block: &mut [u8], parity: usize) -> Result<usize, ReedSolomonError> {
+    let syndromes = syndromes(block, parity);
+    if syndromes.iter().all(|&s| s == 0) {
+        return Ok(0);
+    }
+
+    let locator = error_locator(&syndromes)?;
+    let positions = error_positions(&locator, block.len())?;
+    correct_errors(block, &syndromes, &positions);
+
+    if self::syndromes(block, parity).iter().any(|&s| s != 0) {
+        return Err(ReedSolomonError::TooManyErrors);
+    }
+
+    Ok(positions.len())
+}
+
+/// Encodes a payload, appending `parity` parity symbols to every block
+pub fn encode(payload: &[u8], parity: u8) -> Result<Vec<u8>, ReedSolomonError> {
+    let parity = parity as usize;
+    if parity == 0 || parity >= BLOCK_LEN {
+        return Err(ReedSolomonError::InvalidRedundancy);
+    }
+
+    let generator = generator_poly(parity);
+    let mut encoded = Vec::with_capacity(payload.len() + parity);
+    for data in payload.chunks(BLOCK_LEN - parity) {
+        let mut dividend = data.to_vec();
+        dividend.resize(data.len() + parity, 0);
+        encoded.extend_from_slice(data);
+        encoded.extend(poly_rem(&dividend, &generator));
+    }
+
+    Ok(encoded)
+}
+
+/// Decodes a payload encoded with `parity` parity symbols per block, returning the payload and the number of repaired symbols
+pub fn decode(encoded: &[u8], parity: u8) -> Result<(Vec<u8>, usize), ReedSolomonError> {
+    let parity = parity as usize;
+    if parity == 0 || parity >= BLOCK_LEN {
+        return Err(ReedSolomonError::InvalidRedundancy);
+    }
+
+    let mut payload = Vec::with_capacity(encoded.len());
+    let mut repaired = 0;
+    for block in encoded.chunks(BLOCK_LEN) {
+        if block.len() <= parity {
+            return Err(ReedSolomonError::InvalidLength);
+        }
+        let mut block = block.to_vec();
+        repaired += decode_block(&mut block, parity)?;
+        payload.extend_from_slice(&block[..block.len() - parity]);
+    }
+
+    Ok((payload, repaired))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &[u8] = b"This is where your secret message will be!";
+
+    #[test]
+    fn test_encode_is_systematic() {
+        let encoded = encode(MESSAGE, 8).unwrap();
+        assert_eq!(encoded.len(), MESSAGE.len() + 8);
+        assert_eq!(&encoded[..MESSAGE.len()], MESSAGE);
+    }
+
+    #[test]
+    fn test_decode_without_errors() {
+        let encoded = encode(MESSAGE, 8).unwrap();
+        let (decoded, repaired) = decode(&encoded, 8).unwrap();
+        assert_eq!(decoded, MESSAGE);
+        assert_eq!(repaired, 0);
+    }
+
+    #[test]
+    fn test_decode_corrects_errors() {
+        let mut encoded = encode(MESSAGE, 8).unwrap();
+        encoded[0] ^= 0xff;
+        encoded[10] = 0;
+        encoded[20] ^= 0x01;
+        encoded[MESSAGE.len() + 3] ^= 0x55;
+        let (decoded, repaired) = decode(&encoded, 8).unwrap();
+        assert_eq!(decoded, MESSAGE);
+        assert_eq!(repaired, 4);
+    }
+
+    #[test]
+    fn test_decode_corrects_up_to_half_parity() {
+        let encoded = encode(MESSAGE, 10).unwrap();
+        for offset in 0..encoded.len() {
+            let mut corrupted = encoded.clone();
+            (0..5).for_each(|i| corrupted[(offset + i * 7) % encoded.len()] ^= (i + 1) as u8);
+            let (decoded, repaired) = decode(&corrupted, 10).unwrap();
+            assert_eq!(decoded, MESSAGE);
+            assert_eq!(repaired, 5);
+        }
+    }
+
+    #[test]
+    fn test_decode_too_many_errors() {
+        let mut encoded = encode(MESSAGE, 4).unwrap();
+        encoded[0] ^= 0xff;
+        encoded[1] ^= 0xff;
+        encoded[2] ^= 0xff;
+        assert!(decode(&encoded, 4).is_err());
+    }
+
+    #[test]
+    fn test_multiple_blocks() {
+        let payload: Vec<u8> = (0..1000u32).map(|x| (x * 31 % 256) as u8).collect();
+        let mut encoded = encode(&payload, 16).unwrap();
+        assert_eq!(encoded.len(), payload.len() + 16 * 5);
+        encoded[5] ^= 0x12;
+        encoded[300] ^= 0x34;
+        let last = encoded.len() - 1;
+        encoded[last] ^= 0x56;
+        let (decoded, repaired) = decode(&encoded, 16).unwrap();
+        assert_eq!(decoded, payload);
+        assert_eq!(repaired, 3);
+    }
+
+    #[test]
+    fn test_invalid_redundancy() {
+        assert!(encode(MESSAGE, 0).is_err());
+        assert!(encode(MESSAGE, 255).is_err());
+        assert!(decode(MESSAGE, 0).is_err());
+    }
+}
@@ -0,0 +1,604 @@
+/*!
+ * # Report crate
+ *
+ * Defines the machine readable output of the read operations, written as JSON, JSON Lines or YAML.
+ *
+ * The schema is stable, fields are only added in a backward compatible way and any other change increases
+ * the `schema_version` field.
+ *
+ * ## Print report (schema version 1)
+ *
+ * | Field            | Type    | Description                                              |
+ * |------------------|---------|----------------------------------------------------------|
+ * | `schema_version` | integer | Version of the schema, currently `1`                     |
+ * | `file`           | string  | Path of the PNG file                                     |
+ * | `chunks`         | array   | Chunks of the PNG file, in the order they are stored in  |
+ * | `trailer`        | object  | Data after the last chunk, only present if there is any  |
+ *
+ * Every chunk is an object with the following fields:
+ *
+ * | Field                           | Type    | Description                                               |
+ * |---------------------------------|---------|-----------------------------------------------------------|
+ * | `index`                         | integer | Index of the chunk, starting at 0 for `IHDR`              |
+ * | `offset`                        | integer | Byte offset of the chunk from the start of the file       |
+ * | `type`                          | string  | Chunk type, e.g. `IHDR`                                   |
+ * | `properties.critical`           | boolean | Ancillary bit of the chunk type is not set                |
+ * | `properties.public`             | boolean | Private bit of the chunk type is not set                  |
+ * | `properties.reserved_bit_valid` | boolean | Reserved bit of the chunk type is not set                 |
+ * | `properties.safe_to_copy`       | boolean | Safe-to-copy bit of the chunk type is set                 |
+ * | `length`                        | integer | Number of data bytes                                      |
+ * | `crc`                           | integer | CRC stored in the file                                    |
+ * | `crc_valid`                     | boolean | Stored CRC matches the chunk type and data                |
+ * | `data`                          | string  | Base64 encoded chunk data, only present if requested      |
+ *
+ * The trailer is an object with the `offset` of the data from the start of the file, its `length` and, if requested,
+ * the base64 encoded `data`.
+ *
+ * In the JSON Lines format every chunk is written on its own line, with the `schema_version` and `file`
+ * fields added to the chunk object. The trailer, if there is any, is written on the last line as an object with the
+ * `schema_version`, `file` and `trailer` fields.
+ *
+ * ## Decode report (schema version 1)
+ *
+ * | Field              | Type    | Description                                                         |
+ * |--------------------|---------|---------------------------------------------------------------------|
+ * | `schema_version`   | integer | Version of the schema, currently `1`                                |
+ * | `file`             | string  | Path of the PNG file                                                |
+ * | `type`             | string  | Chunk type under which the message is stored                        |
+ * | `message`          | string  | Decoded message                                                     |
+ * | `repaired_symbols` | integer | Number of symbols repaired by error correction, only present if used |
+ *
+ * ## Diff report (schema version 1)
+ *
+ * | Field            | Type   | Description                                                     |
+ * |------------------|--------|-----------------------------------------------------------------|
+ * | `schema_version` | integer | Version of the schema, currently `1`                          |
+ * | `old_file`       | string | Path of the original PNG file                                   |
+ * | `new_file`       | string | Path of the PNG file compared to the original                   |
+ * | `header`         | array  | Changed image header fields as `field`, `old` and `new` strings |
+ * | `chunks`         | array  | Chunk changes, ordered by their position in the new file        |
+ * | `trailer`        | object | Change of the data after the last chunk, only present if any    |
+ * | `pixels`         | object | Comparison of the decoded pixels                                |
+ *
+ * Every chunk change has a `change` field, either `added`, `removed`, `reordered` or `changed`, the chunk `type`
+ * and the `old_index` and `new_index` of the chunk in the files it is present in. Changed chunks also have the
+ * `old_length` and `new_length` fields, and changed decoded chunks, e.g. textual chunks, a `text` array of unified
+ * diff lines.
+ *
+ * The trailer change has the `old_length` and `new_length` of the trailing data and their `old_sha256` and
+ * `new_sha256` digests as hexadecimal strings, the digest of empty data if there is none.
+ *
+ * The pixel comparison has a `result` field, either `identical`, `different` with the number of different `pixels`
+ * out of the `total`, or `incomparable` with the `reason`.
+ *
+ * ## Combine decode report (schema version 1)
+ *
+ * | Field            | Type    | Description                                         |
+ * |------------------|---------|-----------------------------------------------------|
+ * | `schema_version` | integer | Version of the schema, currently `1`                |
+ * | `files`          | array   | Paths of the PNG files the shares were read from    |
+ * | `type`           | string  | Chunk type under which the shares are stored        |
+ * | `shares`         | integer | Number of shares found in the files                 |
+ * | `message`        | string  | Reconstructed message                               |
+ *
+ * ## EXIF report (schema version 1)
+ *
+ * | Field            | Type    | Description                                                     |
+ * |------------------|---------|-----------------------------------------------------------------|
+ * | `schema_version` | integer | Version of the schema, currently `1`                            |
+ * | `file`           | string  | Path of the PNG file                                            |
+ * | `entries`        | array   | Entries of every image file directory, directories in file order |
+ *
+ * Every entry has the `ifd` that contains it, e.g. `IFD0` or `Exif`, the numeric `tag`, the tag `name` and the
+ * formatted `value`. Entries that point to another directory, e.g. `GPSInfo`, don't have a value.
+ *
+ * In the JSON Lines format every entry is written on its own line, with the `schema_version` and `file` fields added.
+ *
+ * ## XMP report (schema version 1)
+ *
+ * | Field            | Type    | Description                          |
+ * |------------------|---------|--------------------------------------|
+ * | `schema_version` | integer | Version of the schema, currently `1` |
+ * | `file`           | string  | Path of the PNG file                 |
+ * | `packet`         | string  | XMP packet                           |
+ *
+ * ## APNG report (schema version 1)
+ *
+ * | Field            | Type    | Description                                           |
+ * |------------------|---------|-------------------------------------------------------|
+ * | `schema_version` | integer | Version of the schema, currently `1`                  |
+ * | `file`           | string  | Path of the APNG file                                 |
+ * | `num_frames`     | integer | Number of frames declared by the `acTL` chunk         |
+ * | `num_plays`      | integer | Number of times the animation is played, 0 is infinite |
+ * | `frames`         | array   | Frames of the animation                               |
+ *
+ * Every frame has its `index`, the `offset` of its `fcTL` chunk, whether it is the `default_image`, its
+ * `sequence_number`, the `width`, `height`, `x_offset` and `y_offset` of its region, its `delay_ms` and its `dispose`
+ * and `blend` operations.
+ *
+ * In the JSON Lines format every frame is written on its own line, with the `schema_version` and `file` fields added.
+ *
+ */
+
+use crate::apng::Animation;
+use crate::chunk::Chunk;
+use crate::exif::{Exif, Ifd};
+use crate::png::Png;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::Serialize;
+use std::error;
+use std::fmt;
+use std::path::Path;
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum ReportError {
+    Json(serde_json::Error),
+    Yaml(serde_yaml::Error),
+}
+
+impl From<serde_json::Error> for ReportError {
+    fn from(item: serde_json::Error) -> ReportError {
+        ReportError::Json(item)
+    }
+}
+
+impl From<serde_yaml::Error> for ReportError {
+    fn from(item: serde_yaml::Error) -> ReportError {
+        ReportError::Yaml(item)
+    }
+}
+
+impl fmt::Display for ReportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReportError::Json(ref err) => write!(f, "JSON error: {}", err),
+            ReportError::Yaml(ref err) => write!(f, "YAML error: {}", err),
+        }
+    }
+}
+
+impl error::Error for ReportError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ReportError::Json(ref err) => Some(err),
+            ReportError::Yaml(ref err) => Some(err),
+        }
+    }
+}
+
+/// Report that can be written in every machine readable format
+pub trait Report: Serialize {
+    /// Returns the records that are written on separate lines in the JSON Lines format
+    fn lines(&self) -> Result<Vec<String>, ReportError> {
+        Ok(vec![serde_json::to_string(self)?])
+    }
+
+    fn to_json(&self) -> Result<String, ReportError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    fn to_jsonl(&self) -> Result<String, ReportError> {
+        Ok(self.lines()?.join("\n"))
+    }
+
+    fn to_yaml(&self) -> Result<String, ReportError> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChunkProperties {
+    pub critical: bool,
+    pub public: bool,
+    pub reserved_bit_valid: bool,
+    pub safe_to_copy: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChunkReport {
+    pub index: usize,
+    pub offset: usize,
+    #[serde(rename = "type")]
+    pub chunk_type: String,
+    pub properties: ChunkProperties,
+    pub length: u32,
+    pub crc: u32,
+    pub crc_valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+impl ChunkReport {
+    pub fn new(index: usize, offset: usize, chunk: &Chunk, include_data: bool) -> ChunkReport {
+        let chunk_type = chunk.chunk_type();
+        ChunkReport {
+            index,
+            offset,
+            chunk_type: chunk_type.to_string(),
+            properties: ChunkProperties {
+                critical: chunk_type.is_critical(),
+                public: chunk_type.is_public(),
+                reserved_bit_valid: chunk_type.is_reserved_bit_valid(),
+                safe_to_copy: chunk_type.is_safe_to_copy(),
+            },
+            length: chunk.length(),
+            crc: chunk.crc(),
+            crc_valid: chunk.has_valid_crc(),
+            data: include_data.then(|| STANDARD.encode(chunk.data())),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PrintReport {
+    pub schema_version: u32,
+    pub file: String,
+    pub chunks: Vec<ChunkReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trailer: Option<TrailerReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrailerReport {
+    pub offset: usize,
+    pub length: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+impl PrintReport {
+    pub fn new(file_path: &Path, png: &Png, include_data: bool) -> PrintReport {
+        PrintReport {
+            schema_version: SCHEMA_VERSION,
+            file: file_path.display().to_string(),
+            chunks: png
+                .chunks()
+                .iter()
+                .zip(png.chunk_offsets())
+                .enumerate()
+                .map(|(index, (chunk, offset))| {
+                    ChunkReport::new(index, offset, chunk, include_data)
+                })
+                .collect(),
+            trailer: (!png.trailer().is_empty()).then(|| TrailerReport {
+                offset: png.trailer_offset(),
+                length: png.trailer().len(),
+                data: include_data.then(|| STANDARD.encode(png.trailer())),
+            }),
+        }
+    }
+}
+
+/// Record written on its own line in the JSON Lines format, e.g. a chunk
+#[derive(Serialize)]
+struct RecordLine<'a, T: Serialize> {
+    schema_version: u32,
+    file: &'a str,
+    #[serde(flatten)]
+    record: &'a T,
+}
+
+/// Writes every record on its own line, together with the schema version and the file of the report
+fn record_lines<T: Serialize>(
+    schema_version: u32,
+    file: &str,
+    records: &[T],
+) -> Result<Vec<String>, ReportError> {
+    records
+        .iter()
+        .map(|record| {
+            Ok(serde_json::to_string(&RecordLine {
+                schema_version,
+                file,
+                record,
+            })?)
+        })
+        .collect()
+}
+
+/// Trailer record written on the last line in the JSON Lines format
+#[derive(Serialize)]
+struct TrailerLine<'a> {
+    trailer: &'a TrailerReport,
+}
+
+impl Report for PrintReport {
+    fn lines(&self) -> Result<Vec<String>, ReportError> {
+        let mut lines = record_lines(self.schema_version, &self.file, &self.chunks)?;
+        if let Some(trailer) = &self.trailer {
+            lines.extend(record_lines(
+                self.schema_version,
+                &self.file,
+                &[TrailerLine { trailer }],
+            )?);
+        }
+        Ok(lines)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DecodeReport {
+    pub schema_version: u32,
+    pub file: String,
+    #[serde(rename = "type")]
+    pub chunk_type: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repaired_symbols: Option<usize>,
+}
+
+impl Report for DecodeReport {}
+
+#[derive(Debug, Serialize)]
+pub struct CombineReport {
+    pub schema_version: u32,
+    pub files: Vec<String>,
+    #[serde(rename = "type")]
+    pub chunk_type: String,
+    pub shares: usize,
+    pub message: String,
+}
+
+impl Report for CombineReport {}
+
+#[derive(Debug, Serialize)]
+pub struct ExifEntryReport {
+    pub ifd: String,
+    pub tag: u16,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExifReport {
+    pub schema_version: u32,
+    pub file: String,
+    pub entries: Vec<ExifEntryReport>,
+}
+
+impl ExifReport {
+    pub fn new(file_path: &Path, exif: &Exif) -> ExifReport {
+        let mut entries = vec![];
+        for ifd in exif.ifds() {
+            ExifReport::add_entries(&mut entries, ifd, exif);
+        }
+        ExifReport {
+            schema_version: SCHEMA_VERSION,
+            file: file_path.display().to_string(),
+            entries,
+        }
+    }
+
+    /// Adds the entries of the directory, followed by the entries of its sub-directories
+    fn add_entries(entries: &mut Vec<ExifEntryReport>, ifd: &Ifd, exif: &Exif) {
+        entries.extend(ifd.entries().iter().map(|entry| ExifEntryReport {
+            ifd: ifd.kind().to_string(),
+            tag: entry.tag(),
+            name: entry.name(ifd.kind()),
+            value: match entry.sub_ifd() {
+                Some(_) => None,
+                None => Some(entry.value_string(exif.byte_order())),
+            },
+        }));
+        ifd.entries()
+            .iter()
+            .filter_map(|e| e.sub_ifd())
+            .for_each(|sub_ifd| ExifReport::add_entries(entries, sub_ifd, exif));
+    }
+}
+
+impl Report for ExifReport {
+    fn lines(&self) -> Result<Vec<String>, ReportError> {
+        record_lines(self.schema_version, &self.file, &self.entries)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct XmpReport {
+    pub schema_version: u32,
+    pub file: String,
+    pub packet: String,
+}
+
+impl Report for XmpReport {}
+
+#[derive(Debug, Serialize)]
+pub struct FrameReport {
+    pub index: usize,
+    pub offset: usize,
+    pub default_image: bool,
+    pub sequence_number: u32,
+    pub width: u32,
+    pub height: u32,
+    pub x_offset: u32,
+    pub y_offset: u32,
+    pub delay_ms: f64,
+    pub dispose: String,
+    pub blend: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApngReport {
+    pub schema_version: u32,
+    pub file: String,
+    pub num_frames: u32,
+    pub num_plays: u32,
+    pub frames: Vec<FrameReport>,
+}
+
+impl ApngReport {
+    pub fn new(file_path: &Path, png: &Png, animation: &Animation) -> ApngReport {
+        let offsets = png.chunk_offsets();
+        ApngReport {
+            schema_version: SCHEMA_VERSION,
+            file: file_path.display().to_string(),
+            num_frames: animation.control.num_frames,
+            num_plays: animation.control.num_plays,
+            frames: animation
+                .frames
+                .iter()
+                .enumerate()
+                .map(|(index, frame)| FrameReport {
+                    index,
+                    offset: offsets[frame.chunk_index],
+                    default_image: frame.is_default_image,
+                    sequence_number: frame.control.sequence_number,
+                    width: frame.control.width,
+                    height: frame.control.height,
+                    x_offset: frame.control.x_offset,
+                    y_offset: frame.control.y_offset,
+                    delay_ms: frame.control.delay_ms(),
+                    dispose: frame.control.dispose_op.to_string(),
+                    blend: frame.control.blend_op.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Report for ApngReport {
+    fn lines(&self) -> Result<Vec<String>, ReportError> {
+        record_lines(self.schema_version, &self.file, &self.frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_type::ChunkType;
+    use std::str::FromStr;
+
+    fn testing_report(include_data: bool) -> PrintReport {
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![1, 2, 3]),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), b"hi".to_vec()),
+        ]);
+        PrintReport::new(Path::new("test.png"), &png, include_data)
+    }
+
+    #[test]
+    fn test_print_report_json() {
+        let value: serde_json::Value =
+            serde_json::from_str(&testing_report(true).to_json().unwrap()).unwrap();
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["file"], "test.png");
+        let chunk = &value["chunks"][1];
+        assert_eq!(chunk["index"], 1);
+        assert_eq!(chunk["offset"], 23);
+        assert_eq!(chunk["type"], "ruSt");
+        assert_eq!(chunk["properties"]["critical"], false);
+        assert_eq!(chunk["properties"]["public"], false);
+        assert_eq!(chunk["properties"]["safe_to_copy"], true);
+        assert_eq!(chunk["length"], 2);
+        assert_eq!(chunk["crc_valid"], true);
+        assert_eq!(chunk["data"], "aGk=");
+    }
+
+    #[test]
+    fn test_print_report_trailer() {
+        let mut png = Png::from_chunks(vec![Chunk::new(
+            ChunkType::from_str("IEND").unwrap(),
+            vec![],
+        )]);
+        png.set_trailer(b"hi".to_vec());
+        let report = PrintReport::new(Path::new("test.png"), &png, true);
+        let value: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(value["trailer"]["offset"], 20);
+        assert_eq!(value["trailer"]["length"], 2);
+        assert_eq!(value["trailer"]["data"], "aGk=");
+
+        let jsonl = report.to_jsonl().unwrap();
+        let last: serde_json::Value = serde_json::from_str(jsonl.lines().last().unwrap()).unwrap();
+        assert_eq!(jsonl.lines().count(), 2);
+        assert_eq!(last["file"], "test.png");
+        assert_eq!(last["trailer"]["length"], 2);
+        assert!(last.get("type").is_none());
+
+        let value: serde_json::Value =
+            serde_json::from_str(&testing_report(false).to_json().unwrap()).unwrap();
+        assert!(value.get("trailer").is_none());
+    }
+
+    #[test]
+    fn test_print_report_jsonl() {
+        let jsonl = testing_report(false).to_jsonl().unwrap();
+        let lines: Vec<serde_json::Value> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["file"], "test.png");
+        assert_eq!(lines[0]["type"], "IHDR");
+        assert!(lines[0].get("data").is_none());
+    }
+
+    #[test]
+    fn test_exif_report_jsonl() {
+        let mut bytes = b"II".to_vec();
+        bytes.extend(42u16.to_le_bytes());
+        bytes.extend(8u32.to_le_bytes());
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(0x010fu16.to_le_bytes());
+        bytes.extend(2u16.to_le_bytes());
+        bytes.extend(3u32.to_le_bytes());
+        bytes.extend(b"ab\0\0");
+        bytes.extend(0u32.to_le_bytes());
+        let exif = Exif::try_from(bytes.as_slice()).unwrap();
+
+        let jsonl = ExifReport::new(Path::new("test.png"), &exif)
+            .to_jsonl()
+            .unwrap();
+        let lines: Vec<serde_json::Value> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["file"], "test.png");
+        assert_eq!(lines[0]["ifd"], "IFD0");
+        assert_eq!(lines[0]["tag"], 0x010f);
+        assert_eq!(lines[0]["name"], "Make");
+        assert_eq!(lines[0]["value"], "ab");
+    }
+
+    #[test]
+    fn test_apng_report_json() {
+        let frame = Png::from_chunks(vec![
+            Chunk::new(
+                ChunkType::from_str("IHDR").unwrap(),
+                vec![0, 0, 0, 4, 0, 0, 0, 2, 8, 6, 0, 0, 0],
+            ),
+            Chunk::new(ChunkType::from_str("IDAT").unwrap(), vec![1, 2, 3]),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ]);
+        let png = Animation::build(&[frame.clone(), frame], 1, 10, 0).unwrap();
+        let animation = Animation::try_from(&png).unwrap();
+
+        let report = ApngReport::new(Path::new("test.png"), &png, &animation);
+        let value: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(value["num_frames"], 2);
+        assert_eq!(value["num_plays"], 0);
+        assert_eq!(value["frames"][0]["default_image"], true);
+        assert_eq!(value["frames"][1]["sequence_number"], 1);
+        assert_eq!(value["frames"][1]["width"], 4);
+        assert_eq!(value["frames"][1]["delay_ms"], 100.0);
+    }
+
+    #[test]
+    fn test_decode_report_yaml() {
+        let report = DecodeReport {
+            schema_version: SCHEMA_VERSION,
+            file: String::from("test.png"),
+            chunk_type: String::from("ruSt"),
+            message: String::from("hidden"),
+            repaired_symbols: None,
+        };
+        let yaml = report.to_yaml().unwrap();
+        assert!(yaml.contains("type: ruSt"));
+        assert!(yaml.contains("message: hidden"));
+        assert!(!yaml.contains("repaired_symbols"));
+    }
+}
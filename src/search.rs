@@ -0,0 +1,278 @@
+/*!
+ * # Search crate
+ *
+ * Defines the search of a pattern in the chunks of a PNG file. Besides the raw chunk data, the compressed contents of
+ * the `zTXt`, `iTXt` and `iCCP` chunks are inflated and searched as well, so that compressed text and ICC profiles can
+ * be found.
+ *
+ * Messages encoded with forward error correction are searched in their corrected form as well if the redundancy level
+ * is given, since their parity bytes interrupt the message every `255 - parity` bytes. Secret shares can't be searched,
+ * since a single share reveals nothing about the message.
+ *
+ * The pattern is either a regular expression or a sequence of hex encoded bytes, e.g. `de ad be ef`.
+ *
+ */
+
+use crate::chunk::Chunk;
+use crate::png::Png;
+use crate::reed_solomon;
+use crate::text;
+use regex::bytes::{Regex, RegexBuilder};
+use std::error;
+use std::fmt;
+
+const SEPARATOR: u8 = 0;
+const COMPRESSION_FLAG_SET: u8 = 1;
+const MAX_MATCH_LENGTH: usize = 64;
+
+#[derive(Debug)]
+pub enum SearchError {
+    Pattern(regex::Error),
+    InvalidHex(String),
+}
+
+impl From<regex::Error> for SearchError {
+    fn from(item: regex::Error) -> SearchError {
+        SearchError::Pattern(item)
+    }
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SearchError::Pattern(ref err) => write!(f, "Invalid pattern: {}", err),
+            SearchError::InvalidHex(ref pattern) => {
+                write!(f, "Invalid hex byte pattern {}.", pattern)
+            }
+        }
+    }
+}
+
+impl error::Error for SearchError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            SearchError::Pattern(ref err) => Some(err),
+            SearchError::InvalidHex(_) => None,
+        }
+    }
+}
+
+/// Part of the chunk in which a match was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// Chunk data as stored in the file
+    Data,
+    /// Inflated compressed contents of the chunk
+    Inflated,
+    /// Message corrected by the forward error correction, without the parity bytes
+    Corrected,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Source::Data => write!(f, "data"),
+            Source::Inflated => write!(f, "inflated"),
+            Source::Corrected => write!(f, "corrected"),
+        }
+    }
+}
+
+/// Match of the pattern in a chunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    pub index: usize,
+    pub chunk_type: String,
+    pub source: Source,
+    /// Offset of the match from the start of the chunk data or of the inflated contents
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+}
+
+impl fmt::Display for Match {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}:{}:",
+            self.index, self.chunk_type, self.source, self.offset
+        )?;
+        for &byte in self.bytes.iter().take(MAX_MATCH_LENGTH) {
+            match byte {
+                0x20..=0x7e => write!(f, "{}", byte as char)?,
+                _ => write!(f, "\\x{:02x}", byte)?,
+            }
+        }
+        if self.bytes.len() > MAX_MATCH_LENGTH {
+            write!(f, "...")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses hex encoded bytes, optionally separated by whitespace, into a pattern matching exactly those bytes
+fn hex_pattern(pattern: &str) -> Result<String, SearchError> {
+    let digits: Vec<char> = pattern.chars().filter(|c| !c.is_whitespace()).collect();
+    if digits.is_empty()
+        || !digits.len().is_multiple_of(2)
+        || !digits.iter().all(|c| c.is_ascii_hexdigit())
+    {
+        return Err(SearchError::InvalidHex(pattern.to_owned()));
+    }
+    Ok(digits
+        .chunks(2)
+        .map(|pair| format!("\\x{}{}", pair[0], pair[1]))
+        .collect())
+}
+
+/// Builds the regular expression of the pattern, matching arbitrary bytes rather than only UTF-8 text
+pub fn pattern(pattern: &str, hex: bool, ignore_case: bool) -> Result<Regex, SearchError> {
+    let pattern = match hex {
+        true => hex_pattern(pattern)?,
+        false => pattern.to_owned(),
+    };
+    Ok(RegexBuilder::new(&pattern)
+        .unicode(!hex)
+        .case_insensitive(ignore_case)
+        .build()?)
+}
+
+fn split_at_separator(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let index = data.iter().position(|&b| b == SEPARATOR)?;
+    Some((&data[..index], &data[index + 1..]))
+}
+
+/// Returns the compressed contents of the chunk, if the chunk type stores compressed contents
+fn compressed(chunk: &Chunk) -> Option<&[u8]> {
+    let (_, rest) = split_at_separator(chunk.data())?;
+    match &chunk.chunk_type().bytes() {
+        b"zTXt" | b"iCCP" => rest.get(1..),
+        b"iTXt" => match rest {
+            [COMPRESSION_FLAG_SET, _, rest @ ..] => {
+                let (_, rest) = split_at_separator(rest)?;
+                let (_, text) = split_at_separator(rest)?;
+                Some(text)
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/**
+ * Searches the pattern in every chunk of the PNG file, in the raw data and in the inflated compressed contents.
+ *
+ * If the redundancy level of the forward error correction is given, the corrected messages of the ancillary chunks are
+ * searched as well. Compressed contents that fail to inflate and messages that can't be corrected are only searched as
+ * raw data.
+ */
+pub fn search(png: &Png, pattern: &Regex, parity: Option<u8>) -> Vec<Match> {
+    let mut matches = vec![];
+    for (index, chunk) in png.chunks().iter().enumerate() {
+        let mut find = |data: &[u8], source: Source| {
+            matches.extend(pattern.find_iter(data).map(|m| Match {
+                index,
+                chunk_type: chunk.chunk_type().to_string(),
+                source,
+                offset: m.start(),
+                bytes: m.as_bytes().to_vec(),
+            }))
+        };
+        find(chunk.data(), Source::Data);
+        if let Some(Ok(contents)) = compressed(chunk).map(text::inflate) {
+            find(&contents, Source::Inflated);
+        }
+        // NOTE: messages are only encoded in ancillary chunks, correcting the image data would be slow and pointless
+        if let Some(parity) = parity.filter(|_| !chunk.chunk_type().is_critical()) {
+            if let Ok((message, _)) = reed_solomon::decode(chunk.data(), parity) {
+                find(&message, Source::Corrected);
+            }
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_type::ChunkType;
+    use std::str::FromStr;
+
+    fn testing_png() -> Png {
+        let mut compressed = b"Comment\0\0".to_vec();
+        compressed.extend(text::deflate(b"Licensed under MIT").unwrap());
+        let mut international = b"Author\0\x01\0en\0\0".to_vec();
+        international.extend(text::deflate(b"secret token abc123").unwrap());
+        Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![0; 13]),
+            Chunk::new(ChunkType::from_str("tEXt").unwrap(), b"Title\0MIT".to_vec()),
+            Chunk::new(ChunkType::from_str("zTXt").unwrap(), compressed),
+            Chunk::new(ChunkType::from_str("iTXt").unwrap(), international),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ])
+    }
+
+    #[test]
+    fn test_search_inflated_contents() {
+        let matches = search(&testing_png(), &pattern("MIT", false, false).unwrap(), None);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(
+            (matches[0].index, matches[0].source, matches[0].offset),
+            (1, Source::Data, 6)
+        );
+        assert_eq!(
+            (matches[1].index, matches[1].source, matches[1].offset),
+            (2, Source::Inflated, 15)
+        );
+
+        let matches = search(
+            &testing_png(),
+            &pattern(r"token \w+", false, false).unwrap(),
+            None,
+        );
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].chunk_type, "iTXt");
+        assert_eq!(matches[0].to_string(), "3:iTXt:inflated:7:token abc123");
+    }
+
+    #[test]
+    fn test_search_ignore_case() {
+        let matches = search(
+            &testing_png(),
+            &pattern("licensed", false, true).unwrap(),
+            None,
+        );
+        assert_eq!(matches.len(), 1);
+    }
+
+    #[test]
+    fn test_search_corrected_message() {
+        // NOTE: with 250 parity bytes every block holds 5 message bytes, so the message is split by the parity bytes
+        let encoded = reed_solomon::encode(b"secret token", 250).unwrap();
+        let png = Png::from_chunks(vec![
+            Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![0; 13]),
+            Chunk::new(ChunkType::from_str("ruSt").unwrap(), encoded),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ]);
+        let token = pattern("secret token", false, false).unwrap();
+        assert!(search(&png, &token, None).is_empty());
+
+        let matches = search(&png, &token, Some(250));
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].to_string(), "1:ruSt:corrected:0:secret token");
+    }
+
+    #[test]
+    fn test_search_hex_pattern() {
+        let matches = search(
+            &testing_png(),
+            &pattern("00 4d49", true, false).unwrap(),
+            None,
+        );
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].to_string(), "1:tEXt:data:5:\\x00MI");
+        assert!(matches!(
+            pattern("4d4", true, false),
+            Err(SearchError::InvalidHex(_))
+        ));
+    }
+}
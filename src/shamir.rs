@@ -0,0 +1,444 @@
+/*!
+ * # Shamir crate
+ *
+ * Defines [Shamir's secret sharing](https://en.wikipedia.org/wiki/Shamir%27s_secret_sharing) over GF(256).
+ * A secret is split into N shares so that any K of them reconstruct it, while fewer than K reveal nothing about it.
+ *
+ * The CRC of the secret, 4 bytes big endian, is appended to the secret before it is split, so that the checksum is
+ * secret shared as well and verifies the reconstruction without revealing anything about the secret.
+ *
+ * Every share is serialized with a small header so that it can be stored as the data of a single chunk:
+ * - version of the share format, 1 byte
+ * - threshold K, 1 byte
+ * - total number of shares N, 1 byte
+ * - share index (x coordinate), 1 byte
+ * - random identifier of the share set, 4 bytes, used to group the shares of the same split
+ * - share bytes, one per byte of the secret and its CRC
+ *
+ * Shares of several splits, e.g. of files that were split again, are grouped by their share set identifier and every
+ * set is combined on its own.
+ *
+ */
+
+use crate::gf256;
+use crc::{Crc, CRC_32_ISO_HDLC};
+use std::error;
+use std::fmt;
+
+const SHARE_VERSION: u8 = 2;
+const SHARE_HEADER_LEN: usize = 8;
+const CHECKSUM_LEN: usize = 4;
+
+#[derive(Debug)]
+pub enum ShamirError {
+    InvalidThreshold,
+    InvalidShare,
+    InsufficientShares { needed: u8, found: usize },
+    InconsistentShares,
+    AmbiguousShares(usize),
+    Random(getrandom::Error),
+}
+
+impl From<getrandom::Error> for ShamirError {
+    fn from(item: getrandom::Error) -> ShamirError {
+        ShamirError::Random(item)
+    }
+}
+
+impl fmt::Display for ShamirError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ShamirError::InvalidThreshold => write!(
+                f,
+                "Threshold must be at least 1 and not greater than the number of shares."
+            ),
+            ShamirError::InvalidShare => write!(f, "Malformed secret share."),
+            ShamirError::InsufficientShares { needed, found } => write!(
+                f,
+                "Insufficient shares, {} needed but only {} found.",
+                needed, found
+            ),
+            ShamirError::InconsistentShares => write!(
+                f,
+                "Shares are inconsistent, they do not belong to the same secret."
+            ),
+            ShamirError::AmbiguousShares(sets) => write!(
+                f,
+                "Shares of {} different secrets are complete, combine the files of a single secret.",
+                sets
+            ),
+            ShamirError::Random(ref err) => write!(f, "Random generator error: {}", err),
+        }
+    }
+}
+
+impl error::Error for ShamirError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ShamirError::Random(ref err) => Some(err),
+            ShamirError::InvalidThreshold => None,
+            ShamirError::InvalidShare => None,
+            ShamirError::InsufficientShares { .. } => None,
+            ShamirError::InconsistentShares => None,
+            ShamirError::AmbiguousShares(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Share {
+    threshold: u8,
+    shares: u8,
+    index: u8,
+    set_id: [u8; 4],
+    data: Vec<u8>,
+}
+
+impl Share {
+    pub fn as_bytes(&self) -> Vec<u8> {
+        [SHARE_VERSION, self.threshold, self.shares, self.index]
+            .iter()
+            .chain(self.set_id.iter())
+            .chain(self.data.iter())
+            .copied()
+            .collect()
+    }
+}
+
+impl TryFrom<&[u8]> for Share {
+    type Error = ShamirError;
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        if value.len() < SHARE_HEADER_LEN || value[0] != SHARE_VERSION {
+            return Err(ShamirError::InvalidShare);
+        }
+
+        let (threshold, shares, index) = (value[1], value[2], value[3]);
+        if threshold == 0
+            || threshold > shares
+            || index == 0
+            || index > shares
+            || value.len() < SHARE_HEADER_LEN + CHECKSUM_LEN
+        {
+            return Err(ShamirError::InvalidShare);
+        }
+
+        Ok(Share {
+            threshold,
+            shares,
+            index,
+            set_id: [value[4], value[5], value[6], value[7]],
+            data: value[SHARE_HEADER_LEN..].to_vec(),
+        })
+    }
+}
+
+fn checksum(secret: &[u8]) -> u32 {
+    Crc::<u32>::new(&CRC_32_ISO_HDLC).checksum(secret)
+}
+
+/// Splits a secret into `shares` shares, any `threshold` of which reconstruct the secret
+pub fn split(secret: &[u8], shares: u8, threshold: u8) -> Result<Vec<Share>, ShamirError> {
+    if threshold == 0 || threshold > shares {
+        return Err(ShamirError::InvalidThreshold);
+    }
+
+    let mut set_id = [0u8; 4];
+    getrandom::getrandom(&mut set_id)?;
+    let mut shared = secret.to_vec();
+    shared.extend(checksum(secret).to_be_bytes());
+
+    let mut result: Vec<Share> = (1..=shares)
+        .map(|index| Share {
+            threshold,
+            shares,
+            index,
+            set_id,
+            data: Vec::with_capacity(shared.len()),
+        })
+        .collect();
+
+    let mut coefficients = vec![0u8; threshold as usize];
+    for &byte in &shared {
+        coefficients[0] = byte;
+        getrandom::getrandom(&mut coefficients[1..])?;
+        result.iter_mut().for_each(|share| {
+            share
+                .data
+                .push(gf256::poly_eval(&coefficients, share.index))
+        });
+    }
+
+    Ok(result)
+}
+
+/// Reconstructs a secret from a set of shares, verifying that the shares are sufficient and consistent
+pub fn combine(shares: &[Share]) -> Result<Vec<u8>, ShamirError> {
+    let first = shares.first().ok_or(ShamirError::InsufficientShares {
+        needed: 1,
+        found: 0,
+    })?;
+
+    if shares.iter().any(|s| {
+        s.threshold != first.threshold
+            || s.shares != first.shares
+            || s.set_id != first.set_id
+            || s.data.len() != first.data.len()
+    }) {
+        return Err(ShamirError::InconsistentShares);
+    }
+
+    let mut unique: Vec<&Share> = vec![];
+    for share in shares {
+        match unique.iter().find(|s| s.index == share.index) {
+            Some(s) if s.data != share.data => return Err(ShamirError::InconsistentShares),
+            Some(_) => (),
+            None => unique.push(share),
+        }
+    }
+
+    if unique.len() < first.threshold as usize {
+        return Err(ShamirError::InsufficientShares {
+            needed: first.threshold,
+            found: unique.len(),
+        });
+    }
+    let unique = &unique[..first.threshold as usize];
+
+    // Lagrange interpolation at x = 0
+    let weights: Vec<u8> = unique
+        .iter()
+        .map(|i| {
+            unique
+                .iter()
+                .filter(|j| j.index != i.index)
+                .fold(1, |acc, j| {
+                    gf256::mul(acc, gf256::div(j.index, gf256::add(j.index, i.index)))
+                })
+        })
+        .collect();
+
+    let mut secret: Vec<u8> = (0..first.data.len())
+        .map(|pos| {
+            unique
+                .iter()
+                .zip(weights.iter())
+                .fold(0, |acc, (share, &w)| {
+                    gf256::add(acc, gf256::mul(share.data[pos], w))
+                })
+        })
+        .collect();
+
+    if secret.len() < CHECKSUM_LEN {
+        return Err(ShamirError::InvalidShare);
+    }
+    let stored = secret.split_off(secret.len() - CHECKSUM_LEN);
+    if checksum(&secret).to_be_bytes()[..] != stored[..] {
+        return Err(ShamirError::InconsistentShares);
+    }
+
+    Ok(secret)
+}
+
+/// Groups the shares by the share set they belong to, in the order the sets are first found
+pub fn group(shares: &[Share]) -> Vec<Vec<Share>> {
+    let mut sets: Vec<Vec<Share>> = vec![];
+    for share in shares {
+        match sets.iter_mut().find(|set| set[0].set_id == share.set_id) {
+            Some(set) => set.push(share.clone()),
+            None => sets.push(vec![share.clone()]),
+        }
+    }
+    sets
+}
+
+/**
+ * Reconstructs a secret from shares that may belong to several share sets, e.g. of files that were split again.
+ *
+ * Every set is combined on its own and the secret of the only set that reconstructs is returned. If no set
+ * reconstructs, the error of the largest set is returned.
+ */
+pub fn combine_sets(shares: &[Share]) -> Result<Vec<u8>, ShamirError> {
+    let mut sets = group(shares);
+    sets.sort_by_key(|set| std::cmp::Reverse(set.len()));
+
+    let mut secrets = vec![];
+    let mut error = None;
+    for set in &sets {
+        match combine(set) {
+            Ok(secret) => secrets.push(secret),
+            Err(err) => {
+                error.get_or_insert(err);
+            }
+        }
+    }
+
+    match secrets.len() {
+        0 => Err(error.unwrap_or(ShamirError::InsufficientShares {
+            needed: 1,
+            found: 0,
+        })),
+        1 => Ok(secrets.remove(0)),
+        sets => Err(ShamirError::AmbiguousShares(sets)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::Chunk;
+    use crate::chunk_type::ChunkType;
+    use crate::png::Png;
+    use std::str::FromStr;
+
+    const SECRET: &[u8] = b"This is where your secret message will be!";
+
+    #[test]
+    fn test_split_and_combine_all() {
+        let shares = split(SECRET, 5, 3).unwrap();
+        assert_eq!(shares.len(), 5);
+        assert_eq!(combine(&shares).unwrap(), SECRET);
+    }
+
+    #[test]
+    fn test_combine_any_threshold_subset() {
+        let shares = split(SECRET, 5, 3).unwrap();
+        let subset = vec![shares[4].clone(), shares[1].clone(), shares[2].clone()];
+        assert_eq!(combine(&subset).unwrap(), SECRET);
+    }
+
+    #[test]
+    fn test_combine_insufficient() {
+        let shares = split(SECRET, 5, 3).unwrap();
+        let result = combine(&shares[..2]);
+        assert!(matches!(
+            result,
+            Err(ShamirError::InsufficientShares {
+                needed: 3,
+                found: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_combine_duplicate_does_not_count() {
+        let shares = split(SECRET, 3, 2).unwrap();
+        let duplicated = vec![shares[0].clone(), shares[0].clone()];
+        assert!(matches!(
+            combine(&duplicated),
+            Err(ShamirError::InsufficientShares { .. })
+        ));
+    }
+
+    #[test]
+    fn test_combine_inconsistent() {
+        let first = split(SECRET, 3, 2).unwrap();
+        let second = split(b"Another secret", 3, 2).unwrap();
+        let mixed = vec![first[0].clone(), second[1].clone()];
+        assert!(matches!(
+            combine(&mixed),
+            Err(ShamirError::InconsistentShares)
+        ));
+    }
+
+    #[test]
+    fn test_combine_tampered() {
+        let mut shares = split(SECRET, 3, 2).unwrap();
+        shares[1].data[0] ^= 0xff;
+        assert!(matches!(
+            combine(&shares[..2]),
+            Err(ShamirError::InconsistentShares)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_threshold() {
+        assert!(split(SECRET, 2, 3).is_err());
+        assert!(split(SECRET, 2, 0).is_err());
+    }
+
+    #[test]
+    fn test_share_bytes_roundtrip() {
+        let shares = split(SECRET, 2, 2).unwrap();
+        let bytes = shares[1].as_bytes();
+        let share = Share::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(share, shares[1]);
+        assert_eq!(share.index, 2);
+        assert_eq!(share.threshold, 2);
+        assert_eq!(share.shares, 2);
+    }
+
+    #[test]
+    fn test_invalid_share_bytes() {
+        assert!(Share::try_from(&[2, 2, 3][..]).is_err());
+        assert!(Share::try_from(&[9, 2, 3, 1, 0, 0, 0, 0, 0, 0, 0, 0][..]).is_err());
+        assert!(Share::try_from(&[2, 2, 3, 4, 0, 0, 0, 0, 0, 0, 0, 0][..]).is_err());
+    }
+
+    #[test]
+    fn test_share_does_not_store_checksum() {
+        let first = split(SECRET, 3, 2).unwrap();
+        let second = split(SECRET, 3, 2).unwrap();
+        assert_ne!(first[0].set_id, second[0].set_id);
+        assert!(!first[0]
+            .as_bytes()
+            .windows(4)
+            .any(|w| w == checksum(SECRET).to_be_bytes()));
+        assert_eq!(first[0].data.len(), SECRET.len() + CHECKSUM_LEN);
+    }
+
+    #[test]
+    fn test_combine_sets() {
+        let first = split(SECRET, 3, 2).unwrap();
+        let second = split(b"Another secret", 3, 2).unwrap();
+
+        let incomplete = vec![first[0].clone(), second[0].clone(), second[2].clone()];
+        assert_eq!(group(&incomplete).len(), 2);
+        assert_eq!(combine_sets(&incomplete).unwrap(), b"Another secret");
+
+        let complete = vec![
+            first[0].clone(),
+            first[1].clone(),
+            second[0].clone(),
+            second[1].clone(),
+        ];
+        assert!(matches!(
+            combine_sets(&complete),
+            Err(ShamirError::AmbiguousShares(2))
+        ));
+        assert!(matches!(
+            combine_sets(&first[..1]),
+            Err(ShamirError::InsufficientShares {
+                needed: 2,
+                found: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_combine_sets_after_resplit() {
+        let chunk_type = ChunkType::from_str("ruSt").unwrap();
+        let mut pngs = vec![
+            Png::from_chunks(vec![
+                Chunk::new(ChunkType::from_str("IHDR").unwrap(), vec![0; 13]),
+                Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+            ]);
+            3
+        ];
+
+        for secret in [&b"first secret"[..], &b"second secret"[..]] {
+            for (png, share) in pngs.iter_mut().zip(split(secret, 3, 2).unwrap()) {
+                png.remove_chunks(|chunk| *chunk.chunk_type() == chunk_type);
+                png.insert_chunk_ordered(Chunk::new(chunk_type.clone(), share.as_bytes()))
+                    .unwrap();
+            }
+        }
+
+        let shares: Vec<Share> = pngs[..2]
+            .iter()
+            .flat_map(|png| png.chunks_of_type(Some(&chunk_type)))
+            .map(|(_, chunk)| Share::try_from(chunk.data()).unwrap())
+            .collect();
+        assert_eq!(shares.len(), 2);
+        assert_eq!(combine_sets(&shares).unwrap(), b"second secret");
+    }
+}
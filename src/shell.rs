@@ -0,0 +1,598 @@
+/*!
+ * # Shell crate
+ *
+ * Defines the interactive shell that loads a PNG file once and lets the user explore and edit its chunks. Every edit
+ * can be undone until the file is saved, and the line editor keeps the history of the commands and completes the
+ * command names and chunk types.
+ *
+ */
+
+use crate::chunk::{Chunk, ChunkError};
+use crate::chunk_type::{ChunkType, ChunkTypeError};
+use crate::hexdump;
+use crate::image_header::ImageHeader;
+use crate::output;
+use crate::png::{Png, PngError};
+use crate::summary;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::env;
+use std::error;
+use std::fmt;
+use std::fmt::Write;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+const PROMPT: &str = "pngme> ";
+const HISTORY_FILE: &str = ".pngme_history";
+const COMMANDS: [&str; 12] = [
+    "ls", "show", "hexdump", "rm", "add", "mv", "validate", "undo", "save", "help", "quit", "exit",
+];
+/// Chunk types defined by the PNG and APNG specifications, completed even if the file doesn't contain them
+const KNOWN_CHUNK_TYPES: [&str; 22] = [
+    "IHDR", "PLTE", "IDAT", "IEND", "tRNS", "cHRM", "gAMA", "iCCP", "sBIT", "sRGB", "tEXt", "zTXt",
+    "iTXt", "bKGD", "hIST", "pHYs", "sPLT", "tIME", "eXIf", "acTL", "fcTL", "fdAT",
+];
+const HELP: &str = "\
+ls                       list the chunks
+show <chunk>             show the chunk and its decoded data
+hexdump <chunk>          show the chunk data as hex and ASCII
+rm <chunk>               remove the chunk
+add <type> <message>     add a chunk with the message before the image data
+mv <chunk> <index>       move the chunk to the index
+validate                 check the structure of the file
+undo                     undo the last edit
+save [path]              save the file, by default over the loaded file
+quit                     leave the shell
+
+A chunk is referenced by its index or by its type, in which case the first chunk of the type is used.";
+
+#[derive(Debug)]
+pub enum ShellError {
+    UnknownCommand(String),
+    MissingArgument(&'static str),
+    InvalidIndex(String),
+    NotFoundChunk(String),
+    NothingToUndo,
+    Png(PngError),
+    Chunk(ChunkError),
+    ChunkType(ChunkTypeError),
+    Filesystem(io::Error),
+    Readline(ReadlineError),
+}
+
+impl From<PngError> for ShellError {
+    fn from(item: PngError) -> ShellError {
+        ShellError::Png(item)
+    }
+}
+
+impl From<ChunkError> for ShellError {
+    fn from(item: ChunkError) -> ShellError {
+        ShellError::Chunk(item)
+    }
+}
+
+impl From<ChunkTypeError> for ShellError {
+    fn from(item: ChunkTypeError) -> ShellError {
+        ShellError::ChunkType(item)
+    }
+}
+
+impl From<io::Error> for ShellError {
+    fn from(item: io::Error) -> ShellError {
+        ShellError::Filesystem(item)
+    }
+}
+
+impl From<ReadlineError> for ShellError {
+    fn from(item: ReadlineError) -> ShellError {
+        ShellError::Readline(item)
+    }
+}
+
+impl fmt::Display for ShellError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ShellError::UnknownCommand(ref command) => {
+                write!(
+                    f,
+                    "Unknown command {}, type help for the commands.",
+                    command
+                )
+            }
+            ShellError::MissingArgument(name) => write!(f, "Missing argument {}.", name),
+            ShellError::InvalidIndex(ref index) => write!(f, "Invalid chunk index {}.", index),
+            ShellError::NotFoundChunk(ref chunk_type) => {
+                write!(f, "Cannot find chunk of type {}.", chunk_type)
+            }
+            ShellError::NothingToUndo => write!(f, "Nothing to undo."),
+            ShellError::Png(ref err) => write!(f, "Png error: {}", err),
+            ShellError::Chunk(ref err) => write!(f, "Chunk error: {}", err),
+            ShellError::ChunkType(ref err) => write!(f, "Chunk type error: {}", err),
+            ShellError::Filesystem(ref err) => write!(f, "Filesystem error: {}", err),
+            ShellError::Readline(ref err) => write!(f, "Line editor error: {}", err),
+        }
+    }
+}
+
+impl error::Error for ShellError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ShellError::UnknownCommand(_) => None,
+            ShellError::MissingArgument(_) => None,
+            ShellError::InvalidIndex(_) => None,
+            ShellError::NotFoundChunk(_) => None,
+            ShellError::NothingToUndo => None,
+            ShellError::Png(ref err) => Some(err),
+            ShellError::Chunk(ref err) => Some(err),
+            ShellError::ChunkType(ref err) => Some(err),
+            ShellError::Filesystem(ref err) => Some(err),
+            ShellError::Readline(ref err) => Some(err),
+        }
+    }
+}
+
+/// Result of a shell command
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Output(String),
+    Quit,
+}
+
+/// PNG file loaded in the shell, together with the edits that can be undone
+pub struct Session {
+    file_path: PathBuf,
+    png: Png,
+    undo: Vec<Png>,
+    /// Depth of the undo stack when the file was saved, `None` once undoing can't return to the saved state
+    saved_depth: Option<usize>,
+    quit_requested: bool,
+}
+
+impl Session {
+    pub fn new(file_path: PathBuf, png: Png) -> Session {
+        Session {
+            file_path,
+            png,
+            undo: Vec::new(),
+            saved_depth: Some(0),
+            quit_requested: false,
+        }
+    }
+
+    /// Returns whether the file differs from the last saved state
+    fn is_modified(&self) -> bool {
+        self.saved_depth != Some(self.undo.len())
+    }
+
+    /// Returns the types of the chunks of the file, without duplicates
+    pub fn chunk_types(&self) -> Vec<String> {
+        let mut chunk_types: Vec<String> = Vec::new();
+        for chunk in self.png.chunks() {
+            let chunk_type = chunk.chunk_type().to_string();
+            if !chunk_types.contains(&chunk_type) {
+                chunk_types.push(chunk_type);
+            }
+        }
+        chunk_types
+    }
+
+    /// Executes a single command line
+    pub fn execute(&mut self, line: &str) -> Result<Outcome, ShellError> {
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            return Ok(Outcome::Output(String::new()));
+        };
+        if !matches!(command, "quit" | "exit") {
+            self.quit_requested = false;
+        }
+
+        let output = match command {
+            "ls" => self.list(),
+            "show" => self.show(self.chunk_index(words.next())?),
+            "hexdump" => {
+                let index = self.chunk_index(words.next())?;
+                hexdump::hexdump(self.png.chunks()[index].data(), 0)
+                    .trim_end()
+                    .to_owned()
+            }
+            "rm" => {
+                let index = self.chunk_index(words.next())?;
+                self.edit(|png| png.remove_chunk_at(index).map(|_| ()))?;
+                format!("Removed chunk {}", index)
+            }
+            "add" => {
+                // NOTE: the message is the rest of the line after the type, so that it can contain spaces
+                let arguments = line.trim_start()[command.len()..].trim_start();
+                let (chunk_type, message) = arguments
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((arguments, ""));
+                if chunk_type.is_empty() {
+                    return Err(ShellError::MissingArgument("type"));
+                }
+                let chunk_type = ChunkType::from_str(chunk_type)?;
+                let message = message.trim();
+                let chunk = Chunk::new(chunk_type, message.as_bytes().to_vec());
+                self.edit(|png| png.insert_chunk_ordered(chunk))?;
+                String::from("Added chunk")
+            }
+            "mv" => {
+                let from = self.chunk_index(words.next())?;
+                let to = words.next().ok_or(ShellError::MissingArgument("index"))?;
+                let to = to
+                    .parse()
+                    .map_err(|_| ShellError::InvalidIndex(to.to_owned()))?;
+                self.edit(|png| png.move_chunk(from, to))?;
+                format!("Moved chunk {} to {}", from, to)
+            }
+            "validate" => match validate(&self.png) {
+                problems if problems.is_empty() => String::from("No problems found."),
+                problems => problems.join("\n"),
+            },
+            "undo" => {
+                self.png = self.undo.pop().ok_or(ShellError::NothingToUndo)?;
+                String::from("Undone")
+            }
+            "save" => {
+                let file_path = words.next().map_or(self.file_path.clone(), PathBuf::from);
+                output::write_atomic(&file_path, &self.png.as_bytes(), None, false)?;
+                self.saved_depth = Some(self.undo.len());
+                format!("Saved {}", file_path.display())
+            }
+            "help" => String::from(HELP),
+            "quit" | "exit" if self.is_modified() && !self.quit_requested => {
+                self.quit_requested = true;
+                String::from("There are unsaved changes, quit again to discard them.")
+            }
+            "quit" | "exit" => return Ok(Outcome::Quit),
+            _ => return Err(ShellError::UnknownCommand(command.to_owned())),
+        };
+
+        Ok(Outcome::Output(output))
+    }
+
+    /// Applies the edit, keeping the previous state of the file to undo it
+    fn edit<F>(&mut self, edit: F) -> Result<(), ShellError>
+    where
+        F: FnOnce(&mut Png) -> Result<(), PngError>,
+    {
+        let previous = self.png.clone();
+        edit(&mut self.png)?;
+        // NOTE: an edit after undoing past the saved state discards that state from the undo history
+        if self
+            .saved_depth
+            .is_some_and(|depth| depth > self.undo.len())
+        {
+            self.saved_depth = None;
+        }
+        self.undo.push(previous);
+        Ok(())
+    }
+
+    /// Resolves a chunk reference, either an index or the type of the first chunk of that type
+    fn chunk_index(&self, reference: Option<&str>) -> Result<usize, ShellError> {
+        let reference = reference.ok_or(ShellError::MissingArgument("chunk"))?;
+        if let Ok(index) = reference.parse::<usize>() {
+            return match index < self.png.chunks().len() {
+                true => Ok(index),
+                false => Err(ShellError::InvalidIndex(reference.to_owned())),
+            };
+        }
+        self.png
+            .chunk_position(reference)?
+            .ok_or_else(|| ShellError::NotFoundChunk(reference.to_owned()))
+    }
+
+    fn list(&self) -> String {
+        let mut listing = String::new();
+        for (index, (chunk, offset)) in self
+            .png
+            .chunks()
+            .iter()
+            .zip(self.png.chunk_offsets())
+            .enumerate()
+        {
+            let _ = writeln!(
+                listing,
+                "{:>4}  {:>10}  {}  {:>10} bytes{}",
+                index,
+                offset,
+                chunk.chunk_type(),
+                chunk.length(),
+                if chunk.has_valid_crc() {
+                    ""
+                } else {
+                    "  invalid CRC"
+                }
+            );
+        }
+        listing.trim_end().to_owned()
+    }
+
+    fn show(&self, index: usize) -> String {
+        let chunk = &self.png.chunks()[index];
+        let decoded = summary::describe(&self.png, chunk);
+
+        let mut description = format!("Index: {}\n{}", index, chunk);
+        if !chunk.has_valid_crc() {
+            description.push_str("Invalid CRC\n");
+        }
+        if !decoded.is_empty() {
+            description.push_str(&decoded);
+        }
+        description.trim_end().to_owned()
+    }
+}
+
+/// Checks the structure of the PNG file and returns the problems that were found
+pub fn validate(png: &Png) -> Vec<String> {
+    let chunks = png.chunks();
+    let types: Vec<String> = chunks.iter().map(|c| c.chunk_type().to_string()).collect();
+    let mut problems = Vec::new();
+
+    match chunks.first() {
+        Some(chunk) if types[0] == ImageHeader::CHUNK_TYPE => {
+            if let Err(err) = ImageHeader::try_from(chunk.data()) {
+                problems.push(format!("IHDR: {}", err));
+            }
+        }
+        _ => problems.push(String::from("IHDR is not the first chunk")),
+    }
+    if types.last().map(String::as_str) != Some("IEND") {
+        problems.push(String::from("IEND is not the last chunk"));
+    }
+    for unique in [ImageHeader::CHUNK_TYPE, "IEND"] {
+        if types.iter().filter(|t| *t == unique).count() > 1 {
+            problems.push(format!("{} appears more than once", unique));
+        }
+    }
+
+    let image_data: Vec<usize> = (0..types.len()).filter(|&i| types[i] == "IDAT").collect();
+    match (image_data.first(), image_data.last()) {
+        (Some(first), Some(last)) if last - first + 1 != image_data.len() => {
+            problems.push(String::from("IDAT chunks are not consecutive"))
+        }
+        (None, _) => problems.push(String::from("IDAT is missing")),
+        _ => {}
+    }
+
+    for (index, chunk) in chunks.iter().enumerate() {
+        if !chunk.has_valid_crc() {
+            problems.push(format!("chunk {} {}: invalid CRC", index, types[index]));
+        }
+        if !chunk.chunk_type().is_reserved_bit_valid() {
+            problems.push(format!(
+                "chunk {} {}: reserved bit is set",
+                index, types[index]
+            ));
+        }
+    }
+    problems
+}
+
+/// Line editor helper that completes the command names and chunk types
+pub struct ShellHelper {
+    chunk_types: Vec<String>,
+}
+
+impl ShellHelper {
+    pub fn new(session: &Session) -> ShellHelper {
+        let mut helper = ShellHelper {
+            chunk_types: Vec::new(),
+        };
+        helper.refresh(session);
+        helper
+    }
+
+    /// Updates the completed chunk types after the file was edited
+    pub fn refresh(&mut self, session: &Session) {
+        self.chunk_types = session.chunk_types();
+        for chunk_type in KNOWN_CHUNK_TYPES {
+            if !self.chunk_types.iter().any(|t| t == chunk_type) {
+                self.chunk_types.push(chunk_type.to_owned());
+            }
+        }
+    }
+
+    /// Returns the start of the completed word and the candidates, commands for the first word and chunk types after it
+    fn completions(&self, line: &str) -> (usize, Vec<String>) {
+        let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let word = &line[start..];
+        let candidates: Vec<String> = match line[..start].trim().is_empty() {
+            true => COMMANDS.iter().map(|c| c.to_string()).collect(),
+            false => self.chunk_types.clone(),
+        };
+        (
+            start,
+            candidates
+                .into_iter()
+                .filter(|candidate| candidate.starts_with(word))
+                .collect(),
+        )
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(self.completions(&line[..pos]))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+/// Runs the shell on the terminal until the user quits
+pub fn run(mut session: Session) -> Result<(), ShellError> {
+    let mut editor: Editor<ShellHelper, DefaultHistory> = Editor::new()?;
+    editor.set_helper(Some(ShellHelper::new(&session)));
+    let history = env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE));
+    if let Some(history) = &history {
+        let _ = editor.load_history(history);
+    }
+
+    loop {
+        let line = match editor.readline(PROMPT) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(err.into()),
+        };
+        if !line.trim().is_empty() {
+            editor.add_history_entry(line.as_str())?;
+        }
+
+        match session.execute(&line) {
+            Ok(Outcome::Output(output)) if output.is_empty() => {}
+            Ok(Outcome::Output(output)) => println!("{}", output),
+            Ok(Outcome::Quit) => break,
+            Err(err) => eprintln!("{}", err),
+        }
+        if let Some(helper) = editor.helper_mut() {
+            helper.refresh(&session);
+        }
+    }
+
+    if let Some(history) = &history {
+        let _ = editor.save_history(history);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(chunk_type: &str, data: &[u8]) -> Chunk {
+        Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data.to_vec())
+    }
+
+    fn testing_session() -> Session {
+        let png = Png::from_chunks(vec![
+            chunk("IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 0, 0]),
+            chunk("IDAT", &[1, 2, 3]),
+            chunk("IEND", &[]),
+        ]);
+        Session::new(PathBuf::from("test.png"), png)
+    }
+
+    fn output(session: &mut Session, line: &str) -> String {
+        match session.execute(line).unwrap() {
+            Outcome::Output(output) => output,
+            Outcome::Quit => panic!("unexpected quit"),
+        }
+    }
+
+    #[test]
+    fn test_edit_and_undo() {
+        let mut session = testing_session();
+        output(&mut session, "add  ruSt  hello  shell ");
+        assert_eq!(session.png.chunks()[1].data(), b"hello  shell");
+        assert!(output(&mut session, "show ruSt").contains("Message: hello  shell"));
+
+        output(&mut session, "mv ruSt 2");
+        assert_eq!(session.chunk_types(), vec!["IHDR", "IDAT", "ruSt", "IEND"]);
+        output(&mut session, "rm 0");
+        assert_eq!(session.png.chunks().len(), 3);
+
+        output(&mut session, "undo");
+        output(&mut session, "undo");
+        output(&mut session, "undo");
+        assert_eq!(session.chunk_types(), vec!["IHDR", "IDAT", "IEND"]);
+        assert!(matches!(
+            session.execute("undo"),
+            Err(ShellError::NothingToUndo)
+        ));
+    }
+
+    #[test]
+    fn test_quit_with_unsaved_changes() {
+        let mut session = testing_session();
+        assert_eq!(session.execute("quit").unwrap(), Outcome::Quit);
+        output(&mut session, "rm IEND");
+        assert!(output(&mut session, "quit").contains("unsaved changes"));
+        assert_eq!(session.execute("quit").unwrap(), Outcome::Quit);
+
+        // Undoing every edit returns to the unmodified file
+        let mut session = testing_session();
+        output(&mut session, "rm IEND");
+        output(&mut session, "undo");
+        assert!(!session.is_modified());
+        assert_eq!(session.execute("quit").unwrap(), Outcome::Quit);
+
+        // Editing after undoing past the saved state can't return to the saved state
+        let mut session = testing_session();
+        output(&mut session, "rm IEND");
+        session.saved_depth = Some(session.undo.len());
+        output(&mut session, "undo");
+        assert!(session.is_modified());
+        output(&mut session, "rm IDAT");
+        assert!(session.is_modified());
+        assert_eq!(session.saved_depth, None);
+    }
+
+    #[test]
+    fn test_invalid_commands() {
+        let mut session = testing_session();
+        assert!(matches!(
+            session.execute("frobnicate"),
+            Err(ShellError::UnknownCommand(_))
+        ));
+        assert!(matches!(
+            session.execute("show 3"),
+            Err(ShellError::InvalidIndex(_))
+        ));
+        assert!(matches!(
+            session.execute("rm tEXt"),
+            Err(ShellError::NotFoundChunk(_))
+        ));
+        assert!(matches!(
+            session.execute("hexdump"),
+            Err(ShellError::MissingArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut session = testing_session();
+        assert_eq!(output(&mut session, "validate"), "No problems found.");
+        output(&mut session, "mv IEND 0");
+        let problems = validate(&session.png);
+        assert!(problems.contains(&String::from("IHDR is not the first chunk")));
+        assert!(problems.contains(&String::from("IEND is not the last chunk")));
+    }
+
+    #[test]
+    fn test_completions() {
+        let session = testing_session();
+        let helper = ShellHelper::new(&session);
+        assert_eq!(
+            helper.completions("h"),
+            (0, vec![String::from("hexdump"), String::from("help")])
+        );
+        let (start, candidates) = helper.completions("show I");
+        assert_eq!(start, 5);
+        assert_eq!(candidates, vec!["IHDR", "IDAT", "IEND"]);
+        assert_eq!(helper.completions("add tE").1, vec!["tEXt"]);
+    }
+}
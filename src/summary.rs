@@ -0,0 +1,328 @@
+/*!
+ * # Summary crate
+ *
+ * Defines a line based summary of the PNG file that is stable between runs and suitable for diffing, e.g. as a git
+ * `textconv` driver. The summary lists the image header fields, the decoded ancillary, textual and animation chunks,
+ * the EXIF entries and a hash of the image data and of the data after the last chunk instead of the data itself.
+ *
+ */
+
+use crate::ancillary::Ancillary;
+use crate::apng::{AnimationControl, FrameControl};
+use crate::chunk::Chunk;
+use crate::exif::{Exif, Ifd};
+use crate::image_header::ImageHeader;
+use crate::png::Png;
+use crate::text::{CompressedText, InternationalText, Text};
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+
+const IMAGE_DATA: &str = "IDAT";
+const CONTINUATION_INDENT: &str = "  ";
+
+/// Returns the SHA-256 digest of the data as lowercase hexadecimal
+pub fn sha256(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Writes a possibly multiline value, indenting the continuation lines
+fn write_value(summary: &mut String, label: &str, value: &str) {
+    let mut lines = value.lines();
+    let _ = writeln!(summary, "{}: {}", label, lines.next().unwrap_or_default());
+    for line in lines {
+        let _ = writeln!(summary, "{}{}", CONTINUATION_INDENT, line);
+    }
+}
+
+/// Collects the entries of the IFD and its sub-IFDs as `(label, value)` pairs, labelled with the IFD and entry name
+fn ifd_entries(entries: &mut Vec<(String, String)>, exif: &Exif, ifd: &Ifd) {
+    for entry in ifd.entries() {
+        match entry.sub_ifd() {
+            Some(sub_ifd) => ifd_entries(entries, exif, sub_ifd),
+            None => entries.push((
+                format!("{} {}", ifd.kind(), entry.name(ifd.kind())),
+                entry.value_string(exif.byte_order()),
+            )),
+        }
+    }
+}
+
+/// Returns whether the data can be shown as text without hiding any bytes
+fn is_text(data: &[u8]) -> bool {
+    std::str::from_utf8(data).is_ok_and(|text| {
+        !text
+            .chars()
+            .any(|c| c.is_control() && !matches!(c, '\n' | '\t'))
+    })
+}
+
+fn describe_animation(chunk: &Chunk) -> Result<String, String> {
+    let value = match chunk.chunk_type().to_string().as_str() {
+        AnimationControl::CHUNK_TYPE => AnimationControl::try_from(chunk.data())
+            .map(|c| format!("frames {}, plays {}", c.num_frames, c.num_plays)),
+        _ => FrameControl::try_from(chunk.data()).map(|c| {
+            format!(
+                "sequence {}, {}x{} at ({}, {}), delay {}/{}, dispose {}, blend {}",
+                c.sequence_number,
+                c.width,
+                c.height,
+                c.x_offset,
+                c.y_offset,
+                c.delay_num,
+                c.delay_den,
+                c.dispose_op,
+                c.blend_op
+            )
+        }),
+    };
+    value.map_err(|err| err.to_string())
+}
+
+/**
+ * Decodes a chunk by its type to `(label, value)` pairs, e.g. the keyword and text of a textual chunk or the name and
+ * value of every EXIF entry. The label is empty for chunks that decode to a single value, e.g. an ancillary chunk.
+ *
+ * Returns `None` for chunks that aren't decoded by their type, e.g. the image data or private chunks, and the error
+ * message for chunks that fail to decode.
+ */
+pub fn decode_chunk(
+    chunk: &Chunk,
+    header: Option<&ImageHeader>,
+) -> Option<Result<Vec<(String, String)>, String>> {
+    let chunk_type = chunk.chunk_type().to_string();
+    let single = |value: String| vec![(String::new(), value)];
+    let text = |keyword: &str, text: &str| vec![(keyword.to_owned(), text.to_owned())];
+
+    let decoded = match chunk_type.as_str() {
+        ImageHeader::CHUNK_TYPE => ImageHeader::try_from(chunk.data())
+            .map(|header| single(header.to_string()))
+            .map_err(|err| err.to_string()),
+        Text::CHUNK_TYPE => Text::try_from(chunk.data())
+            .map(|t| text(t.keyword(), t.text()))
+            .map_err(|err| err.to_string()),
+        CompressedText::CHUNK_TYPE => CompressedText::try_from(chunk.data())
+            .map(|t| text(t.keyword(), t.text()))
+            .map_err(|err| err.to_string()),
+        InternationalText::CHUNK_TYPE => InternationalText::try_from(chunk.data())
+            .map(|t| text(t.keyword(), t.text()))
+            .map_err(|err| err.to_string()),
+        AnimationControl::CHUNK_TYPE | FrameControl::CHUNK_TYPE => {
+            describe_animation(chunk).map(single)
+        }
+        Exif::CHUNK_TYPE => Exif::try_from(chunk.data())
+            .map(|exif| {
+                let mut entries = Vec::new();
+                exif.ifds()
+                    .iter()
+                    .for_each(|ifd| ifd_entries(&mut entries, &exif, ifd));
+                entries
+            })
+            .map_err(|err| err.to_string()),
+        _ => match Ancillary::parse(&chunk_type, chunk.data(), header) {
+            Ok(Some(ancillary)) => Ok(single(ancillary.to_string())),
+            Ok(None) => return None,
+            Err(err) => Err(err.to_string()),
+        },
+    };
+    Some(decoded)
+}
+
+/// Joins the decoded values of a chunk on separate lines, labelled values as `label: value`
+pub fn decoded_text(values: &[(String, String)]) -> String {
+    values
+        .iter()
+        .map(|(label, value)| match label.is_empty() {
+            true => value.clone(),
+            false => format!("{}: {}", label, value),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Decodes a single chunk of the PNG file, returning an empty string for chunks that can't be decoded
+pub fn describe(png: &Png, chunk: &Chunk) -> String {
+    let header = png
+        .chunk_by_type(ImageHeader::CHUNK_TYPE)
+        .and_then(|c| ImageHeader::try_from(c.data()).ok());
+
+    match decode_chunk(chunk, header.as_ref()) {
+        Some(Ok(values)) => decoded_text(&values),
+        Some(Err(err)) => err,
+        None if is_text(chunk.data()) => {
+            format!("Message: {}", String::from_utf8_lossy(chunk.data()))
+        }
+        None => String::new(),
+    }
+}
+
+/// Creates the summary of the PNG file
+pub fn summarize(png: &Png) -> String {
+    let mut summary = String::new();
+    let header = png
+        .chunk_by_type(ImageHeader::CHUNK_TYPE)
+        .and_then(|c| ImageHeader::try_from(c.data()).ok());
+    let mut image_data_written = false;
+
+    for chunk in png.chunks() {
+        let chunk_type = chunk.chunk_type().to_string();
+        if !chunk.has_valid_crc() {
+            let _ = writeln!(summary, "{}: invalid CRC {:#010x}", chunk_type, chunk.crc());
+        }
+
+        if chunk_type == ImageHeader::CHUNK_TYPE {
+            match ImageHeader::try_from(chunk.data()) {
+                Ok(header) => {
+                    let _ = writeln!(summary, "IHDR width: {}", header.width);
+                    let _ = writeln!(summary, "IHDR height: {}", header.height);
+                    let _ = writeln!(summary, "IHDR bit depth: {}", header.bit_depth);
+                    let _ = writeln!(summary, "IHDR color type: {}", header.color_type);
+                    let _ = writeln!(summary, "IHDR compression: {}", header.compression_method);
+                    let _ = writeln!(summary, "IHDR filter: {}", header.filter_method);
+                    let _ = writeln!(summary, "IHDR interlace: {}", header.interlace_method);
+                }
+                Err(err) => {
+                    let _ = writeln!(summary, "IHDR: {}", err);
+                }
+            }
+            continue;
+        }
+
+        if chunk_type == IMAGE_DATA {
+            // NOTE: image data is hashed as a whole, so that splitting it differently doesn't change the summary
+            if !image_data_written {
+                let image_data: Vec<&Chunk> = png
+                    .chunks()
+                    .iter()
+                    .filter(|c| c.chunk_type().to_string() == IMAGE_DATA)
+                    .collect();
+                let data: Vec<u8> = image_data.iter().flat_map(|c| c.data()).copied().collect();
+                let _ = writeln!(
+                    summary,
+                    "IDAT: {} bytes, sha256 {}",
+                    data.len(),
+                    sha256(&data)
+                );
+                image_data_written = true;
+            }
+            continue;
+        }
+
+        match decode_chunk(chunk, header.as_ref()) {
+            Some(Ok(values)) => {
+                for (label, value) in values {
+                    let label = match label.is_empty() {
+                        true => chunk_type.clone(),
+                        false => format!("{} {}", chunk_type, label),
+                    };
+                    write_value(&mut summary, &label, &value);
+                }
+            }
+            Some(Err(err)) => {
+                let _ = writeln!(summary, "{}: {}", chunk_type, err);
+            }
+            None if chunk.data().is_empty() => {
+                let _ = writeln!(summary, "{}", chunk_type);
+            }
+            None if is_text(chunk.data()) => write_value(
+                &mut summary,
+                &chunk_type,
+                &String::from_utf8_lossy(chunk.data()),
+            ),
+            None => {
+                let _ = writeln!(
+                    summary,
+                    "{}: {} bytes, sha256 {}",
+                    chunk_type,
+                    chunk.data().len(),
+                    sha256(chunk.data())
+                );
+            }
+        }
+    }
+
+    if !png.trailer().is_empty() {
+        let _ = writeln!(
+            summary,
+            "trailer: {} bytes, sha256 {}",
+            png.trailer().len(),
+            sha256(png.trailer())
+        );
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_type::ChunkType;
+    use std::str::FromStr;
+
+    fn chunk(chunk_type: &str, data: Vec<u8>) -> Chunk {
+        Chunk::new(ChunkType::from_str(chunk_type).unwrap(), data)
+    }
+
+    fn testing_png(image_data: Vec<Vec<u8>>) -> Png {
+        let mut chunks = vec![
+            chunk("IHDR", vec![0, 0, 0, 4, 0, 0, 0, 3, 8, 2, 0, 0, 0]),
+            chunk("gAMA", vec![0, 0, 177, 143]),
+            chunk("tEXt", Text::new("Comment", "first\nsecond").as_bytes()),
+        ];
+        chunks.extend(image_data.into_iter().map(|data| chunk("IDAT", data)));
+        chunks.push(chunk("ruSt", b"secret message".to_vec()));
+        chunks.push(chunk("ruSx", vec![0, 1, 2]));
+        chunks.push(chunk("IEND", vec![]));
+        Png::from_chunks(chunks)
+    }
+
+    #[test]
+    fn test_summarize() {
+        let summary = summarize(&testing_png(vec![vec![1, 2, 3]]));
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines[0], "IHDR width: 4");
+        assert!(lines.contains(&"IHDR color type: RGB"));
+        assert!(lines.contains(&"gAMA: Gamma: 0.45455"));
+        assert!(summary.contains("tEXt Comment: first\n  second\n"));
+        assert!(lines.contains(&format!("IDAT: 3 bytes, sha256 {}", sha256(&[1, 2, 3])).as_str()));
+        assert!(lines.contains(&"ruSt: secret message"));
+        assert!(lines.contains(&format!("ruSx: 3 bytes, sha256 {}", sha256(&[0, 1, 2])).as_str()));
+        assert_eq!(lines.last(), Some(&"IEND"));
+    }
+
+    #[test]
+    fn test_summarize_ignores_image_data_split() {
+        assert_eq!(
+            summarize(&testing_png(vec![vec![1, 2, 3]])),
+            summarize(&testing_png(vec![vec![1], vec![2, 3]]))
+        );
+    }
+
+    #[test]
+    fn test_decode_chunk() {
+        let png = testing_png(vec![vec![1, 2, 3]]);
+        let decode = |index: usize| decode_chunk(&png.chunks()[index], None);
+        assert_eq!(
+            decode(2),
+            Some(Ok(vec![(
+                String::from("Comment"),
+                String::from("first\nsecond")
+            )]))
+        );
+        assert_eq!(
+            decode(1),
+            Some(Ok(vec![(String::new(), String::from("Gamma: 0.45455"))]))
+        );
+        assert_eq!(decode(4), None);
+        assert!(matches!(
+            decode_chunk(&chunk("tEXt", vec![0xff; 3]), None),
+            Some(Err(_))
+        ));
+
+        assert_eq!(describe(&png, &png.chunks()[2]), "Comment: first\nsecond");
+        assert_eq!(describe(&png, &png.chunks()[4]), "Message: secret message");
+        assert_eq!(describe(&png, &png.chunks()[5]), "");
+    }
+}
@@ -0,0 +1,348 @@
+/*!
+ * # Text crate
+ *
+ * Defines the textual chunks as specified in [PNG specification](http://www.libpng.org/pub/png/spec/1.2/PNG-Chunks.html#C.Anc-text).
+ *
+ */
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::error;
+use std::fmt;
+use std::io;
+use std::io::{Read, Write};
+use std::string;
+
+const SEPARATOR: u8 = 0;
+const COMPRESSION_METHOD_DEFLATE: u8 = 0;
+/// Maximum size of inflated contents, guarding against decompression bombs
+pub const INFLATE_LIMIT: usize = 64 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum TextError {
+    MissingSeparator,
+    UnknownCompressionMethod(u8),
+    Conversion(string::FromUtf8Error),
+    Compression(io::Error),
+    InflateLimit(usize),
+}
+
+impl From<string::FromUtf8Error> for TextError {
+    fn from(item: string::FromUtf8Error) -> TextError {
+        TextError::Conversion(item)
+    }
+}
+
+impl From<io::Error> for TextError {
+    fn from(item: io::Error) -> TextError {
+        TextError::Compression(item)
+    }
+}
+
+impl fmt::Display for TextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TextError::MissingSeparator => {
+                write!(f, "Missing null separator in the textual chunk data.")
+            }
+            TextError::UnknownCompressionMethod(method) => {
+                write!(f, "Unknown compression method {}.", method)
+            }
+            TextError::Conversion(ref err) => write!(f, "Utf8 error: {}", err),
+            TextError::Compression(ref err) => write!(f, "Compression error: {}", err),
+            TextError::InflateLimit(limit) => {
+                write!(f, "Inflated data exceeds the limit of {} bytes.", limit)
+            }
+        }
+    }
+}
+
+impl error::Error for TextError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            TextError::MissingSeparator => None,
+            TextError::UnknownCompressionMethod(_) => None,
+            TextError::Conversion(ref err) => Some(err),
+            TextError::Compression(ref err) => Some(err),
+            TextError::InflateLimit(_) => None,
+        }
+    }
+}
+
+/// Splits the data at the first null separator, returning the data before and after the separator
+fn split_at_separator(data: &[u8]) -> Result<(&[u8], &[u8]), TextError> {
+    let index = data
+        .iter()
+        .position(|&b| b == SEPARATOR)
+        .ok_or(TextError::MissingSeparator)?;
+    Ok((&data[..index], &data[index + 1..]))
+}
+
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, TextError> {
+    inflate_limited(data, INFLATE_LIMIT)
+}
+
+/// Inflates the data, failing as soon as the inflated data exceeds the limit
+pub fn inflate_limited(data: &[u8], limit: usize) -> Result<Vec<u8>, TextError> {
+    let mut inflated = vec![];
+    ZlibDecoder::new(data)
+        .take(limit as u64 + 1)
+        .read_to_end(&mut inflated)?;
+    if inflated.len() > limit {
+        return Err(TextError::InflateLimit(limit));
+    }
+    Ok(inflated)
+}
+
+pub fn deflate(data: &[u8]) -> Result<Vec<u8>, TextError> {
+    let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+/// Decodes Latin-1 text, in which every byte is a single character
+fn decode_latin1(data: &[u8]) -> String {
+    data.iter().map(|&b| b as char).collect()
+}
+
+/// Encodes Latin-1 text, replacing the characters that aren't part of Latin-1
+fn encode_latin1(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| u8::try_from(c).unwrap_or(b'?'))
+        .collect()
+}
+
+/// Uncompressed Latin-1 textual data, stored in the `tEXt` chunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Text {
+    keyword: String,
+    text: String,
+}
+
+impl Text {
+    pub const CHUNK_TYPE: &'static str = "tEXt";
+
+    pub fn new(keyword: &str, text: &str) -> Text {
+        Text {
+            keyword: keyword.to_owned(),
+            text: text.to_owned(),
+        }
+    }
+
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn as_bytes(&self) -> Vec<u8> {
+        encode_latin1(&self.keyword)
+            .into_iter()
+            .chain([SEPARATOR])
+            .chain(encode_latin1(&self.text))
+            .collect()
+    }
+}
+
+impl TryFrom<&[u8]> for Text {
+    type Error = TextError;
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let (keyword, text) = split_at_separator(value)?;
+        Ok(Text {
+            keyword: decode_latin1(keyword),
+            text: decode_latin1(text),
+        })
+    }
+}
+
+/// Compressed Latin-1 textual data, stored in the `zTXt` chunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompressedText {
+    keyword: String,
+    text: String,
+}
+
+impl CompressedText {
+    pub const CHUNK_TYPE: &'static str = "zTXt";
+
+    pub fn new(keyword: &str, text: &str) -> CompressedText {
+        CompressedText {
+            keyword: keyword.to_owned(),
+            text: text.to_owned(),
+        }
+    }
+
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn as_bytes(&self) -> Result<Vec<u8>, TextError> {
+        Ok(encode_latin1(&self.keyword)
+            .into_iter()
+            .chain([SEPARATOR, COMPRESSION_METHOD_DEFLATE])
+            .chain(deflate(&encode_latin1(&self.text))?)
+            .collect())
+    }
+}
+
+impl TryFrom<&[u8]> for CompressedText {
+    type Error = TextError;
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let (keyword, rest) = split_at_separator(value)?;
+        let text = match rest {
+            [COMPRESSION_METHOD_DEFLATE, text @ ..] => inflate(text)?,
+            [method, ..] => return Err(TextError::UnknownCompressionMethod(*method)),
+            [] => return Err(TextError::MissingSeparator),
+        };
+        Ok(CompressedText {
+            keyword: decode_latin1(keyword),
+            text: decode_latin1(&text),
+        })
+    }
+}
+
+/// International textual data, stored in the `iTXt` chunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InternationalText {
+    keyword: String,
+    compressed: bool,
+    language_tag: String,
+    translated_keyword: String,
+    text: String,
+}
+
+impl InternationalText {
+    pub const CHUNK_TYPE: &'static str = "iTXt";
+
+    pub fn new(keyword: &str, text: &str) -> InternationalText {
+        InternationalText {
+            keyword: keyword.to_owned(),
+            compressed: false,
+            language_tag: String::new(),
+            translated_keyword: String::new(),
+            text: text.to_owned(),
+        }
+    }
+
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_owned();
+    }
+
+    pub fn as_bytes(&self) -> Result<Vec<u8>, TextError> {
+        let text = match self.compressed {
+            true => deflate(self.text.as_bytes())?,
+            false => self.text.as_bytes().to_vec(),
+        };
+
+        Ok(self
+            .keyword
+            .bytes()
+            .chain([SEPARATOR, self.compressed as u8, COMPRESSION_METHOD_DEFLATE])
+            .chain(self.language_tag.bytes())
+            .chain([SEPARATOR])
+            .chain(self.translated_keyword.bytes())
+            .chain([SEPARATOR])
+            .chain(text)
+            .collect())
+    }
+}
+
+impl TryFrom<&[u8]> for InternationalText {
+    type Error = TextError;
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let (keyword, rest) = split_at_separator(value)?;
+        let (flag, method, rest) = match rest {
+            [flag, method, rest @ ..] => (*flag, *method, rest),
+            _ => return Err(TextError::MissingSeparator),
+        };
+        let (language_tag, rest) = split_at_separator(rest)?;
+        let (translated_keyword, text) = split_at_separator(rest)?;
+
+        let compressed = flag != 0;
+        let text = match (compressed, method) {
+            (false, _) => text.to_vec(),
+            (true, COMPRESSION_METHOD_DEFLATE) => inflate(text)?,
+            (true, method) => return Err(TextError::UnknownCompressionMethod(method)),
+        };
+
+        Ok(InternationalText {
+            keyword: String::from_utf8_lossy(keyword).into_owned(),
+            compressed,
+            language_tag: String::from_utf8_lossy(language_tag).into_owned(),
+            translated_keyword: String::from_utf8(translated_keyword.to_vec())?,
+            text: String::from_utf8(text)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_roundtrip() {
+        let text = Text::new("Author", "Jürgen");
+        let bytes = text.as_bytes();
+        assert_eq!(bytes, b"Author\0J\xfcrgen");
+        assert_eq!(Text::try_from(bytes.as_slice()).unwrap(), text);
+        assert!(Text::try_from(&b"Author"[..]).is_err());
+    }
+
+    #[test]
+    fn test_inflate_limit() {
+        let compressed = deflate(&[0; 4096]).unwrap();
+        assert_eq!(inflate_limited(&compressed, 4096).unwrap().len(), 4096);
+        assert!(matches!(
+            inflate_limited(&compressed, 4095),
+            Err(TextError::InflateLimit(4095))
+        ));
+    }
+
+    #[test]
+    fn test_compressed_text_roundtrip() {
+        let text = CompressedText::new("Comment", "compressed text");
+        let bytes = text.as_bytes().unwrap();
+        assert_eq!(&bytes[..9], b"Comment\0\0");
+        assert_eq!(CompressedText::try_from(bytes.as_slice()).unwrap(), text);
+        assert!(CompressedText::try_from(&b"Comment\0\x01"[..]).is_err());
+    }
+
+    #[test]
+    fn test_international_text_roundtrip() {
+        let text = InternationalText::new("Title", "Grüße");
+        let bytes = text.as_bytes().unwrap();
+        assert_eq!(&bytes[..9], b"Title\0\0\0\0");
+        assert_eq!(InternationalText::try_from(bytes.as_slice()).unwrap(), text);
+    }
+
+    #[test]
+    fn test_compressed_international_text() {
+        let mut bytes = b"Comment\0\x01\0en\0Kommentar\0".to_vec();
+        bytes.extend(deflate("compressed text".as_bytes()).unwrap());
+        let text = InternationalText::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(text.keyword(), "Comment");
+        assert_eq!(text.text(), "compressed text");
+        assert_eq!(text.as_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_invalid_international_text() {
+        assert!(InternationalText::try_from(&b"Title"[..]).is_err());
+        assert!(InternationalText::try_from(&b"Title\0\x01\x05\0\0text"[..]).is_err());
+    }
+}
@@ -0,0 +1,306 @@
+/*!
+ * # View crate
+ *
+ * Defines the full screen terminal browser of the chunks of a PNG file. The screen is split into the chunk list, with
+ * the property badges of the chunk types and the CRC status, the decoded data of the selected chunk and the hex and
+ * ASCII listing of its data.
+ *
+ * Drawing is independent of the terminal, so that the browser can be rendered to any backend, e.g. a headless one in tests.
+ *
+ */
+
+use crate::chunk::Chunk;
+use crate::hexdump;
+use crate::png::Png;
+use crate::summary;
+use ratatui::backend::Backend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+use std::io;
+
+const LIST_WIDTH: u16 = 34;
+const DECODED_HEIGHT: u16 = 10;
+const PAGE_LINES: usize = 16;
+const BYTES_PER_LINE: usize = 16;
+const HELP: &str =
+    " C/P/S: critical/public/safe to copy  j/k or arrows: select  PgUp/PgDn: scroll data  q: quit ";
+
+/// State of the chunk browser
+pub struct Viewer {
+    title: String,
+    png: Png,
+    selected: usize,
+    /// First visible line of the hex listing
+    scroll: usize,
+    /// Decoded data of the selected chunk, decoded once when the chunk is selected
+    decoded: String,
+}
+
+impl Viewer {
+    pub fn new(title: String, png: Png) -> Viewer {
+        let mut viewer = Viewer {
+            title,
+            png,
+            selected: 0,
+            scroll: 0,
+            decoded: String::new(),
+        };
+        viewer.select(0);
+        viewer
+    }
+
+    fn selected_chunk(&self) -> Option<&Chunk> {
+        self.png.chunks().get(self.selected)
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index.min(self.png.chunks().len().saturating_sub(1));
+        self.scroll = 0;
+        self.decoded = match self.selected_chunk() {
+            Some(chunk) => summary::describe(&self.png, chunk),
+            None => String::new(),
+        };
+    }
+
+    /// Handles the pressed key, returning whether the browser should quit
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        let lines = self
+            .selected_chunk()
+            .map_or(0, |c| c.data().len().div_ceil(BYTES_PER_LINE));
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Down | KeyCode::Char('j') => self.select(self.selected + 1),
+            KeyCode::Up | KeyCode::Char('k') => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Home | KeyCode::Char('g') => self.select(0),
+            KeyCode::End | KeyCode::Char('G') => self.select(usize::MAX),
+            KeyCode::PageDown => {
+                self.scroll = self
+                    .scroll
+                    .saturating_add(PAGE_LINES)
+                    .min(lines.saturating_sub(1))
+            }
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(PAGE_LINES),
+            _ => {}
+        }
+        false
+    }
+
+    fn badge(set: bool, label: &'static str, color: Color) -> Span<'static> {
+        match set {
+            true => Span::styled(label, Style::default().fg(color)),
+            false => Span::styled(label.to_lowercase(), Style::default().fg(Color::DarkGray)),
+        }
+    }
+
+    fn chunk_item(index: usize, chunk: &Chunk) -> ListItem<'static> {
+        let chunk_type = chunk.chunk_type();
+        let type_style = match chunk.has_valid_crc() {
+            true => Style::default(),
+            false => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        };
+        ListItem::new(Line::from(vec![
+            Span::raw(format!("{:>3} ", index)),
+            Span::styled(chunk_type.to_string(), type_style),
+            Span::raw(" "),
+            Viewer::badge(chunk_type.is_critical(), "C", Color::Yellow),
+            Viewer::badge(chunk_type.is_public(), "P", Color::Cyan),
+            Viewer::badge(chunk_type.is_safe_to_copy(), "S", Color::Green),
+            Span::raw(format!(" {:>10}", chunk.length())),
+            match chunk.has_valid_crc() {
+                true => Span::raw(""),
+                false => Span::styled(" CRC!", Style::default().fg(Color::Red)),
+            },
+        ]))
+    }
+
+    /// Draws the browser on the frame
+    pub fn draw(&self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [list_area, detail] =
+            Layout::horizontal([Constraint::Length(LIST_WIDTH), Constraint::Min(0)]).areas(main);
+        let [decoded_area, hex_area] =
+            Layout::vertical([Constraint::Length(DECODED_HEIGHT), Constraint::Min(0)])
+                .areas(detail);
+
+        let items: Vec<ListItem> = self
+            .png
+            .chunks()
+            .iter()
+            .enumerate()
+            .map(|(index, chunk)| Viewer::chunk_item(index, chunk))
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(" Chunks "))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(list, list_area, &mut state);
+
+        // NOTE: only the visible lines of the listing are formatted, since chunks can be megabytes long
+        let visible_lines = hex_area.height.saturating_sub(2) as usize;
+        let (crc_line, listing) = match self.selected_chunk() {
+            Some(chunk) => (
+                match chunk.has_valid_crc() {
+                    true => Line::styled(
+                        format!("CRC {:#010x} valid", chunk.crc()),
+                        Style::default().fg(Color::Green),
+                    ),
+                    false => Line::styled(
+                        format!("CRC {:#010x} invalid", chunk.crc()),
+                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    ),
+                },
+                {
+                    let data = chunk.data();
+                    let start = (self.scroll * BYTES_PER_LINE).min(data.len());
+                    let end = start
+                        .saturating_add(visible_lines * BYTES_PER_LINE)
+                        .min(data.len());
+                    hexdump::hexdump(&data[start..end], start)
+                },
+            ),
+            None => (Line::raw("No chunks"), String::new()),
+        };
+
+        let mut decoded_lines = vec![crc_line];
+        decoded_lines.extend(self.decoded.lines().map(|line| Line::raw(line.to_owned())));
+        let decoded_title = match self.selected_chunk() {
+            Some(chunk) => format!(" {} ", chunk.chunk_type()),
+            None => String::from(" Decoded "),
+        };
+        frame.render_widget(
+            Paragraph::new(decoded_lines)
+                .block(Block::bordered().title(decoded_title))
+                .wrap(Wrap { trim: false }),
+            decoded_area,
+        );
+
+        frame.render_widget(
+            Paragraph::new(listing).block(Block::bordered().title(" Data ")),
+            hex_area,
+        );
+
+        frame.render_widget(
+            Line::from(vec![
+                Span::styled(
+                    format!(" {} ", self.title),
+                    Style::default().add_modifier(Modifier::REVERSED),
+                ),
+                Span::raw(HELP),
+            ]),
+            status,
+        );
+    }
+
+    /// Draws the browser and handles the key presses until the user quits
+    pub fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && self.handle_key(key.code) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Runs the browser on the terminal, restoring the terminal when it exits
+pub fn run(mut viewer: Viewer) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = viewer.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk_type::ChunkType;
+    use ratatui::backend::TestBackend;
+    use std::str::FromStr;
+
+    fn testing_viewer() -> Viewer {
+        let png = Png::from_chunks(vec![
+            Chunk::new(
+                ChunkType::from_str("IHDR").unwrap(),
+                vec![0, 0, 0, 4, 0, 0, 0, 3, 8, 2, 0, 0, 0],
+            ),
+            Chunk::with_crc(
+                ChunkType::from_str("ruSt").unwrap(),
+                b"hidden message".to_vec(),
+                0,
+            ),
+            Chunk::new(ChunkType::from_str("IEND").unwrap(), vec![]),
+        ]);
+        Viewer::new(String::from("test.png"), png)
+    }
+
+    fn render(viewer: &Viewer) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(120, 24)).unwrap();
+        terminal.draw(|frame| viewer.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_draw_chunk_list() {
+        let lines = render(&testing_viewer());
+        assert!(lines.iter().any(|l| l.contains("0 IHDR CPs")));
+        assert!(lines
+            .iter()
+            .any(|l| l.contains("1 ruSt cpS") && l.contains("CRC!")));
+        assert!(lines
+            .iter()
+            .any(|l| l.contains("4x3, bit depth 8, color type RGB")));
+        assert!(lines.iter().any(|l| l.contains("00000000  00 00 00 04")));
+        assert!(lines.last().unwrap().contains("test.png"));
+    }
+
+    #[test]
+    fn test_select_chunk() {
+        let mut viewer = testing_viewer();
+        assert!(!viewer.handle_key(KeyCode::Down));
+        let lines = render(&viewer);
+        assert!(lines.iter().any(|l| l.contains("CRC 0x00000000 invalid")));
+        assert!(lines.iter().any(|l| l.contains("Message: hidden message")));
+        assert!(lines.iter().any(|l| l.contains("|hidden message|")));
+
+        viewer.handle_key(KeyCode::End);
+        assert_eq!(viewer.selected, 2);
+        viewer.handle_key(KeyCode::Down);
+        assert_eq!(viewer.selected, 2);
+        assert!(viewer.handle_key(KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn test_scroll_large_chunk() {
+        let png = Png::from_chunks(vec![Chunk::new(
+            ChunkType::from_str("IDAT").unwrap(),
+            vec![0xab; 1 << 20],
+        )]);
+        let mut viewer = Viewer::new(String::from("large.png"), png);
+        viewer.handle_key(KeyCode::PageDown);
+        assert_eq!(viewer.scroll, PAGE_LINES);
+        let lines = render(&viewer);
+        assert!(lines.iter().any(|l| l.contains("00000100  ab ab")));
+        assert!(!lines.iter().any(|l| l.contains("00000000  ab ab")));
+
+        for _ in 0..(1 << 16) / PAGE_LINES + 1 {
+            viewer.handle_key(KeyCode::PageDown);
+        }
+        assert_eq!(viewer.scroll, (1 << 16) - 1);
+        let lines = render(&viewer);
+        assert!(lines.iter().any(|l| l.contains("000ffff0  ab ab")));
+    }
+}
@@ -0,0 +1,357 @@
+/*!
+ * # XMP crate
+ *
+ * Defines the editing of the [XMP](https://www.adobe.com/devnet/xmp.html) packet that is stored in an `iTXt` chunk with the
+ * `XML:com.adobe.xmp` keyword.
+ *
+ * The packet is edited as text, without a full XML parser. Properties are merged into the first `rdf:Description` element
+ * and the well known Dublin Core properties are written with the RDF container they are specified with.
+ *
+ */
+
+use std::error;
+use std::fmt;
+
+const DESCRIPTION_START: &str = "<rdf:Description";
+const DESCRIPTION_END: &str = "</rdf:Description>";
+
+/// Namespaces of the well known property prefixes
+const NAMESPACES: [(&str, &str); 6] = [
+    ("dc", "http://purl.org/dc/elements/1.1/"),
+    ("xmp", "http://ns.adobe.com/xap/1.0/"),
+    ("xmpRights", "http://ns.adobe.com/xap/1.0/rights/"),
+    ("photoshop", "http://ns.adobe.com/photoshop/1.0/"),
+    (
+        "Iptc4xmpCore",
+        "http://iptc.org/std/Iptc4xmpCore/1.0/xmlns/",
+    ),
+    ("tiff", "http://ns.adobe.com/tiff/1.0/"),
+];
+
+/// Properties stored as an ordered array
+const SEQ_PROPERTIES: [&str; 2] = ["dc:creator", "dc:date"];
+/// Properties stored as an unordered array
+const BAG_PROPERTIES: [&str; 5] = [
+    "dc:subject",
+    "dc:publisher",
+    "dc:contributor",
+    "dc:language",
+    "dc:type",
+];
+/// Properties stored as a language alternative
+const ALT_PROPERTIES: [&str; 3] = ["dc:rights", "dc:title", "dc:description"];
+
+#[derive(Debug)]
+pub enum XmpError {
+    InvalidPropertyName(String),
+    UnknownNamespace(String),
+    MissingDescription,
+}
+
+impl fmt::Display for XmpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            XmpError::InvalidPropertyName(ref name) => write!(
+                f,
+                "Invalid property name {}, expected a prefixed name such as dc:creator.",
+                name
+            ),
+            XmpError::UnknownNamespace(ref prefix) => write!(
+                f,
+                "Namespace of the prefix {} is not declared in the XMP packet.",
+                prefix
+            ),
+            XmpError::MissingDescription => {
+                write!(f, "XMP packet doesn't contain an rdf:Description element.")
+            }
+        }
+    }
+}
+
+impl error::Error for XmpError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            XmpError::InvalidPropertyName(_) => None,
+            XmpError::UnknownNamespace(_) => None,
+            XmpError::MissingDescription => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Xmp {
+    packet: String,
+}
+
+impl Xmp {
+    /// Keyword of the `iTXt` chunk that stores the XMP packet
+    pub const KEYWORD: &'static str = "XML:com.adobe.xmp";
+
+    pub fn new(packet: &str) -> Xmp {
+        Xmp {
+            packet: packet.to_owned(),
+        }
+    }
+
+    /// Creates an XMP packet without any properties
+    pub fn empty() -> Xmp {
+        Xmp::new(concat!(
+            "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n",
+            "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n",
+            " <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n",
+            "  <rdf:Description rdf:about=\"\">\n",
+            "  </rdf:Description>\n",
+            " </rdf:RDF>\n",
+            "</x:xmpmeta>\n",
+            "<?xpacket end=\"w\"?>"
+        ))
+    }
+
+    pub fn packet(&self) -> &str {
+        &self.packet
+    }
+
+    /// Sets the value of a property, replacing any previous value of the property
+    pub fn set_property(&mut self, name: &str, value: &str) -> Result<(), XmpError> {
+        let prefix = match name.split_once(':') {
+            Some((prefix, local)) if !prefix.is_empty() && !local.is_empty() => prefix,
+            _ => return Err(XmpError::InvalidPropertyName(name.to_owned())),
+        };
+        if name.contains(|c: char| c.is_whitespace() || "<>\"'=/".contains(c)) {
+            return Err(XmpError::InvalidPropertyName(name.to_owned()));
+        }
+
+        self.remove_property(name);
+        self.expand_description()?;
+
+        let description = self
+            .packet
+            .find(DESCRIPTION_START)
+            .ok_or(XmpError::MissingDescription)?;
+        let start_tag_end = description
+            + self.packet[description..]
+                .find('>')
+                .ok_or(XmpError::MissingDescription)?;
+
+        if !self.packet[description..start_tag_end].contains(&format!("xmlns:{}=", prefix)) {
+            let namespace = NAMESPACES
+                .iter()
+                .find(|(p, _)| *p == prefix)
+                .ok_or(XmpError::UnknownNamespace(prefix.to_owned()))?
+                .1;
+            self.packet.insert_str(
+                start_tag_end,
+                &format!("\n    xmlns:{}=\"{}\"", prefix, namespace),
+            );
+        }
+
+        let end = self
+            .packet
+            .find(DESCRIPTION_END)
+            .ok_or(XmpError::MissingDescription)?;
+        let line_start = self.packet[..end].rfind('\n').map_or(end, |i| i + 1);
+        let insert_at = if self.packet[line_start..end].trim().is_empty() {
+            line_start
+        } else {
+            end
+        };
+        self.packet
+            .insert_str(insert_at, &Xmp::property_element(name, value));
+
+        Ok(())
+    }
+
+    fn property_element(name: &str, value: &str) -> String {
+        let value = escape(value);
+        let container = if SEQ_PROPERTIES.contains(&name) {
+            Some(("rdf:Seq", "<rdf:li>"))
+        } else if BAG_PROPERTIES.contains(&name) {
+            Some(("rdf:Bag", "<rdf:li>"))
+        } else if ALT_PROPERTIES.contains(&name) {
+            Some(("rdf:Alt", "<rdf:li xml:lang=\"x-default\">"))
+        } else {
+            None
+        };
+
+        match container {
+            Some((container, item)) => format!(
+                "   <{name}>\n    <{container}>\n     {item}{value}</rdf:li>\n    </{container}>\n   </{name}>\n",
+            ),
+            None => format!("   <{name}>{value}</{name}>\n"),
+        }
+    }
+
+    /// Converts a self closing `rdf:Description` element to an element with a separate end tag
+    fn expand_description(&mut self) -> Result<(), XmpError> {
+        let description = self
+            .packet
+            .find(DESCRIPTION_START)
+            .ok_or(XmpError::MissingDescription)?;
+        let start_tag_end = description
+            + self.packet[description..]
+                .find('>')
+                .ok_or(XmpError::MissingDescription)?;
+
+        if self.packet[..start_tag_end].ends_with('/') {
+            self.packet.replace_range(
+                start_tag_end - 1..start_tag_end + 1,
+                &format!(">\n  {}", DESCRIPTION_END),
+            );
+        }
+        Ok(())
+    }
+
+    /// Removes the property, whether it is stored as an element or as an attribute
+    fn remove_property(&mut self, name: &str) {
+        let open = format!("<{}", name);
+        let close = format!("</{}>", name);
+        let mut search_from = 0;
+        while let Some(offset) = self.packet[search_from..].find(&open) {
+            let start = search_from + offset;
+            let after_name = self.packet[start + open.len()..].chars().next();
+            if !matches!(after_name, Some('>' | '/' | ' ' | '\n' | '\r' | '\t')) {
+                search_from = start + open.len();
+                continue;
+            }
+
+            let tag_end = match self.packet[start..].find('>') {
+                Some(i) => start + i + 1,
+                None => return,
+            };
+            let end = if self.packet[..tag_end].ends_with("/>") {
+                tag_end
+            } else {
+                match self.packet[tag_end..].find(&close) {
+                    Some(i) => tag_end + i + close.len(),
+                    None => return,
+                }
+            };
+
+            let line_start = self.packet[..start].rfind('\n').map_or(start, |i| i + 1);
+            let (start, end) = if self.packet[line_start..start].trim().is_empty()
+                && self.packet[end..].starts_with('\n')
+            {
+                (line_start, end + 1)
+            } else {
+                (start, end)
+            };
+            self.packet.replace_range(start..end, "");
+            search_from = start;
+        }
+
+        let attribute = format!("{}=\"", name);
+        let mut search_from = 0;
+        while let Some(offset) = self.packet[search_from..].find(&attribute) {
+            let start = search_from + offset;
+            let preceded_by_space = self.packet[..start]
+                .chars()
+                .last()
+                .is_some_and(char::is_whitespace);
+            let value_start = start + attribute.len();
+            match (preceded_by_space, self.packet[value_start..].find('"')) {
+                (true, Some(i)) => {
+                    let whitespace =
+                        self.packet[..start].len() - self.packet[..start].trim_end().len();
+                    self.packet
+                        .replace_range(start - whitespace..value_start + i + 1, "");
+                    search_from = start - whitespace;
+                }
+                _ => search_from = value_start,
+            }
+        }
+    }
+}
+
+/// Escapes the characters that have a special meaning in XML
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_property_on_empty_packet() {
+        let mut xmp = Xmp::empty();
+        xmp.set_property("dc:creator", "Alice").unwrap();
+        xmp.set_property("dc:rights", "CC BY 4.0 & more").unwrap();
+        xmp.set_property("xmp:Rating", "5").unwrap();
+
+        let packet = xmp.packet();
+        assert!(packet.contains("xmlns:dc=\"http://purl.org/dc/elements/1.1/\""));
+        assert!(packet.contains("<rdf:Seq>\n     <rdf:li>Alice</rdf:li>"));
+        assert!(packet.contains("<rdf:li xml:lang=\"x-default\">CC BY 4.0 &amp; more</rdf:li>"));
+        assert!(packet.contains("<xmp:Rating>5</xmp:Rating>"));
+        assert!(packet.find("xmp:Rating").unwrap() < packet.find(DESCRIPTION_END).unwrap());
+    }
+
+    #[test]
+    fn test_set_property_replaces_element() {
+        let mut xmp = Xmp::empty();
+        xmp.set_property("dc:creator", "Alice").unwrap();
+        xmp.set_property("dc:creator", "Bob").unwrap();
+
+        assert!(!xmp.packet().contains("Alice"));
+        assert_eq!(xmp.packet().matches("<dc:creator>").count(), 1);
+        assert!(xmp.packet().contains("<rdf:li>Bob</rdf:li>"));
+    }
+
+    #[test]
+    fn test_set_property_replaces_attribute() {
+        let mut xmp = Xmp::new(
+            "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"><rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\
+             <rdf:Description rdf:about=\"\" xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\" xmp:Rating=\"1\"/>\
+             </rdf:RDF></x:xmpmeta>",
+        );
+        xmp.set_property("xmp:Rating", "3").unwrap();
+
+        assert!(!xmp.packet().contains("xmp:Rating=\"1\""));
+        assert!(xmp.packet().contains("<xmp:Rating>3</xmp:Rating>"));
+        assert!(xmp.packet().contains(DESCRIPTION_END));
+    }
+
+    #[test]
+    fn test_set_property_keeps_similar_names() {
+        let mut xmp = Xmp::empty();
+        xmp.set_property("xmp:Rating", "1").unwrap();
+        xmp.set_property("xmp:RatingPercent", "20").unwrap();
+        xmp.set_property("xmp:Rating", "2").unwrap();
+
+        assert!(xmp
+            .packet()
+            .contains("<xmp:RatingPercent>20</xmp:RatingPercent>"));
+        assert!(xmp.packet().contains("<xmp:Rating>2</xmp:Rating>"));
+    }
+
+    #[test]
+    fn test_set_property_declares_namespace_on_description() {
+        let mut xmp = Xmp::new(
+            "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"><rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\
+             <rdf:Description rdf:about=\"\">\n</rdf:Description>\
+             <rdf:Description rdf:about=\"\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\"/>\
+             </rdf:RDF></x:xmpmeta>",
+        );
+        xmp.set_property("dc:creator", "Alice").unwrap();
+
+        let description = xmp.packet().find(DESCRIPTION_START).unwrap();
+        let start_tag = &xmp.packet()
+            [description..description + xmp.packet()[description..].find('>').unwrap()];
+        assert!(start_tag.contains("xmlns:dc=\"http://purl.org/dc/elements/1.1/\""));
+        assert_eq!(xmp.packet().matches("xmlns:dc=").count(), 2);
+    }
+
+    #[test]
+    fn test_invalid_property() {
+        let mut xmp = Xmp::empty();
+        assert!(xmp.set_property("creator", "Alice").is_err());
+        assert!(xmp.set_property("foo:bar", "Alice").is_err());
+        assert!(Xmp::new("<x:xmpmeta/>")
+            .set_property("dc:creator", "Alice")
+            .is_err());
+    }
+}